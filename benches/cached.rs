@@ -0,0 +1,68 @@
+// benches/cached.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Measures how the reads of a `CachedResolver` scale with the number of threads. Run it with
+//! `cargo bench --bench cached`. The throughput per thread should stay roughly constant up to the
+//! number of cores.
+
+use std::{
+    hint::black_box,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+use homedir::{CachedResolver, GetHomeError, HomeProvider, UserIdentifier};
+
+/// The number of distinct users looked up by each thread.
+const USERS: usize = 256;
+
+/// The number of lookups done by each thread.
+const LOOKUPS: usize = 1_000_000;
+
+/// A provider which answers immediately, so that only the cache is measured.
+struct Fixed;
+
+impl HomeProvider for Fixed {
+    fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+        Ok(Some(PathBuf::from("/home").join(username)))
+    }
+
+    fn home_of(&self, _id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+        Ok(None)
+    }
+
+    fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        Ok(Some(PathBuf::from("/home/me")))
+    }
+}
+
+fn main() {
+    let usernames = (0..USERS).map(|i| format!("user{i}")).collect::<Vec<_>>();
+    let max_threads = thread::available_parallelism().map_or(4, usize::from) * 2;
+    let mut threads = 1;
+    while threads <= max_threads {
+        let resolver = CachedResolver::with_provider(Fixed, Duration::from_secs(3600));
+        let start = Instant::now();
+        thread::scope(|s| {
+            for t in 0..threads {
+                let (resolver, usernames) = (&resolver, &usernames);
+                s.spawn(move || {
+                    for i in 0..LOOKUPS {
+                        let username = &usernames[(i + t * 17) % USERS];
+                        black_box(resolver.home(username).unwrap());
+                    }
+                });
+            }
+        });
+        let elapsed = start.elapsed();
+        let rate = (threads * LOOKUPS) as f64 / elapsed.as_secs_f64() / 1e6;
+        println!(
+            "{threads:>3} threads: {rate:>8.1} M lookups/s, {:>6.1} M lookups/s per thread",
+            rate / threads as f64,
+        );
+        threads *= 2;
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * include/homedir.h
+ *
+ * Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+ * Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+ *
+ * The C API of the homedir crate, exported when it is built with the `ffi` feature, for instance
+ * with `cargo rustc --release --features ffi --crate-type cdylib`. This must be kept in sync with
+ * src/ffi.rs.
+ */
+
+#ifndef HOMEDIR_H
+#define HOMEDIR_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The result of a function of this API. */
+typedef enum HomedirStatus {
+    /* The home directory was found, and stored in *out. */
+    HOMEDIR_OK = 0,
+    /* The user or their home directory was not found. */
+    HOMEDIR_NOT_FOUND = 1,
+    /* A pointer was null, or the username or identifier was malformed. */
+    HOMEDIR_INVALID_ARGUMENT = 2,
+    /* The lookup is not supported on this target or with the enabled features. */
+    HOMEDIR_UNSUPPORTED = 3,
+    /* The home directory cannot be represented as a C string. */
+    HOMEDIR_ENCODING = 4,
+    /* The lookup failed, or panicked. */
+    HOMEDIR_ERROR = 5
+} HomedirStatus;
+
+/*
+ * The functions below store the home directory they found in *out as a NUL-terminated string,
+ * which must be freed with homedir_free, or a null pointer if none was found. On Unix, the
+ * strings are in the encoding of the system. On Windows, they are UTF-8.
+ */
+
+/* Get the home directory of the process' current user. */
+HomedirStatus homedir_my_home(char **out);
+
+/* Get the home directory of an arbitrary user given their username. */
+HomedirStatus homedir_home(const char *username, char **out);
+
+/*
+ * Get the home directory of the user with the given identifier: a decimal user id on Unix, or
+ * the text representation of a SID on Windows.
+ */
+HomedirStatus homedir_home_of(const char *id, char **out);
+
+/* Free a string returned by this API. Passing a null pointer does nothing. */
+void homedir_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HOMEDIR_H */
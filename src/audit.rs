@@ -0,0 +1,146 @@
+// src/audit.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A process-wide hook called on the lookups of arbitrary users, see [`set_audit_hook`].
+
+use std::{
+    cell::Cell,
+    ffi::OsStr,
+    fmt,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use crate::{Backend, LookupOutcome, UserIdentifier};
+
+/// The hook set with [`set_audit_hook`].
+static HOOK: RwLock<Option<Arc<dyn AuditHook>>> = RwLock::new(None);
+
+thread_local! {
+    /// Whether the current thread is running the hook, whose own lookups are not audited.
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A hook called each time this crate looks up an arbitrary user, so that programs which must
+/// account for the accesses to other users' account data can record them centrally, see
+/// [`set_audit_hook`]. It is implemented for closures.
+///
+/// The hook is called on the thread doing the lookup, after it completes, so it should return
+/// quickly. The lookups it does itself are not audited.
+pub trait AuditHook: Send + Sync {
+    /// Called after a user is looked up.
+    fn audit(&self, record: &AuditRecord<'_>);
+}
+
+impl<F: Fn(&AuditRecord<'_>) + Send + Sync> AuditHook for F {
+    fn audit(&self, record: &AuditRecord<'_>) {
+        self(record);
+    }
+}
+
+impl fmt::Debug for dyn AuditHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuditHook")
+    }
+}
+
+/// The user who was looked up, see [`AuditRecord::user`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuditedUser<'a> {
+    /// A user given by their username, as with [`home`](crate::home).
+    Username(&'a OsStr),
+    /// A user given by their identifier, as with [`UserIdentifier::to_home`].
+    Id(&'a UserIdentifier),
+    /// The users whose username matches a pattern, as with [`find_users`](crate::find_users).
+    Pattern(&'a str),
+}
+
+/// A lookup of an arbitrary user, passed to [`AuditHook::audit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AuditRecord<'a> {
+    user: AuditedUser<'a>,
+    backend: Option<Backend>,
+    outcome: LookupOutcome,
+}
+
+impl<'a> AuditRecord<'a> {
+    /// The user who was looked up.
+    pub fn user(&self) -> AuditedUser<'a> {
+        self.user
+    }
+
+    /// The backend which was queried by a [`HomeResolver`](crate::HomeResolver), or `None` for
+    /// the functions of this crate which do not take a backend, such as [`home`](crate::home),
+    /// and which query the user database as described in their documentation.
+    pub fn backend(&self) -> Option<Backend> {
+        self.backend
+    }
+
+    /// Whether the user was found. When users are looked up by a pattern, they are
+    /// [`Found`](LookupOutcome::Found) if at least one matched.
+    pub fn outcome(&self) -> LookupOutcome {
+        self.outcome
+    }
+}
+
+/// Set the hook called each time an arbitrary user is looked up, replacing the previous one.
+///
+/// The hook is called by the functions which look up a user given their username or identifier,
+/// such as [`home`](crate::home), [`user_info`](crate::user_info),
+/// [`UserIdentifier::with_username`], and [`UserIdentifier::to_home`], by
+/// [`find_users`](crate::find_users), and for each backend queried by
+/// [`HomeResolver::home`](crate::HomeResolver::home). The functions built on these, such as
+/// [`homes`](crate::homes) and [`home_existing`](crate::home_existing), call it through them, so a
+/// lookup may be reported more than once. The home directories found in a cache, such as that of a
+/// [`CachedResolver`](crate::CachedResolver), are not reported, and neither are the lookups of a
+/// [`HomeProvider`](crate::HomeProvider) which does not use this crate. The lookups of the current
+/// user by [`my_home`](crate::my_home) are not reported either, but those through
+/// [`UserIdentifier::my_id`] are, as they are indistinguishable from those of other users.
+///
+/// # Example
+/// ```no_run
+/// use homedir::{home, set_audit_hook, AuditedUser};
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// set_audit_hook(|record: &homedir::AuditRecord<'_>| {
+///     if let AuditedUser::Username(username) = record.user() {
+///         eprintln!("audit: looked up {username:?}: {:?}", record.outcome());
+///     }
+/// });
+/// home("alice")?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn set_audit_hook<H: AuditHook + 'static>(hook: H) {
+    *HOOK.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(hook));
+}
+
+/// Remove the hook set with [`set_audit_hook`].
+pub fn remove_audit_hook() {
+    *HOOK.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Report a lookup to the hook, if there is one.
+pub(crate) fn audit(user: AuditedUser<'_>, backend: Option<Backend>, outcome: LookupOutcome) {
+    // the lock is not held while the hook runs, so that it can replace itself.
+    let Some(hook) = HOOK.read().unwrap_or_else(PoisonError::into_inner).clone() else {
+        return;
+    };
+    if IN_HOOK.with(|v| v.replace(true)) {
+        return;
+    }
+    /// Clears the flag, including if the hook panics.
+    struct Reset;
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            IN_HOOK.with(|v| v.set(false));
+        }
+    }
+    let _reset = Reset;
+    hook.audit(&AuditRecord {
+        user,
+        backend,
+        outcome,
+    });
+}
@@ -0,0 +1,90 @@
+// src/bindings.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The bindings of the crate for Kotlin, Swift, and the other languages supported by
+//! [UniFFI](https://mozilla.github.io/uniffi-rs/), enabled by the `uniffi` feature.
+//!
+//! As these languages have no type for the paths of the system, the home directories are
+//! returned as strings, and a [`HomeError::NotUnicode`] error is returned if one is not valid
+//! Unicode.
+
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use crate::GetHomeError;
+
+/// The error returned by the bindings.
+#[derive(Debug, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum HomeError {
+    /// The lookup is not supported on this target or with the enabled features.
+    Unsupported,
+    /// The home directory is not valid Unicode.
+    NotUnicode,
+    /// The lookup failed.
+    Failed(GetHomeError),
+}
+
+impl fmt::Display for HomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => f.write_str("the lookup is not supported on this target"),
+            Self::NotUnicode => f.write_str("the home directory is not valid Unicode"),
+            Self::Failed(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl From<GetHomeError> for HomeError {
+    fn from(value: GetHomeError) -> Self {
+        if value.is_unsupported() {
+            Self::Unsupported
+        } else {
+            Self::Failed(value)
+        }
+    }
+}
+
+/// The identifier of a user, see [`crate::UserIdentifier`].
+#[derive(Debug, uniffi::Object)]
+pub struct UserIdentifier(crate::UserIdentifier);
+
+#[uniffi::export]
+impl UserIdentifier {
+    /// Get the identifier of the process' current user.
+    #[uniffi::constructor]
+    pub fn current() -> Result<Arc<Self>, HomeError> {
+        Ok(Arc::new(Self(crate::UserIdentifier::my_id()?)))
+    }
+
+    /// Get the home directory of this user.
+    pub fn to_home(&self) -> Result<Option<String>, HomeError> {
+        to_string(self.0.to_home()?)
+    }
+}
+
+/// Get the home directory of the process' current user.
+#[uniffi::export]
+pub fn my_home() -> Result<Option<String>, HomeError> {
+    to_string(crate::my_home()?)
+}
+
+/// Get the home directory of an arbitrary user given their username.
+#[uniffi::export]
+pub fn home(username: String) -> Result<Option<String>, HomeError> {
+    to_string(crate::home(username)?)
+}
+
+/// Get the identifier of a user given their username.
+#[uniffi::export]
+pub fn user_identifier(username: String) -> Result<Option<Arc<UserIdentifier>>, HomeError> {
+    Ok(crate::UserIdentifier::with_username(username)?.map(|v| Arc::new(UserIdentifier(v))))
+}
+
+/// Convert a home directory into a string.
+fn to_string(home: Option<PathBuf>) -> Result<Option<String>, HomeError> {
+    home.map(|v| v.into_os_string().into_string())
+        .transpose()
+        .map_err(|_| HomeError::NotUnicode)
+}
@@ -0,0 +1,87 @@
+// src/bulk.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Lookups of the home directories of many users at once, see [`homes`] and [`homes_parallel`].
+
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::{home, GetHomeError};
+
+/// Get the home directories of many users given their usernames. The results are in the same
+/// order as the usernames, and each of them is that of [`home`]. A failed lookup does not stop
+/// the others.
+///
+/// # Example
+/// ```no_run
+/// use homedir::homes;
+///
+/// let usernames = ["root", "alice", "bob"];
+/// for (name, home) in usernames.iter().zip(homes(&usernames)) {
+///     println!("{name}: {home:?}");
+/// }
+/// ```
+pub fn homes<S: AsRef<str>>(usernames: &[S]) -> Vec<Result<Option<PathBuf>, GetHomeError>> {
+    usernames.iter().map(home).collect()
+}
+
+/// Get the home directories of many users given their usernames, like [`homes`], with up to the
+/// given number of lookups running at once on separate threads.
+///
+/// When the users are provided by a directory service such as LDAP, most of the time of a lookup
+/// is spent waiting for the server, so running them in parallel is much faster. The threads are
+/// started for this call, and stopped before it returns. If `workers` is zero or one, or if there
+/// is a single username, the lookups run on the current thread.
+///
+/// # Example
+/// ```no_run
+/// use homedir::homes_parallel;
+///
+/// let usernames = (0..1000).map(|i| format!("user{i}")).collect::<Vec<_>>();
+/// let found = homes_parallel(&usernames, 16)
+///     .into_iter()
+///     .filter(|v| matches!(v, Ok(Some(_))))
+///     .count();
+/// println!("{found} users found");
+/// ```
+pub fn homes_parallel<S: AsRef<str> + Sync>(
+    usernames: &[S],
+    workers: usize,
+) -> Vec<Result<Option<PathBuf>, GetHomeError>> {
+    let workers = workers.min(usernames.len());
+    if workers <= 1 {
+        return homes(usernames);
+    }
+    // each worker takes the next username which has not been looked up.
+    let next = AtomicUsize::new(0);
+    let mut results = thread::scope(|s| {
+        let handles = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut found = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(username) = usernames.get(i) else {
+                            return found;
+                        };
+                        found.push((i, home(username)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|v| match v.join() {
+                Ok(v) => v,
+                Err(e) => std::panic::resume_unwind(e),
+            })
+            .collect::<Vec<_>>()
+    });
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, v)| v).collect()
+}
@@ -0,0 +1,358 @@
+// src/cached.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A cache of home directories whose entries expire, see [`CachedResolver`].
+
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::BuildHasher,
+    path::PathBuf,
+    sync::{PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+use crate::observer::observe;
+use crate::{GetHomeError, HomeProvider, LookupObserver, SystemProvider, UserIdentifier};
+
+/// The number of entries a [`CachedResolver`] holds by default.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// The largest number of shards the entries of a [`CachedResolver`] are split into.
+const SHARDS: usize = 16;
+
+/// A lookup which remembers the home directories it found for a limited time, for programs
+/// such as servers which look up the same users many times, and should not query the C library
+/// or the Windows Management Instrumentation every time.
+///
+/// The users who were not found are remembered as well, but errors are not. When the cache is
+/// full, the expired entries are removed, then the oldest ones.
+///
+/// The entries are split into shards, each behind its own read-write lock, so that many threads,
+/// such as the request handlers of a server, can read the cache at once, and a lookup only
+/// blocks the threads which use the same shard while its result is inserted. The capacity is
+/// divided evenly between the shards, of which there are at most 16, and never more than the
+/// capacity.
+///
+/// The lookups are done by [`SystemProvider`] by default, or by any [`HomeProvider`], such as a
+/// [`HomeResolver`](crate::HomeResolver).
+///
+/// # Example
+/// ```no_run
+/// use homedir::CachedResolver;
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// let resolver = CachedResolver::new(Duration::from_secs(60)).capacity(100);
+/// for _ in 0..1000 {
+///     // only the first of these looks up the user.
+///     println!("{:?}", resolver.home("alice")?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CachedResolver<P = SystemProvider> {
+    provider: P,
+    ttl: Duration,
+    capacity: usize,
+    hasher: RandomState,
+    shards: Box<[RwLock<HashMap<Key, Entry>>]>,
+    observer: Option<Box<dyn LookupObserver>>,
+}
+
+/// The user whose home directory is cached.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Current,
+    Name(String),
+    Id(UserIdentifier),
+}
+
+/// A cached home directory.
+#[derive(Debug)]
+struct Entry {
+    home: Option<PathBuf>,
+    inserted: Instant,
+}
+
+impl CachedResolver {
+    /// Construct a cache whose entries expire after the given time, with the lookups of
+    /// [`SystemProvider`].
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_provider(SystemProvider, ttl)
+    }
+}
+
+impl<P: HomeProvider> CachedResolver<P> {
+    /// Construct a cache whose entries expire after the given time, with the lookups of the
+    /// given provider.
+    pub fn with_provider(provider: P, ttl: Duration) -> Self {
+        Self {
+            provider,
+            ttl,
+            capacity: DEFAULT_CAPACITY,
+            hasher: RandomState::new(),
+            shards: new_shards(DEFAULT_CAPACITY),
+            observer: None,
+        }
+    }
+
+    /// Set the maximum number of entries, which is 1024 by default. With a capacity of zero,
+    /// nothing is cached. The cache never holds more entries than this, but as each shard holds
+    /// an equal part of it, rounded down, it may be full before it holds this many. Setting the
+    /// capacity removes all the entries.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.shards = new_shards(capacity);
+        self
+    }
+
+    /// Set a hook called whenever the cache is checked, and after each lookup of the provider,
+    /// see [`LookupObserver`].
+    pub fn observer<O: LookupObserver + 'static>(mut self, observer: O) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// The provider which does the lookups.
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Remove all the entries.
+    pub fn clear(&self) {
+        for shard in &*self.shards {
+            shard
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clear();
+        }
+    }
+
+    /// Remove the entry of a user given their username, so that the next lookup of this
+    /// username queries the provider.
+    pub fn invalidate<S: AsRef<str>>(&self, username: S) {
+        self.remove(&Key::Name(username.as_ref().to_owned()));
+    }
+
+    /// Remove the entry of the user with the given identifier.
+    pub fn invalidate_id(&self, id: &UserIdentifier) {
+        self.remove(&Key::Id(id.clone()));
+    }
+
+    /// Remove the entry of the process' current user.
+    pub fn invalidate_my_home(&self) {
+        self.remove(&Key::Current);
+    }
+
+    /// Look up all the users of the cache again, and reset the time at which they expire. If a
+    /// lookup fails, the entry of the user is removed, and the first error is returned after the
+    /// other users are looked up.
+    pub fn refresh(&self) -> Result<(), GetHomeError> {
+        let keys = self
+            .shards
+            .iter()
+            .flat_map(|v| {
+                let shard = v.read().unwrap_or_else(PoisonError::into_inner);
+                shard.keys().cloned().collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut ret = Ok(());
+        for key in keys {
+            // the lock is not held during the lookups, which may be slow.
+            let home = match &key {
+                Key::Current => self.provider.my_home(),
+                Key::Name(username) => self.provider.home(username),
+                Key::Id(id) => self.provider.home_of(id),
+            };
+            let mut entries = self
+                .shard(&key)
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
+            match home {
+                Ok(home) => {
+                    entries.insert(
+                        key,
+                        Entry {
+                            home,
+                            inserted: Instant::now(),
+                        },
+                    );
+                }
+                Err(e) => {
+                    entries.remove(&key);
+                    if ret.is_ok() {
+                        ret = Err(e);
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Get the home directory of an arbitrary user given their username.
+    pub fn home<S: AsRef<str>>(&self, username: S) -> Result<Option<PathBuf>, GetHomeError> {
+        let username = username.as_ref();
+        self.cached(Key::Name(username.to_owned()), || {
+            self.provider.home(username)
+        })
+    }
+
+    /// Get the home directory of the user with the given identifier.
+    pub fn home_of(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+        self.cached(Key::Id(id.clone()), || self.provider.home_of(id))
+    }
+
+    /// Get the home directory of the process' current user.
+    pub fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        self.cached(Key::Current, || self.provider.my_home())
+    }
+
+    /// The shard which holds the entry of a user.
+    fn shard(&self, key: &Key) -> &RwLock<HashMap<Key, Entry>> {
+        &self.shards[self.hasher.hash_one(key) as usize % self.shards.len()]
+    }
+
+    /// Remove an entry.
+    fn remove(&self, key: &Key) {
+        self.shard(key)
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key);
+    }
+
+    /// Get a home directory from the cache, or look it up if it is missing or expired.
+    fn cached(
+        &self,
+        key: Key,
+        lookup: impl FnOnce() -> Result<Option<PathBuf>, GetHomeError>,
+    ) -> Result<Option<PathBuf>, GetHomeError> {
+        let shard = self.shard(&key);
+        let cached = shard
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .filter(|v| v.inserted.elapsed() < self.ttl)
+            .map(|v| v.home.clone());
+        if let Some(observer) = &self.observer {
+            observer.cache(cached.is_some());
+        }
+        if let Some(home) = cached {
+            return Ok(home);
+        }
+        // the lock is not held during the lookup, which may be slow.
+        let start = Instant::now();
+        let home = lookup();
+        observe(
+            self.observer.as_deref(),
+            None,
+            key == Key::Current,
+            start.elapsed(),
+            &home,
+        );
+        let home = home?;
+        if self.capacity == 0 {
+            return Ok(home);
+        }
+        // there are never more shards than the capacity, so each shard holds at least one entry.
+        let capacity = self.capacity / self.shards.len();
+        let mut entries = shard.write().unwrap_or_else(PoisonError::into_inner);
+        if entries.len() >= capacity && !entries.contains_key(&key) {
+            entries.retain(|_, v| v.inserted.elapsed() < self.ttl);
+            while entries.len() >= capacity {
+                let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, v)| v.inserted)
+                    .map(|(k, _)| k.clone())
+                else {
+                    break;
+                };
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                home: home.clone(),
+                inserted: Instant::now(),
+            },
+        );
+        Ok(home)
+    }
+}
+
+/// Construct the empty shards of a cache with the given capacity. A small cache has fewer shards,
+/// so that each of them can hold at least one entry.
+fn new_shards(capacity: usize) -> Box<[RwLock<HashMap<Key, Entry>>]> {
+    (0..capacity.clamp(1, SHARDS))
+        .map(|_| RwLock::default())
+        .collect()
+}
+
+impl<P: HomeProvider> HomeProvider for CachedResolver<P> {
+    fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+        CachedResolver::home(self, username)
+    }
+
+    fn home_of(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+        CachedResolver::home_of(self, id)
+    }
+
+    fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        CachedResolver::my_home(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A provider in which every user exists, and whose home directory is named after them.
+    struct EveryUser;
+
+    impl HomeProvider for EveryUser {
+        fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+            Ok(Some(PathBuf::from("/home").join(username)))
+        }
+
+        fn home_of(&self, _id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+            Ok(None)
+        }
+
+        fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+            Ok(None)
+        }
+    }
+
+    fn len<P: HomeProvider>(resolver: &CachedResolver<P>) -> usize {
+        resolver
+            .shards
+            .iter()
+            .map(|v| v.read().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    #[test]
+    fn capacity_is_never_exceeded() {
+        for capacity in [0, 1, 2, 15, 16, 17, 40, 100] {
+            let resolver = CachedResolver::with_provider(EveryUser, Duration::from_secs(60))
+                .capacity(capacity);
+            for i in 0..capacity * 4 + 32 {
+                resolver.home(format!("user{i}")).unwrap();
+                assert!(len(&resolver) <= capacity, "capacity {capacity}");
+            }
+        }
+    }
+
+    #[test]
+    fn capacity_of_one_holds_an_entry() {
+        let resolver =
+            CachedResolver::with_provider(EveryUser, Duration::from_secs(60)).capacity(1);
+        resolver.home("alice").unwrap();
+        assert_eq!(len(&resolver), 1);
+        resolver.home("bob").unwrap();
+        assert_eq!(len(&resolver), 1);
+    }
+}
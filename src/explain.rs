@@ -0,0 +1,191 @@
+// src/explain.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Reports of how a home directory was looked up, see [`Explanation`].
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{Backend, GetHomeError, LookupOutcome};
+
+/// A report of the lookup of a home directory, returned by
+/// [`HomeResolver::explain`](crate::HomeResolver::explain) and
+/// [`HomeResolver::explain_my_home`](crate::HomeResolver::explain_my_home), listing every
+/// backend and provider which was queried, with its result and the time it took.
+///
+/// With the `serde` feature, this implements `Serialize`, so that it can be written as JSON, for
+/// instance with `serde_json::to_string_pretty`, and attached to a bug report. The paths are
+/// serialized as strings, replacing the parts which are not valid Unicode, and the backends and
+/// outcomes by their names. The version of this crate and the operating system are included.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    username: Option<String>,
+    env_trusted: bool,
+    require_existing: bool,
+    steps: Vec<ExplainedStep>,
+}
+
+/// A query to a backend or provider, see [`Explanation::steps`].
+#[derive(Debug, Clone)]
+pub struct ExplainedStep {
+    backend: Option<Backend>,
+    outcome: LookupOutcome,
+    home: Option<PathBuf>,
+    error: Option<String>,
+    ignored: bool,
+    elapsed: Duration,
+}
+
+impl Explanation {
+    /// Construct an empty report.
+    pub(crate) fn new(username: Option<&str>, env_trusted: bool, require_existing: bool) -> Self {
+        Self {
+            username: username.map(str::to_owned),
+            env_trusted,
+            require_existing,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Add the result of a query to the report. `ignored` is whether the home directory it found
+    /// was not used because it is not a directory.
+    pub(crate) fn push(
+        &mut self,
+        backend: Option<Backend>,
+        result: Result<Option<PathBuf>, GetHomeError>,
+        ignored: bool,
+        elapsed: Duration,
+    ) {
+        let outcome = LookupOutcome::of(&result);
+        let (home, error) = match result {
+            Ok(v) => (v, None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        self.steps.push(ExplainedStep {
+            backend,
+            outcome,
+            home,
+            error,
+            ignored,
+            elapsed,
+        });
+    }
+
+    /// The username which was looked up, or `None` for the process' current user.
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    /// Whether the environment was read, see [`EnvTrust`](crate::EnvTrust).
+    pub fn env_trusted(&self) -> bool {
+        self.env_trusted
+    }
+
+    /// Whether the home directories had to be existing directories, see
+    /// [`HomeResolver::require_existing`](crate::HomeResolver::require_existing).
+    pub fn require_existing(&self) -> bool {
+        self.require_existing
+    }
+
+    /// The queries, in the order they were made.
+    pub fn steps(&self) -> &[ExplainedStep] {
+        &self.steps
+    }
+
+    /// The home directory the lookup returns: that of the first query which found one, unless an
+    /// earlier one failed.
+    pub fn home(&self) -> Option<&Path> {
+        self.result().ok().flatten()
+    }
+
+    /// The error the lookup returns: that of the first query which failed, unless an earlier one
+    /// found a home directory.
+    pub fn error(&self) -> Option<&str> {
+        self.result().err()
+    }
+
+    /// The result of the lookup, which stops at the first query which fails or finds a home
+    /// directory.
+    fn result(&self) -> Result<Option<&Path>, &str> {
+        for step in &self.steps {
+            if let Some(e) = &step.error {
+                return Err(e);
+            }
+            if let (Some(home), false) = (&step.home, step.ignored) {
+                return Ok(Some(home));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl ExplainedStep {
+    /// The backend which was queried, or `None` for a [`HomeProvider`](crate::HomeProvider).
+    pub fn backend(&self) -> Option<Backend> {
+        self.backend
+    }
+
+    /// The result of the query.
+    pub fn outcome(&self) -> LookupOutcome {
+        self.outcome
+    }
+
+    /// The home directory which was found, if any.
+    pub fn home(&self) -> Option<&Path> {
+        self.home.as_deref()
+    }
+
+    /// The message of the error, if the query failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Whether the home directory which was found was ignored because it is not a directory.
+    pub fn ignored(&self) -> bool {
+        self.ignored
+    }
+
+    /// The time the query took.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Explanation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("Explanation", 8)?;
+        s.serialize_field("version", env!("CARGO_PKG_VERSION"))?;
+        s.serialize_field("os", std::env::consts::OS)?;
+        s.serialize_field("username", &self.username)?;
+        s.serialize_field("env_trusted", &self.env_trusted)?;
+        s.serialize_field("require_existing", &self.require_existing)?;
+        s.serialize_field("steps", &self.steps)?;
+        s.serialize_field("home", &self.home().map(Path::to_string_lossy))?;
+        s.serialize_field("error", &self.error())?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ExplainedStep {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("ExplainedStep", 6)?;
+        s.serialize_field("backend", &self.backend.map(|v| format!("{v:?}")))?;
+        s.serialize_field("outcome", &format!("{:?}", self.outcome))?;
+        s.serialize_field("home", &self.home.as_deref().map(Path::to_string_lossy))?;
+        s.serialize_field("error", &self.error)?;
+        s.serialize_field("ignored", &self.ignored)?;
+        let elapsed = u64::try_from(self.elapsed.as_micros()).unwrap_or(u64::MAX);
+        s.serialize_field("elapsed_us", &elapsed)?;
+        s.end()
+    }
+}
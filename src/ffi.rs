@@ -0,0 +1,210 @@
+// src/ffi.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A C API to the lookups of this crate, for programs written in C, C++, or any language which can
+//! call C functions, enabled by the `ffi` feature.
+//!
+//! The functions are declared in `include/homedir.h`. To build a shared or static library which
+//! exports them, run one of the following.
+//!
+//! ```sh
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! cargo rustc --release --features ffi --crate-type staticlib
+//! ```
+//!
+//! Each function returns a [`HomedirStatus`](crate::ffi::HomedirStatus), and stores the home
+//! directory it found in `*out` as a NUL-terminated string, which must be freed with
+//! [`homedir_free`](crate::ffi::homedir_free). When no home directory is found, or on an error,
+//! `*out` is set to a null pointer. On Unix, the strings are bytes in the encoding of the system,
+//! like the paths of the C library. On Windows, they are UTF-8.
+//!
+//! ```c
+//! #include <stdio.h>
+//! #include "homedir.h"
+//!
+//! int main(void) {
+//!     char *home;
+//!     if (homedir_home("root", &home) == HOMEDIR_OK) {
+//!         printf("%s\n", home);
+//!         homedir_free(home);
+//!     }
+//!     return 0;
+//! }
+//! ```
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::PathBuf,
+    ptr,
+};
+
+use cfg_if::cfg_if;
+
+use crate::{GetHomeError, UserIdentifier};
+
+/// The result of a function of the C API.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HomedirStatus {
+    /// The home directory was found, and stored in `*out`.
+    Ok = 0,
+    /// The user or their home directory was not found.
+    NotFound = 1,
+    /// A pointer was null, or the username or identifier was malformed.
+    InvalidArgument = 2,
+    /// The lookup is not supported on this target or with the enabled features.
+    Unsupported = 3,
+    /// The home directory cannot be represented as a C string, for instance because it is not
+    /// valid UTF-8 on Windows.
+    Encoding = 4,
+    /// The lookup failed, or panicked.
+    Error = 5,
+}
+
+/// Get the home directory of the process' current user, see [`my_home`](crate::my_home).
+///
+/// # Safety
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn homedir_my_home(out: *mut *mut c_char) -> HomedirStatus {
+    unsafe { lookup(out, crate::my_home) }
+}
+
+/// Get the home directory of an arbitrary user given their username, see [`home`](crate::home).
+/// On Unix, the username may be in any encoding. On Windows, it must be UTF-8.
+///
+/// # Safety
+/// `username` must be null or point to a NUL-terminated string, and `out` must be null or valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn homedir_home(
+    username: *const c_char,
+    out: *mut *mut c_char,
+) -> HomedirStatus {
+    if username.is_null() {
+        return unsafe { clear(out, HomedirStatus::InvalidArgument) };
+    }
+    let username = unsafe { CStr::from_ptr(username) };
+    cfg_if! {
+        if #[cfg(unix)] {
+            use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+            let username = OsStr::from_bytes(username.to_bytes());
+        } else {
+            let Ok(username) = username.to_str() else {
+                return unsafe { clear(out, HomedirStatus::InvalidArgument) };
+            };
+        }
+    }
+    unsafe { lookup(out, || crate::home_os(username)) }
+}
+
+/// Get the home directory of the user with the given identifier, see
+/// [`UserIdentifier::to_home`]. On Unix, the identifier is a user id in decimal, such as `1000`.
+/// On Windows, it is the text representation of a SID, such as `S-1-5-21-...-1001`. On the other
+/// targets, this returns [`HomedirStatus::Unsupported`].
+///
+/// # Safety
+/// `id` must be null or point to a NUL-terminated string, and `out` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn homedir_home_of(
+    id: *const c_char,
+    out: *mut *mut c_char,
+) -> HomedirStatus {
+    if id.is_null() {
+        return unsafe { clear(out, HomedirStatus::InvalidArgument) };
+    }
+    let Ok(id) = unsafe { CStr::from_ptr(id) }.to_str() else {
+        return unsafe { clear(out, HomedirStatus::InvalidArgument) };
+    };
+    match parse_id(id) {
+        Ok(Some(id)) => unsafe { lookup(out, || id.to_home()) },
+        Ok(None) => unsafe { clear(out, HomedirStatus::InvalidArgument) },
+        Err(status) => unsafe { clear(out, status) },
+    }
+}
+
+/// Free a string returned by the other functions of the C API. Passing a null pointer does
+/// nothing.
+///
+/// # Safety
+/// `s` must be null or a string returned by this API which was not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn homedir_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Parse the text representation of a user identifier.
+fn parse_id(id: &str) -> Result<Option<UserIdentifier>, HomedirStatus> {
+    cfg_if! {
+        if #[cfg(all(
+            unix,
+            not(feature = "env-only"),
+            not(any(
+                target_os = "ios",
+                target_os = "tvos",
+                target_os = "watchos",
+                target_os = "visionos"
+            ))
+        ))] {
+            use crate::unix::{Uid, UserIdentifier as UnixIdentifier};
+            Ok(id
+                .parse()
+                .ok()
+                .map(|v| UnixIdentifier::from(Uid::from_raw(v)).into()))
+        } else if #[cfg(all(windows, not(feature = "env-only")))] {
+            Ok(crate::windows::UserIdentifier::from_sid_string(id).map(Into::into))
+        } else {
+            let _ = id;
+            Err(HomedirStatus::Unsupported)
+        }
+    }
+}
+
+/// Run a lookup, catching panics, and store the home directory it found.
+unsafe fn lookup(
+    out: *mut *mut c_char,
+    f: impl FnOnce() -> Result<Option<PathBuf>, GetHomeError>,
+) -> HomedirStatus {
+    if out.is_null() {
+        return HomedirStatus::InvalidArgument;
+    }
+    unsafe { *out = ptr::null_mut() };
+    let home = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(Some(v))) => v,
+        Ok(Ok(None)) => return HomedirStatus::NotFound,
+        Ok(Err(e)) if e.is_unsupported() => return HomedirStatus::Unsupported,
+        Ok(Err(_)) | Err(_) => return HomedirStatus::Error,
+    };
+    cfg_if! {
+        if #[cfg(unix)] {
+            use std::os::unix::ffi::OsStringExt;
+            let bytes = home.into_os_string().into_vec();
+        } else {
+            let Ok(home) = home.into_os_string().into_string() else {
+                return HomedirStatus::Encoding;
+            };
+            let bytes = home.into_bytes();
+        }
+    }
+    match CString::new(bytes) {
+        Ok(v) => {
+            unsafe { *out = v.into_raw() };
+            HomedirStatus::Ok
+        }
+        Err(_) => HomedirStatus::Encoding,
+    }
+}
+
+/// Set `*out` to a null pointer, if `out` is not null, and return the given status.
+unsafe fn clear(out: *mut *mut c_char, status: HomedirStatus) -> HomedirStatus {
+    if !out.is_null() {
+        unsafe { *out = ptr::null_mut() };
+    }
+    status
+}
@@ -0,0 +1,199 @@
+// src/home_path.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A path argument type which expands a leading tilde, see [`HomePath`].
+
+use std::{
+    ffi::OsStr,
+    fmt,
+    ops::Deref,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[cfg(feature = "serde")]
+use crate::contract_tilde;
+use crate::{expand_tilde, GetHomeError};
+
+/// A path in which a leading `~` or `~alice` has been expanded with [`expand_tilde`] when it was
+/// parsed. This is meant for command-line arguments, as a shell does not expand a tilde which
+/// follows `=`, such as in `--output=~/reports`.
+///
+/// With the `clap` feature, this implements `clap::builder::ValueParserFactory`, so it can be
+/// used as the type of an argument directly. Arguments which are not valid UTF-8 are accepted.
+///
+/// With the `serde` feature, this implements `Serialize` and `Deserialize`. It is serialized as
+/// a string in which the current user's home directory is contracted with [`contract_tilde`], so
+/// that `/home/alice/reports` is written as `~/reports` when serialized by `alice`, and the
+/// tilde is expanded when it is deserialized. Configuration files containing such paths can
+/// thus be shared between users and machines. Paths which are not valid UTF-8 cannot be
+/// serialized.
+///
+/// [`contract_tilde`]: crate::contract_tilde
+///
+/// # Example
+/// ```no_run
+/// use homedir::HomePath;
+///
+/// # fn main() -> Result<(), homedir::ParseHomePathError> {
+/// let output: HomePath = "~alice/reports".parse()?;
+/// println!("{}", output.display());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HomePath(PathBuf);
+
+/// The error returned when a [`HomePath`] cannot be parsed.
+#[derive(Debug)]
+pub enum ParseHomePathError {
+    /// The home directory could not be looked up.
+    Lookup(GetHomeError),
+    /// The user named after the tilde does not exist or does not have a home directory. This
+    /// contains the path which could not be expanded.
+    NotFound(PathBuf),
+}
+
+impl HomePath {
+    /// Expand the leading tilde of a path, if it has one.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, ParseHomePathError> {
+        let path = path.as_ref();
+        match expand_tilde(path)? {
+            Some(v) => Ok(Self(v)),
+            None => Err(ParseHomePathError::NotFound(path.to_owned())),
+        }
+    }
+
+    /// The expanded path.
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Convert this into the expanded path.
+    pub fn into_path_buf(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl FromStr for HomePath {
+    type Err = ParseHomePathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl Deref for HomePath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for HomePath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<OsStr> for HomePath {
+    fn as_ref(&self) -> &OsStr {
+        self.0.as_os_str()
+    }
+}
+
+impl From<HomePath> for PathBuf {
+    fn from(value: HomePath) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for ParseHomePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lookup(e) => e.fmt(f),
+            Self::NotFound(path) => write!(
+                f,
+                "cannot expand the tilde in {}: no such user or home directory",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseHomePathError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Lookup(e) => Some(e),
+            Self::NotFound(_) => None,
+        }
+    }
+}
+
+impl From<GetHomeError> for ParseHomePathError {
+    fn from(value: GetHomeError) -> Self {
+        Self::Lookup(value)
+    }
+}
+
+/// The `clap` value parser for [`HomePath`], which accepts arguments which are not valid UTF-8.
+#[cfg(feature = "clap")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HomePathValueParser;
+
+#[cfg(feature = "clap")]
+impl clap::builder::TypedValueParser for HomePathValueParser {
+    type Value = HomePath;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<HomePath, clap::Error> {
+        HomePath::new(value).map_err(|e| {
+            let arg = arg.map_or_else(|| "...".to_owned(), ToString::to_string);
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                format!("invalid value for {arg}: {e}\n"),
+            )
+            .with_cmd(cmd)
+        })
+    }
+}
+
+#[cfg(feature = "clap")]
+impl clap::builder::ValueParserFactory for HomePath {
+    type Parser = HomePathValueParser;
+
+    fn value_parser() -> Self::Parser {
+        HomePathValueParser
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for HomePath {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+
+        let path = contract_tilde(&self.0).map_err(S::Error::custom)?;
+        match path.to_str() {
+            Some(path) => serializer.serialize_str(path),
+            None => Err(S::Error::custom(format_args!(
+                "path is not valid UTF-8: {}",
+                path.display()
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for HomePath {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let path = String::deserialize(deserializer)?;
+        Self::new(path).map_err(serde::de::Error::custom)
+    }
+}
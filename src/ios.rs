@@ -0,0 +1,219 @@
+// src/ios.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! This is the implementation of the crate for Apple's mobile platforms: iOS, tvOS, watchOS, and
+//! visionOS. Applications on these platforms run in a sandbox, so the home directory of the
+//! current user is the application's container, as returned by `NSHomeDirectory`, and looking up
+//! other users always fails with [`GetHomeError::Unsupported`].
+
+use std::{
+    ffi::{c_void, OsStr},
+    fmt,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::Backend;
+
+/// A reference to a `CFURL` object.
+type CFURLRef = *const c_void;
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFCopyHomeDirectoryURL() -> CFURLRef;
+    fn CFURLGetFileSystemRepresentation(
+        url: CFURLRef,
+        resolve_against_base: u8,
+        buffer: *mut u8,
+        max_buf_len: isize,
+    ) -> u8;
+    fn CFRelease(cf: *const c_void);
+}
+
+/// The size of the buffer the home directory is written to, `PATH_MAX` on Apple platforms.
+const PATH_MAX: usize = 1024;
+
+/// An identifier for a user. Other users cannot be looked up on these platforms,
+/// so this is never constructed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UserIdentifier {
+    _priv: (),
+}
+
+/// Information about a user, as returned by [`user_info`]. Other users cannot be looked up on
+/// these platforms, so this is never constructed.
+#[derive(Debug, Clone)]
+pub struct UserInfo {
+    _priv: (),
+}
+
+/// The primary group of a user, as returned by [`primary_group`]. Other users cannot be looked up
+/// on these platforms, so this is never constructed.
+#[derive(Debug, Clone)]
+pub struct PrimaryGroup {
+    _priv: (),
+}
+
+/// The error type returned by this library when errors occur.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GetHomeError {
+    /// The operation is not supported in the application sandbox.
+    Unsupported,
+    /// The relative path passed to [`home_join`](crate::home_join) was absolute or escaped the home directory.
+    InvalidPath,
+}
+
+/// Get a user's home directory path. The user database is not accessible from the application
+/// sandbox, so this always fails with [`GetHomeError::Unsupported`].
+pub fn home<S: AsRef<str>>(_username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get a user's home directory path given a username which may not be valid UTF-8. This always
+/// fails with [`GetHomeError::Unsupported`].
+pub fn home_os<S: AsRef<OsStr>>(_username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get information about a user given their username. This always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn user_info<S: AsRef<str>>(_username: S) -> Result<Option<UserInfo>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get the primary group of a user given their username. This always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn primary_group<S: AsRef<str>>(_username: S) -> Result<Option<PrimaryGroup>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// The backends used by [`my_home`], in order, see [`HomeResolver`](crate::HomeResolver).
+pub(crate) fn default_backends() -> Vec<Backend> {
+    vec![Backend::Container]
+}
+
+/// Look up a home directory through a single backend, see
+/// [`HomeResolver`](crate::HomeResolver). `None` stands for the current user.
+pub(crate) fn backend_home(
+    backend: Backend,
+    username: Option<&str>,
+    _timeout: Option<Duration>,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    match (backend, username) {
+        (Backend::Container, None) => my_home(),
+        (Backend::Container, Some(_)) => Ok(None),
+        _ => Err(GetHomeError::Unsupported),
+    }
+}
+
+/// Find the users whose username matches a glob pattern. This always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn find_users(_pattern: &str) -> Result<Vec<UserInfo>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get the home directory of this application, which is its sandbox container. This is the
+/// directory returned by `NSHomeDirectory`, obtained with `CFCopyHomeDirectoryURL`.
+pub fn my_home() -> Result<Option<PathBuf>, GetHomeError> {
+    unsafe {
+        let url = CFCopyHomeDirectoryURL();
+        if url.is_null() {
+            return Ok(None);
+        }
+        let mut buf = [0u8; PATH_MAX];
+        let ok = CFURLGetFileSystemRepresentation(url, 1, buf.as_mut_ptr(), PATH_MAX as isize);
+        CFRelease(url);
+        if ok == 0 {
+            return Ok(None);
+        }
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(PATH_MAX);
+        Ok(Some(PathBuf::from(OsStr::from_bytes(&buf[..len]))))
+    }
+}
+
+impl UserIdentifier {
+    /// Get a user's id from their username. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn with_username<S: AsRef<str>>(_username: S) -> Result<Option<Self>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get a user's id given a username which may not be valid UTF-8. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn with_username_os<S: AsRef<OsStr>>(_username: S) -> Result<Option<Self>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get a user's id and the canonical name of their account, matching the username
+    /// case-insensitively. This always fails with [`GetHomeError::Unsupported`].
+    pub fn with_username_ignore_case<S: AsRef<str>>(
+        _username: S,
+    ) -> Result<Option<(Self, String)>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get the current process' user id. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn my_id() -> Result<Self, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get a user's home directory path from their user identifier. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn to_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get information about the user with this identifier. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn to_user_info(&self) -> Result<Option<UserInfo>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+}
+
+impl UserInfo {
+    /// The home directory of the user.
+    pub fn home(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The login shell of the user.
+    pub fn shell(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The username of the user.
+    pub fn username(&self) -> Option<&str> {
+        None
+    }
+
+    /// The full name of the user.
+    pub fn full_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl PrimaryGroup {
+    /// The name of the group.
+    pub fn name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl fmt::Display for GetHomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => write!(
+                f,
+                "user lookups are not supported in the application sandbox"
+            ),
+            Self::InvalidPath => write!(f, "the path is absolute or escapes the home directory"),
+        }
+    }
+}
+
+impl std::error::Error for GetHomeError {}
@@ -0,0 +1,113 @@
+// src/js.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The lookup of the current user's home directory from a JavaScript host, on
+//! `wasm32-unknown-unknown` with the `wasm-bindgen` feature.
+//!
+//! This target has no environment variables and no user database, so [`my_home`](crate::my_home)
+//! asks the host instead. It first calls the callback set with
+//! [`set_my_home_callback`](crate::js::set_my_home_callback), if there is one. Otherwise, when
+//! running in Node.js or Electron, it calls `os.homedir()` through `process.getBuiltinModule`, or
+//! reads `HOME` (`USERPROFILE` on Windows) from `process.env` on the versions of Node.js which do
+//! not have it. In a browser, without a callback, it fails with an error for which
+//! [`GetHomeError::is_unsupported`](crate::GetHomeError::is_unsupported) is true, as without this
+//! feature. Looking up other users always fails this way.
+//!
+//! # Example
+//! ```no_run
+//! use homedir::js::set_my_home_callback;
+//! use wasm_bindgen::prelude::*;
+//!
+//! /// Called by the JavaScript host, e.g. `setHome(() => app.getPath("home"))` in Electron.
+//! #[wasm_bindgen(js_name = setHome)]
+//! pub fn set_home(callback: js_sys::Function) {
+//!     set_my_home_callback(Some(callback));
+//! }
+//! ```
+
+use std::{cell::RefCell, path::PathBuf};
+
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::unsupported::GetHomeError;
+
+thread_local! {
+    /// The callback set with [`set_my_home_callback`].
+    static CALLBACK: RefCell<Option<Function>> = const { RefCell::new(None) };
+}
+
+/// Set the function called by [`my_home`](crate::my_home) to get the home directory of the current
+/// user, or remove it with `None`. It is called without arguments, and must return the home
+/// directory as a string, or `null` or `undefined` if there is none. If it throws or returns
+/// anything else, `my_home` fails.
+///
+/// The callback is only used by the thread which set it, which is the only one in most programs
+/// on this target.
+pub fn set_my_home_callback(callback: Option<Function>) {
+    CALLBACK.with(|v| *v.borrow_mut() = callback);
+}
+
+/// Get the home directory of the current user from the callback or from Node.js.
+pub(crate) fn my_home() -> Result<Option<PathBuf>, GetHomeError> {
+    if let Some(callback) = CALLBACK.with(|v| v.borrow().clone()) {
+        let home = callback
+            .call0(&JsValue::UNDEFINED)
+            .map_err(|e| GetHomeError::Host(describe(&e)))?;
+        if home.is_null() || home.is_undefined() {
+            return Ok(None);
+        }
+        return match home.as_string() {
+            Some(v) => Ok(Some(PathBuf::from(v))),
+            None => Err(GetHomeError::Host(format!(
+                "the callback returned {}",
+                describe(&home)
+            ))),
+        };
+    }
+    let process = get(&js_sys::global(), "process");
+    if process.is_undefined() || get(&get(&process, "versions"), "node").is_undefined() {
+        return Err(GetHomeError::Unsupported);
+    }
+    if let Some(get_builtin) = get(&process, "getBuiltinModule").dyn_ref::<Function>() {
+        let os = get_builtin
+            .call1(&process, &JsValue::from_str("os"))
+            .map_err(|e| GetHomeError::Host(describe(&e)))?;
+        if let Some(homedir) = get(&os, "homedir").dyn_ref::<Function>() {
+            let home = homedir
+                .call0(&os)
+                .map_err(|e| GetHomeError::Host(describe(&e)))?;
+            return Ok(home
+                .as_string()
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from));
+        }
+    }
+    let key = if get(&process, "platform").as_string().as_deref() == Some("win32") {
+        "USERPROFILE"
+    } else {
+        "HOME"
+    };
+    Ok(get(&get(&process, "env"), key)
+        .as_string()
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from))
+}
+
+/// Get a property of a JavaScript value, or `undefined` if it has none.
+fn get(target: &JsValue, key: &str) -> JsValue {
+    if !target.is_object() {
+        return JsValue::UNDEFINED;
+    }
+    Reflect::get(target, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+}
+
+/// Describe a JavaScript value, such as an exception, for an error message.
+fn describe(value: &JsValue) -> String {
+    match value.dyn_ref::<js_sys::Error>() {
+        Some(e) => String::from(e.message()),
+        None => format!("{value:?}"),
+    }
+}
@@ -27,7 +27,10 @@
 //! [`windows`](https://docs.rs/homedir/latest/x86_64-pc-windows-msvc/homedir/windows/index.html)
 //! module contains the implementation details. For Linux, macOS, and other Unix systems, the
 //! [`unix`](https://docs.rs/homedir/latest/homedir/unix/index.html) module contains the
-//! implementation details.
+//! implementation details. On the WebAssembly System Interface, which has no users, [`my_home`]
+//! reads the `HOME` environment variable and looking up other users fails. Likewise, on iOS,
+//! tvOS, watchOS, and visionOS, [`my_home`] returns the application's sandbox container and
+//! looking up other users fails.
 //!
 //! # Usage
 //! This crate is on [crates.io](https://crates.io/crates/homedir) and can be used by executing `cargo add homedir`
@@ -105,33 +108,258 @@
 //! required, or initialize the other libraries that use it (for example
 //! [`wmi`](https://crates.io/crates/wmi)) first.
 //!
+//! The `windows` crate, with the features required for the COM library, takes a significant
+//! amount of time to compile. If this is an issue, the `windows-sys` feature can be used instead
+//! of the default `windows-rs` feature (by setting `default-features = false`). This uses the
+//! lighter [`windows-sys`](https://crates.io/crates/windows-sys) crate, and it reads the profile
+//! directory from the registry rather than the Windows Management Instrumentation, so the COM
+//! library is not used at all. The tradeoff is that only the platform-agnostic API at the root of
+//! this crate is available; `GetHomeInstance` and the other Windows-specific extensions require
+//! the `windows-rs` feature. Since features are additive, `windows-rs` takes precedence if both
+//! are enabled, so that enabling `windows-sys` somewhere in the dependency graph never removes
+//! the Windows-specific extensions from another crate. If neither is enabled, the `windows-sys`
+//! implementation is used, so `default-features = false` must be combined with
+//! `features = ["windows-rs"]` to keep the Windows-specific extensions. With the `windows-sys`
+//! implementation, `windows::GetHomeError` wraps an `io::Error` or an HRESULT rather than a
+//! [`windows::core::Error`](https://docs.rs/windows/latest/windows/core/struct.Error.html), as
+//! the `windows` crate is not compiled at all.
+//!
+//! Between the two, the Windows Management Instrumentation can be left out of the `windows`
+//! crate by enabling `windows-rs` without the default `windows-wmi` feature (which
+//! `windows-coinitialize` implies). The profile directories are then read from the registry as
+//! with `windows-sys`, and the Windows-specific extensions remain available, except for
+//! `GetHomeInstance` and the functions which depend on it: [`find_users`] and [`Backend::Wmi`]
+//! fail with an error for which [`GetHomeError::is_unsupported`] is true.
+//!
+//! # Reading only the environment
+//! Programs which only need the home directory of the current user, such as small command-line
+//! tools, can enable the `env-only` feature (with `default-features = false`). [`my_home`] then
+//! only reads the `HOME` environment variable, or `USERPROFILE` on Windows, and no unsafe code,
+//! C library function, or Windows API is used. The other functions fail, and
+//! [`is_supported`] returns `false`. This feature takes precedence over all others.
+//!
+//! # Ignoring the environment
+//! Conversely, the `no-env` feature compiles out every read of the environment, so that the
+//! results depend only on the user database, and not on how the process was started. [`my_home`]
+//! ignores `HOME`, the `XDG_*` variables are not read by the base directory lookups,
+//! [`invoking_user_home`] ignores `SUDO_UID` and the like, [`Backend::Env`] never finds a home
+//! directory, and [`expand_env_in_path`] leaves every variable as it is. On the WebAssembly
+//! System Interface, or if `env-only` is also enabled, [`my_home`] then always returns
+//! `Ok(None)`.
+//!
 //! Finally, this program has been tested on a regular Windows 11 installation. It has
 //! not been tested within any Active Directory Windows installation, and the implementation does
 //! not test for this or try to account for it in any way. If it does work on these, it will likely
 //! return the local profile path of the specified user.
 
+use std::ffi::{OsStr, OsString};
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 use cfg_if::cfg_if;
 
+/// Emit a debug record with the `log` crate, if the `log` feature is enabled. The arguments are
+/// those of [`format_args`].
+#[allow(unused_macros)]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+/// Emit a warning record with the `log` crate, if the `log` feature is enabled, see
+/// [`log_debug`].
+#[allow(unused_macros)]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::warn!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+#[cfg(all(windows, not(feature = "env-only")))]
+mod wide;
+
+mod audit;
+#[cfg(feature = "uniffi")]
+mod bindings;
+mod bulk;
+mod cached;
+mod explain;
+/// Contains the C API of the crate, enabled by the `ffi` feature.
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod home_path;
+/// Contains the lookup of the current user's home directory from a JavaScript host.
+#[cfg(all(
+    target_arch = "wasm32",
+    target_os = "unknown",
+    feature = "wasm-bindgen"
+))]
+pub mod js;
+mod metadata;
+mod observer;
+#[cfg(feature = "async")]
+mod offload;
+mod options;
+mod ownership;
+mod provider;
+mod resolver;
+/// Contains helpers for applications sandboxed by Flatpak or Snap.
+pub mod sandbox;
+/// Contains helpers for the tests of programs which use this crate.
+#[cfg(feature = "test-util")]
+pub mod testing;
+#[cfg(feature = "typed-path")]
+mod typed;
+#[cfg(feature = "camino")]
+mod utf8;
+/// Contains helpers to translate paths between Windows and the Windows Subsystem for Linux.
+pub mod wsl;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!("homedir");
+
+use audit::audit;
+pub use audit::{remove_audit_hook, set_audit_hook, AuditHook, AuditRecord, AuditedUser};
+pub use bulk::{homes, homes_parallel};
+pub use cached::CachedResolver;
+pub use explain::{ExplainedStep, Explanation};
+#[cfg(feature = "clap")]
+pub use home_path::HomePathValueParser;
+pub use home_path::{HomePath, ParseHomePathError};
+pub use metadata::{home_metadata, HomeMetadata};
+pub use observer::{LookupCounters, LookupEvent, LookupObserver, LookupOutcome};
+#[cfg(feature = "async")]
+pub use offload::{home_async, my_home_async};
+pub use options::{LookupOptions, LookupOrder};
+pub use ownership::{verify_ownership, OwnershipError};
+pub use provider::{HomeProvider, SystemProvider};
+pub use resolver::{Backend, EnvSource, EnvTrust, HomeResolver, ProcessEnv};
+#[cfg(feature = "typed-path")]
+pub use typed::{home_typed, my_home_typed};
+#[cfg(feature = "camino")]
+pub use utf8::{home_utf8, my_home_utf8, Utf8HomeError};
+
 cfg_if! {
-    if #[cfg(windows)] {
+    if #[cfg(feature = "env-only")] {
+        /// Contains the implementation of the crate enabled by the `env-only` feature.
+        pub mod env_only;
+        use env_only::backend_home as backend_home_imp;
+        use env_only::default_backends as default_backends_imp;
+        use env_only::find_users as find_users_imp;
+        use env_only::home as home_imp;
+        use env_only::home_os as home_os_imp;
+        use env_only::my_home as my_home_imp;
+        use env_only::primary_group as primary_group_imp;
+        use env_only::user_info as user_info_imp;
+        use env_only::GetHomeError as GetHomeErrorImp;
+        use env_only::PrimaryGroup as PrimaryGroupImp;
+        use env_only::UserIdentifier as UserIdentifierImp;
+        use env_only::UserInfo as UserInfoImp;
+    } else if #[cfg(all(windows, not(feature = "windows-rs")))] {
         /// Contains the implementation of the crate for Windows systems.
+        #[path = "windows_sys.rs"]
         pub mod windows;
+        use windows::backend_home as backend_home_imp;
+        use windows::default_backends as default_backends_imp;
+        use windows::find_users as find_users_imp;
         use windows::home as home_imp;
+        use windows::home_os as home_os_imp;
         use windows::my_home as my_home_imp;
+        use windows::primary_group as primary_group_imp;
+        use windows::user_info as user_info_imp;
         use windows::GetHomeError as GetHomeErrorImp;
         use windows::UserIdentifier as UserIdentifierImp;
+        use windows::PrimaryGroup as PrimaryGroupImp;
+        use windows::UserInfo as UserInfoImp;
+    } else if #[cfg(windows)] {
+        /// Contains the implementation of the crate for Windows systems.
+        pub mod windows;
+        use windows::backend_home as backend_home_imp;
+        use windows::default_backends as default_backends_imp;
+        use windows::find_users as find_users_imp;
+        use windows::home as home_imp;
+        use windows::home_os as home_os_imp;
+        use windows::my_home as my_home_imp;
+        use windows::primary_group as primary_group_imp;
+        use windows::user_info as user_info_imp;
+        use windows::GetHomeError as GetHomeErrorImp;
+        use windows::UserIdentifier as UserIdentifierImp;
+        use windows::PrimaryGroup as PrimaryGroupImp;
+        use windows::UserInfo as UserInfoImp;
+    } else if #[cfg(any(
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos",
+        target_os = "visionos",
+    ))] {
+        /// Contains the implementation of the crate for iOS, tvOS, watchOS, and visionOS.
+        pub mod ios;
+        use ios::backend_home as backend_home_imp;
+        use ios::default_backends as default_backends_imp;
+        use ios::find_users as find_users_imp;
+        use ios::home as home_imp;
+        use ios::home_os as home_os_imp;
+        use ios::my_home as my_home_imp;
+        use ios::primary_group as primary_group_imp;
+        use ios::user_info as user_info_imp;
+        use ios::GetHomeError as GetHomeErrorImp;
+        use ios::PrimaryGroup as PrimaryGroupImp;
+        use ios::UserIdentifier as UserIdentifierImp;
+        use ios::UserInfo as UserInfoImp;
     } else if #[cfg(unix)] {
         /// Contains the implementation of the crate for Unix systems.
         pub mod unix;
+        use unix::backend_home as backend_home_imp;
+        use unix::default_backends as default_backends_imp;
+        use unix::find_users as find_users_imp;
         use unix::home as home_imp;
+        use unix::home_os as home_os_imp;
         use unix::my_home as my_home_imp;
+        use unix::primary_group as primary_group_imp;
+        use unix::user_info as user_info_imp;
         use unix::GetHomeError as GetHomeErrorImp;
         use unix::UserIdentifier as UserIdentifierImp;
+        use unix::PrimaryGroup as PrimaryGroupImp;
+        use unix::UserInfo as UserInfoImp;
+    } else if #[cfg(target_os = "wasi")] {
+        /// Contains the implementation of the crate for the WebAssembly System Interface.
+        pub mod wasi;
+        use wasi::backend_home as backend_home_imp;
+        use wasi::default_backends as default_backends_imp;
+        use wasi::find_users as find_users_imp;
+        use wasi::home as home_imp;
+        use wasi::home_os as home_os_imp;
+        use wasi::my_home as my_home_imp;
+        use wasi::primary_group as primary_group_imp;
+        use wasi::user_info as user_info_imp;
+        use wasi::GetHomeError as GetHomeErrorImp;
+        use wasi::PrimaryGroup as PrimaryGroupImp;
+        use wasi::UserIdentifier as UserIdentifierImp;
+        use wasi::UserInfo as UserInfoImp;
+    } else if #[cfg(feature = "unsupported-fallback")] {
+        /// Contains a stub implementation of the crate for unsupported targets.
+        pub mod unsupported;
+        use unsupported::backend_home as backend_home_imp;
+        use unsupported::default_backends as default_backends_imp;
+        use unsupported::find_users as find_users_imp;
+        use unsupported::home as home_imp;
+        use unsupported::home_os as home_os_imp;
+        use unsupported::my_home as my_home_imp;
+        use unsupported::primary_group as primary_group_imp;
+        use unsupported::user_info as user_info_imp;
+        use unsupported::GetHomeError as GetHomeErrorImp;
+        use unsupported::PrimaryGroup as PrimaryGroupImp;
+        use unsupported::UserIdentifier as UserIdentifierImp;
+        use unsupported::UserInfo as UserInfoImp;
     } else {
-        compile_error!("this crate only supports windows and unix systems");
+        compile_error!("this crate only supports windows, unix, and wasi systems; enable the `unsupported-fallback` feature to compile a stub on other targets");
     }
 }
 
@@ -148,10 +376,114 @@ cfg_if! {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct UserIdentifier(UserIdentifierImp);
 
+/// This structure contains information about a user, as returned by [`user_info`].
+///
+/// # Example
+/// ```no_run
+/// use homedir::user_info;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// if let Some(info) = user_info("Administrator")? {
+///     println!("{:?} {:?}", info.home(), info.shell());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[repr(transparent)]
+pub struct UserInfo(UserInfoImp);
+
+/// This structure contains the primary group of a user, as returned by [`primary_group`].
+///
+/// # Example
+/// ```no_run
+/// use homedir::primary_group;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// if let Some(group) = primary_group("Administrator")? {
+///     println!("{:?}", group.name());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[repr(transparent)]
+pub struct PrimaryGroup(PrimaryGroupImp);
+
+/// A home directory which has been checked on disk, as returned by [`home_existing`].
+///
+/// # Example
+/// ```no_run
+/// use homedir::{home_existing, ExistingHome};
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// match home_existing("alice")? {
+///     Some(ExistingHome::Present(path)) => println!("backing up {}", path.display()),
+///     Some(ExistingHome::Missing(path)) => eprintln!("{} is missing", path.display()),
+///     None => eprintln!("no such user"),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExistingHome {
+    /// The home directory exists and is a directory.
+    Present(PathBuf),
+    /// The user has a home directory, but it does not exist, is not a directory, or cannot be
+    /// accessed.
+    Missing(PathBuf),
+}
+
+/// The result of looking up a user's home directory with [`home_lookup`], which distinguishes a
+/// user who has no home directory from a user who does not exist.
+///
+/// # Example
+/// ```no_run
+/// use homedir::{home_lookup, HomeLookup};
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// match home_lookup("alice")? {
+///     HomeLookup::Found(path) => println!("removing {}", path.display()),
+///     HomeLookup::UserExistsButNoHome => println!("nothing to remove"),
+///     HomeLookup::NoSuchUser => eprintln!("no such user"),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HomeLookup {
+    /// The user has this home directory. It is not checked to exist.
+    Found(PathBuf),
+    /// The user exists, but has no home directory. On Unix, the home directory field of their
+    /// entry in the user database is empty. On Windows, they have no profile, for instance
+    /// because they have never logged in.
+    UserExistsButNoHome,
+    /// There is no user with this username.
+    NoSuchUser,
+}
+
+/// The mechanism which provided the home directory of the process' current user, as reported by
+/// [`my_home_with_source`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HomeSource {
+    /// An environment variable: `HOME` on Unix and WASI, and `USERPROFILE` on Windows with the
+    /// `env-only` feature.
+    Env,
+    /// The user database on Unix, through the C library or one of the fallbacks enabled by this
+    /// crate's features. The `unix::my_home_with_source` function tells them apart.
+    Passwd,
+    /// The profile known folder on Windows, obtained with `SHGetKnownFolderPath`.
+    KnownFolder,
+    /// The sandbox container of the application on iOS, tvOS, watchOS, and visionOS, obtained
+    /// with `CFCopyHomeDirectoryURL`.
+    Container,
+}
+
 /// This structure contains the error type returned by the functions within this crate.
 #[derive(Debug)]
 #[repr(transparent)]
@@ -163,26 +495,798 @@ pub struct GetHomeError(GetHomeErrorImp);
 ///
 /// There is an example of the usage of this function in the [crate documentation](crate).
 pub fn home<S: AsRef<str>>(username: S) -> Result<Option<PathBuf>, GetHomeError> {
-    home_imp(username.as_ref()).map_err(GetHomeError)
+    let username = username.as_ref();
+    let ret = home_imp(username).map_err(GetHomeError);
+    audit_username(username, &ret);
+    ret
+}
+
+/// Get information about an arbitrary user, such as their home directory and login shell. If no
+/// user with the given username can be found, `Ok(None)` is returned.
+///
+/// There is an example of the usage of this function in the
+/// [structure's documentation](UserInfo).
+pub fn user_info<S: AsRef<str>>(username: S) -> Result<Option<UserInfo>, GetHomeError> {
+    let username = username.as_ref();
+    let ret = user_info_imp(username)
+        .map(|v| v.map(UserInfo))
+        .map_err(GetHomeError);
+    audit_username(username, &ret);
+    ret
+}
+
+/// Get the primary group of an arbitrary user. On Unix, this is the group whose id is stored in
+/// the user's passwd entry. On Windows, this is the group whose SID is the account's primary group,
+/// which requires the `windows-rs` feature; with the `windows-sys` feature, an error for which
+/// [`GetHomeError::is_unsupported`] is true is returned. If no user with the given username can
+/// be found, `Ok(None)` is returned.
+///
+/// There is an example of the usage of this function in the
+/// [structure's documentation](PrimaryGroup).
+pub fn primary_group<S: AsRef<str>>(username: S) -> Result<Option<PrimaryGroup>, GetHomeError> {
+    let username = username.as_ref();
+    let ret = primary_group_imp(username)
+        .map(|v| v.map(PrimaryGroup))
+        .map_err(GetHomeError);
+    audit_username(username, &ret);
+    ret
+}
+
+/// Find the users whose username matches a glob pattern, in which `*` matches any sequence of
+/// characters and `?` matches a single character, so that `adm*` finds all the usernames starting
+/// with `adm`. This is meant for interactive lookups, such as the completion of usernames.
+///
+/// On Unix, the user database is enumerated and the pattern is matched case-sensitively. On
+/// Windows, the local accounts are queried through the Windows Management Instrumentation and the
+/// pattern is matched case-insensitively; this requires the `windows-rs` feature, and with the
+/// `windows-sys` feature, an error for which [`GetHomeError::is_unsupported`] is true is
+/// returned.
+///
+/// # Example
+/// ```no_run
+/// use homedir::find_users;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// for info in find_users("adm*")? {
+///     println!("{:?}: {:?}", info.username(), info.home());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_users<S: AsRef<str>>(pattern: S) -> Result<Vec<UserInfo>, GetHomeError> {
+    let pattern = pattern.as_ref();
+    let ret = match find_users_imp(pattern) {
+        Ok(v) => Ok(v.into_iter().map(UserInfo).collect::<Vec<_>>()),
+        Err(e) => Err(GetHomeError(e)),
+    };
+    let outcome = match &ret {
+        Ok(v) if v.is_empty() => LookupOutcome::NotFound,
+        Ok(_) => LookupOutcome::Found,
+        Err(_) => LookupOutcome::Failed,
+    };
+    audit(AuditedUser::Pattern(pattern), None, outcome);
+    ret
+}
+
+/// Get the home directory of an arbitrary user given a username which may not be valid UTF-8,
+/// for instance one read from the filesystem or the command line. This is otherwise identical to
+/// [`home`].
+pub fn home_os<S: AsRef<OsStr>>(username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    let username = username.as_ref();
+    let ret = home_os_imp(username).map_err(GetHomeError);
+    audit_username(username, &ret);
+    ret
+}
+
+/// Report the lookup of a user given their username to the audit hook.
+fn audit_username<S: AsRef<OsStr> + ?Sized, T>(
+    username: &S,
+    result: &Result<Option<T>, GetHomeError>,
+) {
+    audit(
+        AuditedUser::Username(username.as_ref()),
+        None,
+        LookupOutcome::of(result),
+    );
+}
+
+/// Get the home directory of an arbitrary user and check that it exists and is a directory.
+/// Symbolic links are followed. This distinguishes a user whose home directory is missing, for
+/// which [`ExistingHome::Missing`] is returned, from a user who cannot be found, for which
+/// `Ok(None)` is returned.
+///
+/// There is an example of the usage of this function in the
+/// [enumeration's documentation](ExistingHome).
+pub fn home_existing<S: AsRef<str>>(username: S) -> Result<Option<ExistingHome>, GetHomeError> {
+    Ok(home(username)?.map(ExistingHome::new))
+}
+
+/// Get the home directory of an arbitrary user, distinguishing a user who has no home directory
+/// from a user who does not exist, which [`home`] both reports as `Ok(None)` on Windows. On
+/// Unix, [`home`] returns an empty path for a user whose home directory field is empty, for
+/// which [`HomeLookup::UserExistsButNoHome`] is returned instead.
+///
+/// There is an example of the usage of this function in the
+/// [enumeration's documentation](HomeLookup).
+pub fn home_lookup<S: AsRef<str>>(username: S) -> Result<HomeLookup, GetHomeError> {
+    cfg_if! {
+        if #[cfg(all(unix, not(feature = "env-only"), not(any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos",
+            target_os = "visionos",
+        ))))] {
+            Ok(match home(username)? {
+                Some(path) if path.as_os_str().is_empty() => HomeLookup::UserExistsButNoHome,
+                Some(path) => HomeLookup::Found(path),
+                None => HomeLookup::NoSuchUser,
+            })
+        } else {
+            let Some(id) = UserIdentifier::with_username(username)? else {
+                return Ok(HomeLookup::NoSuchUser);
+            };
+            Ok(match id.to_home()? {
+                Some(path) => HomeLookup::Found(path),
+                None => HomeLookup::UserExistsButNoHome,
+            })
+        }
+    }
 }
 
 /// Get the home directory of the process' current user.
 ///
+/// Inside Snap, this is the directory private to the application which Snap sets as `HOME`. The
+/// [`sandbox`] module finds the user's home directory on the host instead.
+///
 /// There is an example of the usage of this function in the [crate documentation](crate).
 pub fn my_home() -> Result<Option<PathBuf>, GetHomeError> {
+    #[cfg(feature = "test-util")]
+    if let Some(home) = testing::overridden_home() {
+        return Ok(Some(home));
+    }
     my_home_imp().map_err(GetHomeError)
 }
 
+/// Get the home directory of the process' current user, writing it into `buf` instead of
+/// returning a new [`PathBuf`]. This is otherwise identical to [`my_home`], and is meant for
+/// programs which look up the home directory in a loop, as the allocation of `buf` is reused.
+///
+/// If the home directory is found, `Ok(true)` is returned. Otherwise, `buf` is cleared and
+/// `Ok(false)` is returned. On Unix and Windows, the intermediate copies of the other fields of
+/// the user's entry are avoided as well.
+///
+/// # Example
+/// ```no_run
+/// use homedir::my_home_into;
+/// use std::path::PathBuf;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// let mut home = PathBuf::new();
+/// for _ in 0..1000 {
+///     if my_home_into(&mut home)? {
+///         println!("{}", home.display());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn my_home_into(buf: &mut PathBuf) -> Result<bool, GetHomeError> {
+    #[cfg(feature = "test-util")]
+    if let Some(home) = testing::overridden_home() {
+        replace_path(buf, &home);
+        return Ok(true);
+    }
+    cfg_if! {
+        if #[cfg(all(
+            unix,
+            not(feature = "env-only"),
+            not(any(
+                target_os = "ios",
+                target_os = "tvos",
+                target_os = "watchos",
+                target_os = "visionos",
+            )),
+        ))] {
+            unix::my_home_into(buf).map_err(GetHomeError)
+        } else if #[cfg(all(windows, not(feature = "env-only")))] {
+            windows::my_home_into(buf).map_err(GetHomeError)
+        } else {
+            let home = my_home_imp().map_err(GetHomeError)?;
+            Ok(replace_path_opt(buf, home.as_deref()))
+        }
+    }
+}
+
+/// Get the home directory of the process' current user along with the mechanism which provided
+/// it. This is otherwise identical to [`my_home`], and is useful to explain where an unexpected
+/// home directory came from, such as a `HOME` environment variable set by a service manager.
+///
+/// # Example
+/// ```no_run
+/// use homedir::{my_home_with_source, HomeSource};
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// if let Some((path, source)) = my_home_with_source()? {
+///     if source == HomeSource::Env {
+///         println!("using {} from the environment", path.display());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn my_home_with_source() -> Result<Option<(PathBuf, HomeSource)>, GetHomeError> {
+    // an overridden home directory stands in for the environment on every platform.
+    #[cfg(feature = "test-util")]
+    if let Some(home) = testing::overridden_home() {
+        return Ok(Some((home, HomeSource::Env)));
+    }
+    cfg_if! {
+        if #[cfg(feature = "env-only")] {
+            Ok(my_home()?.map(|v| (v, HomeSource::Env)))
+        } else if #[cfg(windows)] {
+            Ok(my_home()?.map(|v| (v, HomeSource::KnownFolder)))
+        } else if #[cfg(any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos",
+            target_os = "visionos",
+        ))] {
+            Ok(my_home()?.map(|v| (v, HomeSource::Container)))
+        } else if #[cfg(unix)] {
+            Ok(unix::my_home_with_source()
+                .map_err(GetHomeError)?
+                .map(|v| {
+                    let source = match v.source() {
+                        unix::Source::Env => HomeSource::Env,
+                        _ => HomeSource::Passwd,
+                    };
+                    (v.into_path(), source)
+                }))
+        } else {
+            Ok(my_home()?.map(|v| (v, HomeSource::Env)))
+        }
+    }
+}
+
+/// Get the home directory of an arbitrary user through a single [`Backend`], bypassing the others.
+/// This is useful to find out which mechanism returns an unexpected home directory, or to only
+/// trust one of them. Backends which only apply to the current user, such as [`Backend::Env`],
+/// return `Ok(None)`. Backends which are not available on the current target fail with an error
+/// for which [`GetHomeError::is_unsupported`] is true.
+///
+/// # Example
+/// ```no_run
+/// use homedir::{home_with_backend, Backend};
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// println!("NSS: {:?}", home_with_backend("alice", Backend::Passwd)?);
+/// println!("/etc/passwd: {:?}", home_with_backend("alice", Backend::PasswdFile)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_with_backend<S: AsRef<str>>(
+    username: S,
+    backend: Backend,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    HomeResolver::new().backends([backend]).home(username)
+}
+
+/// Get the home directory of the process' current user through a single [`Backend`]. This is
+/// otherwise identical to [`home_with_backend`].
+pub fn my_home_with_backend(backend: Backend) -> Result<Option<PathBuf>, GetHomeError> {
+    HomeResolver::new().backends([backend]).my_home()
+}
+
+/// Get the home directory of an arbitrary user, or `default` if no user with the given username
+/// can be found or they do not have a home directory. Errors are still returned, so that a
+/// failed lookup is not mistaken for a missing user.
+///
+/// # Example
+/// ```no_run
+/// use homedir::home_or;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// let home = home_or("alice", "/var/empty")?;
+/// println!("{}", home.display());
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_or<S: AsRef<str>, P: Into<PathBuf>>(
+    username: S,
+    default: P,
+) -> Result<PathBuf, GetHomeError> {
+    Ok(home(username)?.unwrap_or_else(|| default.into()))
+}
+
+/// Get the home directory of the process' current user, or `default` if it cannot be found.
+/// This is otherwise identical to [`home_or`].
+pub fn my_home_or<P: Into<PathBuf>>(default: P) -> Result<PathBuf, GetHomeError> {
+    Ok(my_home()?.unwrap_or_else(|| default.into()))
+}
+
+/// Get the home directory of the process' current user, ignoring the `HOME` environment
+/// variable if the process is running setuid or setgid. Privileged programs should use this
+/// instead of [`my_home`], as the environment is controlled by the user who runs them.
+///
+/// On Windows, [`my_home`] does not read the environment, so this is identical to it. With the
+/// `env-only` feature, the process' privileges cannot be checked, so this is also identical to
+/// [`my_home`].
+pub fn my_home_secure() -> Result<Option<PathBuf>, GetHomeError> {
+    #[cfg(feature = "test-util")]
+    if let Some(home) = testing::overridden_home() {
+        return Ok(Some(home));
+    }
+    cfg_if! {
+        if #[cfg(all(unix, not(feature = "env-only"), not(any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos",
+            target_os = "visionos",
+        ))))] {
+            unix::my_home_secure().map_err(GetHomeError)
+        } else {
+            my_home()
+        }
+    }
+}
+
+/// Get the home directory of the user who invoked this process through `sudo`, `doas`, or
+/// `pkexec`, falling back on [`my_home`] otherwise. Tools which write per-user configuration
+/// should use this when they may be run with elevated privileges, to avoid writing to root's
+/// home directory.
+///
+/// On Windows, there is no equivalent mechanism, so this is identical to [`my_home`], as it is with
+/// the `env-only` feature.
+///
+/// # Example
+/// ```no_run
+/// use homedir::invoking_user_home;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// // When run with `sudo`, this prints the home directory of the user who ran `sudo`.
+/// println!("{:?}", invoking_user_home()?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn invoking_user_home() -> Result<Option<PathBuf>, GetHomeError> {
+    cfg_if! {
+        if #[cfg(all(unix, not(feature = "env-only"), not(any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos",
+            target_os = "visionos",
+        ))))] {
+            unix::invoking_user_home().map_err(GetHomeError)
+        } else {
+            my_home()
+        }
+    }
+}
+
+/// Get the home directory of the process' current user joined with a relative path, such as
+/// `.config/app`. If the path is absolute, or if its `..` components would leave the home
+/// directory, an error for which [`GetHomeError::is_invalid_path`] is true is returned. The path
+/// is checked lexically, so symbolic links inside the home directory are not resolved.
+///
+/// # Example
+/// ```no_run
+/// use homedir::home_join;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// if let Some(config) = home_join(".config/app")? {
+///     println!("{}", config.display());
+/// }
+/// assert!(home_join("../root").unwrap_err().is_invalid_path());
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_join<P: AsRef<Path>>(relative: P) -> Result<Option<PathBuf>, GetHomeError> {
+    let relative = relative.as_ref();
+    check_relative(relative)?;
+    Ok(my_home()?.map(|home| home.join(relative)))
+}
+
+/// Check that a path is relative and does not leave the directory it is joined to.
+fn check_relative(path: &Path) -> Result<(), GetHomeError> {
+    let mut depth = 0usize;
+    for c in path.components() {
+        match c {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => (),
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => return Err(GetHomeError(GetHomeErrorImp::InvalidPath)),
+        }
+    }
+    Ok(())
+}
+
+/// Expand a leading `~` in a path to the home directory of the process' current user, and a
+/// leading `~alice` to the home directory of the user `alice`, as a shell does. Paths which do
+/// not start with a tilde are returned unchanged. If the user cannot be found or has no home
+/// directory, `Ok(None)` is returned.
+///
+/// # Example
+/// ```no_run
+/// use homedir::expand_tilde;
+/// use std::path::PathBuf;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// // This assumes that the user `alice` has "/home/alice" as home directory.
+/// assert_eq!(
+///     Some(PathBuf::from("/home/alice/.config")),
+///     expand_tilde("~alice/.config")?
+/// );
+/// assert_eq!(Some(PathBuf::from("/etc")), expand_tilde("/etc")?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn expand_tilde<P: AsRef<Path>>(path: P) -> Result<Option<PathBuf>, GetHomeError> {
+    let path = path.as_ref();
+    let mut components = path.components();
+    let Some(name) = components.next().and_then(|first| match first {
+        Component::Normal(first) => strip_tilde(first),
+        _ => None,
+    }) else {
+        return Ok(Some(path.to_owned()));
+    };
+    let home = if name.is_empty() {
+        my_home()?
+    } else {
+        home_os(name)?
+    };
+    let rest = components.as_path();
+    // joining an empty path would add a trailing separator.
+    Ok(home.map(|home| {
+        if rest.as_os_str().is_empty() {
+            home
+        } else {
+            home.join(rest)
+        }
+    }))
+}
+
+/// Replace the home directory of the process' current user at the start of a path with `~`, to
+/// display it more compactly. This is the inverse of [`expand_tilde`]. Paths which are not in
+/// the home directory are returned unchanged.
+///
+/// If the home directory is a symbolic link, paths through its target are contracted as well. On
+/// Windows, paths are compared case-insensitively.
+///
+/// # Example
+/// ```no_run
+/// use homedir::contract_tilde;
+/// use std::path::PathBuf;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// // This assumes that the process' user has "/home/jpetersen" as home directory.
+/// assert_eq!(
+///     PathBuf::from("~/.config"),
+///     contract_tilde("/home/jpetersen/.config")?
+/// );
+/// assert_eq!(PathBuf::from("/etc"), contract_tilde("/etc")?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn contract_tilde<P: AsRef<Path>>(path: P) -> Result<PathBuf, GetHomeError> {
+    Ok(contract(path.as_ref(), my_home()?, "~".as_ref()))
+}
+
+/// Replace the home directory of an arbitrary user at the start of a path with `~` followed by
+/// their username, such as `~alice`. This is otherwise identical to [`contract_tilde`]. If no
+/// user with the given username can be found, the path is returned unchanged.
+pub fn contract_tilde_for<P: AsRef<Path>, S: AsRef<str>>(
+    path: P,
+    username: S,
+) -> Result<PathBuf, GetHomeError> {
+    let username = username.as_ref();
+    let home = home(username)?;
+    Ok(contract(
+        path.as_ref(),
+        home,
+        format!("~{username}").as_ref(),
+    ))
+}
+
+fn contract(path: &Path, home: Option<PathBuf>, tilde: &Path) -> PathBuf {
+    // a home directory which is a root, such as `/`, would contain every path.
+    let Some(home) = home.filter(|v| v.parent().is_some()) else {
+        return path.to_owned();
+    };
+    let rest = strip_home(path, &home).or_else(|| {
+        let target = home.canonicalize().ok()?;
+        strip_home(path, &target)
+    });
+    match rest {
+        Some(rest) if rest.as_os_str().is_empty() => tilde.to_owned(),
+        Some(rest) => tilde.join(rest),
+        None => path.to_owned(),
+    }
+}
+
+/// Check whether a path is the home directory of a user or is inside it. The path is compared
+/// component by component, case-insensitively on Windows, and a path whose `..` components
+/// leave the home directory is not inside it. Relative paths are never inside it. If the user
+/// does not have a home directory, `Ok(false)` is returned.
+///
+/// Symbolic links are not resolved, so a link inside the home directory may point outside of
+/// it. Use [`is_within_home_resolved`] to check where a path really leads.
+///
+/// # Example
+/// ```no_run
+/// use homedir::{is_within_home, UserIdentifier};
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// if let Some(id) = UserIdentifier::with_username("alice")? {
+///     // This assumes that the user `alice` has "/home/alice" as home directory.
+///     assert!(is_within_home("/home/alice/notes.txt", &id)?);
+///     assert!(!is_within_home("/home/alice/../bob/notes.txt", &id)?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn is_within_home<P: AsRef<Path>>(path: P, id: &UserIdentifier) -> Result<bool, GetHomeError> {
+    let Some(home) = id.to_home()? else {
+        return Ok(false);
+    };
+    Ok(is_within(path.as_ref(), &home))
+}
+
+/// Check whether a path is the home directory of a user or is inside it after resolving the
+/// symbolic links in both. If the path or the home directory does not exist, `Ok(false)` is
+/// returned. This is otherwise identical to [`is_within_home`].
+///
+/// The result only reflects the filesystem at the time of the call; a path may be replaced by a
+/// symbolic link before it is used.
+pub fn is_within_home_resolved<P: AsRef<Path>>(
+    path: P,
+    id: &UserIdentifier,
+) -> Result<bool, GetHomeError> {
+    let Some(home) = id.to_home()? else {
+        return Ok(false);
+    };
+    let (Ok(path), Ok(home)) = (path.as_ref().canonicalize(), home.canonicalize()) else {
+        return Ok(false);
+    };
+    Ok(is_within(&path, &home))
+}
+
+fn is_within(path: &Path, home: &Path) -> bool {
+    strip_home(path, home).is_some_and(|rest| check_relative(rest).is_ok())
+}
+
+/// Remove a home directory from the start of a path, comparing the components case-insensitively
+/// on Windows.
+fn strip_home<'a>(path: &'a Path, home: &Path) -> Option<&'a Path> {
+    let mut rest = path.components();
+    for c in home.components() {
+        let a = rest.next()?.as_os_str();
+        let b = c.as_os_str();
+        cfg_if! {
+            if #[cfg(windows)] {
+                let eq = match (a.to_str(), b.to_str()) {
+                    (Some(a), Some(b)) => a.to_lowercase() == b.to_lowercase(),
+                    _ => a == b,
+                };
+            } else {
+                let eq = a == b;
+            }
+        }
+        if !eq {
+            return None;
+        }
+    }
+    Some(rest.as_path())
+}
+
+/// Expand the environment variables in a path, such as `$HOME/.config` or `${XDG_DATA_HOME}/app`.
+/// On Windows, variables written as `%USERPROFILE%` are expanded as well, as
+/// [`ExpandEnvironmentStringsW`](https://learn.microsoft.com/en-us/windows/win32/api/processenv/nf-processenv-expandenvironmentstringsw)
+/// would. Variables which are not set are left as they are, as are all variables with the
+/// `no-env` feature.
+///
+/// The profile paths read from the registry on Windows, which may be stored as
+/// `%SystemDrive%\Users\alice`, are already expanded by this crate.
+///
+/// # Example
+/// ```no_run
+/// use homedir::expand_env_in_path;
+/// use std::path::PathBuf;
+///
+/// // This assumes that `HOME` is set to "/home/jpetersen".
+/// assert_eq!(
+///     PathBuf::from("/home/jpetersen/.config"),
+///     expand_env_in_path("$HOME/.config")
+/// );
+/// ```
+pub fn expand_env_in_path<S: AsRef<str>>(path: S) -> PathBuf {
+    let mut rest = path.as_ref();
+    let mut ret = OsString::with_capacity(rest.len());
+    while let Some(i) = rest.find(|c| c == '$' || (cfg!(windows) && c == '%')) {
+        ret.push(&rest[..i]);
+        rest = &rest[i..];
+        let len = match parse_env_var(rest) {
+            Some((name, len)) => {
+                match env_var(name) {
+                    Some(v) => ret.push(v),
+                    None => ret.push(&rest[..len]),
+                }
+                len
+            }
+            None => {
+                ret.push(&rest[..1]);
+                1
+            }
+        };
+        rest = &rest[len..];
+    }
+    ret.push(rest);
+    ret.into()
+}
+
+/// Replace the contents of `buf` with `path`, reusing its allocation.
+pub(crate) fn replace_path(buf: &mut PathBuf, path: &Path) {
+    let s = buf.as_mut_os_string();
+    s.clear();
+    s.push(path);
+}
+
+/// Replace the contents of `buf` with `path`, or clear it if `path` is `None`, returning whether
+/// `path` was `Some`.
+pub(crate) fn replace_path_opt(buf: &mut PathBuf, path: Option<&Path>) -> bool {
+    match path {
+        Some(path) => {
+            replace_path(buf, path);
+            true
+        }
+        None => {
+            buf.as_mut_os_string().clear();
+            false
+        }
+    }
+}
+
+/// Read an environment variable. This is the only place the environment is read, so that the
+/// `no-env` feature can compile it out.
+pub(crate) fn env_var<K: AsRef<OsStr>>(key: K) -> Option<OsString> {
+    cfg_if! {
+        if #[cfg(feature = "no-env")] {
+            let _ = key;
+            None
+        } else {
+            std::env::var_os(key)
+        }
+    }
+}
+
+/// Parse a reference to an environment variable at the start of a string, returning the name
+/// of the variable and the length of the reference.
+fn parse_env_var(s: &str) -> Option<(&str, usize)> {
+    let (name, len) = if let Some(r) = s.strip_prefix("${") {
+        let end = r.find('}')?;
+        (&r[..end], end + 3)
+    } else if let Some(r) = s.strip_prefix('$') {
+        let end = r
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(r.len());
+        (&r[..end], end + 1)
+    } else {
+        let r = s.strip_prefix('%')?;
+        let end = r.find('%')?;
+        (&r[..end], end + 2)
+    };
+    (!name.is_empty()).then_some((name, len))
+}
+
+/// Remove the leading `~` of a path component, if there is one.
+fn strip_tilde(s: &OsStr) -> Option<&OsStr> {
+    cfg_if! {
+        if #[cfg(unix)] {
+            use std::os::unix::ffi::OsStrExt;
+            s.as_bytes().strip_prefix(b"~").map(OsStr::from_bytes)
+        } else {
+            s.to_str()?.strip_prefix('~').map(OsStr::new)
+        }
+    }
+}
+
+/// Check whether looking up users is supported on the target this crate was compiled for. This
+/// is `false` on the WebAssembly System Interface, on Apple's mobile platforms, and on embedded
+/// Unix-like targets which lack `getpwnam_r`, where only [`my_home`] can succeed, and with
+/// the `unsupported-fallback` stub, where every function fails. It is also `false` with the
+/// `env-only` feature. In all cases, the errors are
+/// recognized by [`GetHomeError::is_unsupported`].
+///
+/// This lets code which optionally uses this crate check at runtime instead of repeating this
+/// crate's `cfg` conditions.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// if homedir::is_supported() {
+///     println!("{:?}", homedir::home("alice")?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub const fn is_supported() -> bool {
+    cfg!(all(
+        not(feature = "env-only"),
+        any(
+            windows,
+            all(
+                unix,
+                not(any(
+                    target_os = "ios",
+                    target_os = "tvos",
+                    target_os = "watchos",
+                    target_os = "visionos",
+                    target_os = "espidf",
+                    target_os = "horizon",
+                    target_os = "nuttx",
+                    target_os = "rtems",
+                    target_os = "vita",
+                ))
+            )
+        )
+    ))
+}
+
 impl UserIdentifier {
     /// Get the user identifier of an arbitrary user.
     ///
     /// There is an example of the usage of this function in the
     /// [structure's documentation](UserIdentifier).
     pub fn with_username<S: AsRef<str>>(username: S) -> Result<Option<Self>, GetHomeError> {
-        match UserIdentifierImp::with_username(username.as_ref()) {
+        let username = username.as_ref();
+        let ret = match UserIdentifierImp::with_username(username) {
             Ok(v) => Ok(v.map(Self)),
             Err(e) => Err(GetHomeError(e)),
-        }
+        };
+        audit_username(username, &ret);
+        ret
+    }
+
+    /// Get the user identifier of an arbitrary user given a username which may not be valid
+    /// UTF-8. This is otherwise identical to [`UserIdentifier::with_username`].
+    pub fn with_username_os<S: AsRef<OsStr>>(username: S) -> Result<Option<Self>, GetHomeError> {
+        let username = username.as_ref();
+        let ret = match UserIdentifierImp::with_username_os(username) {
+            Ok(v) => Ok(v.map(Self)),
+            Err(e) => Err(GetHomeError(e)),
+        };
+        audit_username(username, &ret);
+        ret
+    }
+
+    /// Get the user identifier of an arbitrary user, matching the username case-insensitively,
+    /// along with the canonical name of the account which was matched. This is useful to compare
+    /// names supplied by users with the names the system reports, for instance in access lists.
+    ///
+    /// On Windows, account names are always matched case-insensitively. On Unix, if no user has
+    /// exactly this name, the user database is enumerated to find one whose name only differs in
+    /// case, see `unix::PasswdLookup::ignore_case`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::UserIdentifier;
+    ///
+    /// # fn main() -> Result<(), homedir::GetHomeError> {
+    /// if let Some((id, name)) = UserIdentifier::with_username_ignore_case("Alice")? {
+    ///     println!("{name}: {:?}", id.to_home()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_username_ignore_case<S: AsRef<str>>(
+        username: S,
+    ) -> Result<Option<(Self, String)>, GetHomeError> {
+        let username = username.as_ref();
+        let ret = match UserIdentifierImp::with_username_ignore_case(username) {
+            Ok(v) => Ok(v.map(|(id, name)| (Self(id), name))),
+            Err(e) => Err(GetHomeError(e)),
+        };
+        audit_username(username, &ret);
+        ret
     }
 
     /// Get the user identifier of an arbitrary user.
@@ -190,7 +1294,77 @@ impl UserIdentifier {
     /// There is an example of the usage of this function in the
     /// [structure's documentation](UserIdentifier).
     pub fn to_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
-        self.0.to_home().map_err(GetHomeError)
+        let ret = self.0.to_home().map_err(GetHomeError);
+        self.audit(LookupOutcome::of(&ret));
+        ret
+    }
+
+    /// Get the home directory of the user with this identifier, writing it into `buf`. If it is
+    /// found, `Ok(true)` is returned. Otherwise, `buf` is cleared and `Ok(false)` is returned.
+    /// See [`my_home_into`].
+    pub fn to_home_into(&self, buf: &mut PathBuf) -> Result<bool, GetHomeError> {
+        cfg_if! {
+            if #[cfg(any(
+                all(
+                    unix,
+                    not(feature = "env-only"),
+                    not(any(
+                        target_os = "ios",
+                        target_os = "tvos",
+                        target_os = "watchos",
+                        target_os = "visionos",
+                    )),
+                ),
+                all(windows, not(feature = "env-only")),
+            ))] {
+                let ret = self.0.to_home_into(buf).map_err(GetHomeError);
+            } else {
+                let ret = self
+                    .0
+                    .to_home()
+                    .map(|v| replace_path_opt(buf, v.as_deref()))
+                    .map_err(GetHomeError);
+            }
+        }
+        self.audit(match ret {
+            Ok(true) => LookupOutcome::Found,
+            Ok(false) => LookupOutcome::NotFound,
+            Err(_) => LookupOutcome::Failed,
+        });
+        ret
+    }
+
+    /// Get information about the user with this identifier. On Unix, if the identifier does
+    /// not belong to any user, `Ok(None)` is returned.
+    pub fn to_user_info(&self) -> Result<Option<UserInfo>, GetHomeError> {
+        cfg_if! {
+            if #[cfg(all(windows, not(feature = "env-only")))] {
+                let ret = self.0.to_user_info().map(|v| Some(UserInfo(v))).map_err(GetHomeError);
+            } else {
+                let ret = self.0.to_user_info().map(|v| v.map(UserInfo)).map_err(GetHomeError);
+            }
+        }
+        self.audit(LookupOutcome::of(&ret));
+        ret
+    }
+
+    /// Report a lookup of the user with this identifier to the audit hook.
+    fn audit(&self, outcome: LookupOutcome) {
+        audit(AuditedUser::Id(self), None, outcome);
+    }
+
+    /// Get the home directory of the user with this identifier and check that it exists. This is
+    /// otherwise identical to [`home_existing`].
+    pub fn to_home_existing(&self) -> Result<Option<ExistingHome>, GetHomeError> {
+        Ok(self.to_home()?.map(ExistingHome::new))
+    }
+
+    /// Get the home directory of the user with this identifier joined with a relative path. This
+    /// is otherwise identical to [`home_join`].
+    pub fn home_join<P: AsRef<Path>>(&self, relative: P) -> Result<Option<PathBuf>, GetHomeError> {
+        let relative = relative.as_ref();
+        check_relative(relative)?;
+        Ok(self.to_home()?.map(|home| home.join(relative)))
     }
 
     /// Get the user identifier of the process' current user.
@@ -202,6 +1376,107 @@ impl UserIdentifier {
     }
 }
 
+impl UserInfo {
+    /// The home directory of the user, or `None` if they do not have one.
+    pub fn home(&self) -> Option<&Path> {
+        self.0.home()
+    }
+
+    /// The username of the user, or `None` if it is not known or not valid UTF-8. On Windows,
+    /// this is the name of the account without its domain, and requires the `windows-rs`
+    /// feature.
+    pub fn username(&self) -> Option<&str> {
+        self.0.username()
+    }
+
+    /// The login shell of the user. This is always `None` on Windows.
+    pub fn shell(&self) -> Option<&Path> {
+        self.0.shell()
+    }
+
+    /// The full name of the user, such as `Jane Doe`, or `None` if it is not set. On Unix, this
+    /// is obtained from the GECOS field. On Windows, this requires the `windows-rs` feature.
+    pub fn full_name(&self) -> Option<&str> {
+        self.0.full_name()
+    }
+}
+
+impl PrimaryGroup {
+    /// The name of the group, or `None` if it could not be resolved.
+    pub fn name(&self) -> Option<&str> {
+        self.0.name()
+    }
+}
+
+impl ExistingHome {
+    fn new(path: PathBuf) -> Self {
+        if path.is_dir() {
+            Self::Present(path)
+        } else {
+            Self::Missing(path)
+        }
+    }
+
+    /// The path of the home directory, whether or not it exists.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Present(v) | Self::Missing(v) => v,
+        }
+    }
+
+    /// Whether the home directory exists and is a directory.
+    pub fn exists(&self) -> bool {
+        matches!(self, Self::Present(_))
+    }
+
+    /// Convert this into the path of the home directory.
+    pub fn into_path(self) -> PathBuf {
+        match self {
+            Self::Present(v) | Self::Missing(v) => v,
+        }
+    }
+}
+
+impl HomeLookup {
+    /// The path of the home directory, if one was found.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Found(v) => Some(v),
+            Self::UserExistsButNoHome | Self::NoSuchUser => None,
+        }
+    }
+
+    /// Whether the user exists, whether or not they have a home directory.
+    pub fn user_exists(&self) -> bool {
+        !matches!(self, Self::NoSuchUser)
+    }
+
+    /// Convert this into the path of the home directory, if one was found.
+    pub fn into_path(self) -> Option<PathBuf> {
+        match self {
+            Self::Found(v) => Some(v),
+            Self::UserExistsButNoHome | Self::NoSuchUser => None,
+        }
+    }
+}
+
+impl GetHomeError {
+    /// Check whether this error was returned because the operation is not supported on this
+    /// target or with the enabled features, rather than because it failed. See [`is_supported`].
+    ///
+    /// On Windows, this is also the case when looking up other users from a packaged app running
+    /// in an app container.
+    pub fn is_unsupported(&self) -> bool {
+        matches!(self.0, GetHomeErrorImp::Unsupported)
+    }
+
+    /// Check whether this error was returned because the relative path passed to [`home_join`]
+    /// or [`UserIdentifier::home_join`] was absolute or left the home directory.
+    pub fn is_invalid_path(&self) -> bool {
+        matches!(self.0, GetHomeErrorImp::InvalidPath)
+    }
+}
+
 impl fmt::Display for GetHomeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         <GetHomeErrorImp as fmt::Display>::fmt(&self.0, f)
@@ -226,6 +1501,30 @@ impl From<GetHomeErrorImp> for GetHomeError {
     }
 }
 
+impl From<UserInfo> for UserInfoImp {
+    fn from(value: UserInfo) -> Self {
+        value.0
+    }
+}
+
+impl From<UserInfoImp> for UserInfo {
+    fn from(value: UserInfoImp) -> Self {
+        Self(value)
+    }
+}
+
+impl From<PrimaryGroup> for PrimaryGroupImp {
+    fn from(value: PrimaryGroup) -> Self {
+        value.0
+    }
+}
+
+impl From<PrimaryGroupImp> for PrimaryGroup {
+    fn from(value: PrimaryGroupImp) -> Self {
+        Self(value)
+    }
+}
+
 impl From<UserIdentifier> for UserIdentifierImp {
     fn from(value: UserIdentifier) -> Self {
         value.0
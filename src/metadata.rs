@@ -0,0 +1,176 @@
+// src/metadata.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The state of a home directory on disk, see [`home_metadata`].
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use cfg_if::cfg_if;
+
+use crate::{GetHomeError, UserIdentifier};
+
+/// The state of a home directory on disk, returned by [`home_metadata`].
+#[derive(Clone, Debug)]
+pub struct HomeMetadata {
+    path: PathBuf,
+    error: Option<io::ErrorKind>,
+    is_dir: bool,
+    readonly: bool,
+    owner: Option<UserIdentifier>,
+    mode: Option<u32>,
+    acl_entries: Option<u32>,
+    filesystem: Option<String>,
+}
+
+/// Get the home directory of the user with the given identifier, along with whether it exists,
+/// its owner, its permissions, and the type of the filesystem it is on. If the user has no home
+/// directory, `Ok(None)` is returned. If it does not exist or its metadata cannot be read, the
+/// result has [`HomeMetadata::exists`] false and none of the other details.
+///
+/// Symbolic links are followed, so that the directory they lead to is described. The details
+/// which cannot be determined on the target are `None`: the owner is reported on Unix and
+/// Windows, the permission bits on Unix, and the number of entries of the access control list
+/// on Windows. Whether the directory is owned by the user can be checked with
+/// [`verify_ownership`](crate::verify_ownership).
+///
+/// # Example
+/// ```no_run
+/// use homedir::{home_metadata, UserIdentifier};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let id = UserIdentifier::with_username("alice")?.ok_or("no such user")?;
+/// if let Some(metadata) = home_metadata(&id)? {
+///     println!(
+///         "{} exists: {}, owner: {:?}, mode: {:o}, filesystem: {:?}",
+///         metadata.path().display(),
+///         metadata.exists(),
+///         metadata.owner(),
+///         metadata.mode().unwrap_or(0),
+///         metadata.filesystem(),
+///     );
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_metadata(id: &UserIdentifier) -> Result<Option<HomeMetadata>, GetHomeError> {
+    let Some(path) = id.to_home()? else {
+        return Ok(None);
+    };
+    Ok(Some(HomeMetadata::of(path)))
+}
+
+impl HomeMetadata {
+    /// Read the metadata of a directory.
+    fn of(path: PathBuf) -> Self {
+        let metadata = match fs::metadata(&path) {
+            Ok(v) => v,
+            Err(e) => {
+                return Self {
+                    path,
+                    error: Some(e.kind()),
+                    is_dir: false,
+                    readonly: false,
+                    owner: None,
+                    mode: None,
+                    acl_entries: None,
+                    filesystem: None,
+                }
+            }
+        };
+        // the owner, the permission bits, the number of access control entries and the filesystem.
+        let (owner, mode, acl_entries, filesystem) = {
+            cfg_if! {
+                if #[cfg(all(unix, not(feature = "env-only"), not(any(
+                    target_os = "ios",
+                    target_os = "tvos",
+                    target_os = "watchos",
+                    target_os = "visionos",
+                ))))] {
+                    use crate::unix::{Uid, UserIdentifier as UnixIdentifier};
+                    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+                    (
+                        Some(UnixIdentifier::from(Uid::from_raw(metadata.uid())).into()),
+                        Some(metadata.permissions().mode() & 0o7777),
+                        None,
+                        crate::unix::filesystem_type(&path),
+                    )
+                } else if #[cfg(all(windows, not(feature = "env-only")))] {
+                    let (owner, acl_entries) = match crate::windows::file_security(&path) {
+                        Ok((owner, acl_entries)) => (Some(owner.into()), acl_entries),
+                        Err(_) => (None, None),
+                    };
+                    (owner, None, acl_entries, crate::windows::filesystem_type(&path))
+                } else {
+                    (None, None, None, None)
+                }
+            }
+        };
+        Self {
+            error: None,
+            is_dir: metadata.is_dir(),
+            readonly: metadata.permissions().readonly(),
+            owner,
+            mode,
+            acl_entries,
+            filesystem,
+            path,
+        }
+    }
+
+    /// The home directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the home directory exists and its metadata could be read.
+    pub fn exists(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The kind of the error which occurred when reading the metadata of the home directory, for
+    /// instance [`NotFound`](io::ErrorKind::NotFound) if it does not exist.
+    pub fn error(&self) -> Option<io::ErrorKind> {
+        self.error
+    }
+
+    /// Whether the home directory is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// Whether the home directory is read-only, see [`std::fs::Permissions::readonly`].
+    pub fn readonly(&self) -> bool {
+        self.readonly
+    }
+
+    /// The owner of the home directory, on Unix and Windows.
+    pub fn owner(&self) -> Option<&UserIdentifier> {
+        self.owner.as_ref()
+    }
+
+    /// The permission bits of the home directory, including the set-user-id, set-group-id and
+    /// sticky bits, on Unix.
+    pub fn mode(&self) -> Option<u32> {
+        self.mode
+    }
+
+    /// The number of entries in the discretionary access control list of the home directory, on
+    /// Windows. It is `None` if the directory has no such list, which grants everyone full
+    /// access, or if it could not be read.
+    pub fn acl_entries(&self) -> Option<u32> {
+        self.acl_entries
+    }
+
+    /// The type of the filesystem the home directory is on, such as `ext2/ext3/ext4`, `nfs`,
+    /// `apfs` or `NTFS`, if it can be determined. On Linux, the filesystems which are not
+    /// recognized are given by their magic number, such as `0x1234`.
+    pub fn filesystem(&self) -> Option<&str> {
+        self.filesystem.as_deref()
+    }
+}
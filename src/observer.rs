@@ -0,0 +1,244 @@
+// src/observer.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Hooks to monitor the lookups of home directories, see [`LookupObserver`].
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{Backend, GetHomeError};
+
+/// A hook called by [`HomeResolver`](crate::HomeResolver) and
+/// [`CachedResolver`](crate::CachedResolver) as they look up home directories, so that the
+/// services which embed this crate can monitor the health of the user database or directory
+/// service, for instance by exporting metrics. Both methods do nothing by default.
+///
+/// The hook is called on the thread doing the lookup, so it should return quickly.
+/// [`LookupCounters`] is an implementation which counts the events.
+///
+/// # Example
+/// ```no_run
+/// use homedir::{HomeResolver, LookupEvent, LookupObserver, LookupOutcome};
+///
+/// /// Reports the slow and failed lookups.
+/// struct SlowLookups;
+///
+/// impl LookupObserver for SlowLookups {
+///     fn lookup(&self, event: &LookupEvent) {
+///         if event.outcome() == LookupOutcome::Failed || event.elapsed().as_secs() > 1 {
+///             eprintln!("{:?} took {:?}", event.backend(), event.elapsed());
+///         }
+///     }
+/// }
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// let resolver = HomeResolver::new().observer(SlowLookups);
+/// println!("{:?}", resolver.home("alice")?);
+/// # Ok(())
+/// # }
+/// ```
+pub trait LookupObserver: Send + Sync {
+    /// Called after each backend or provider is queried.
+    fn lookup(&self, event: &LookupEvent) {
+        let _ = event;
+    }
+
+    /// Called when a home directory is looked up in a cache, with whether it was found there.
+    fn cache(&self, hit: bool) {
+        let _ = hit;
+    }
+}
+
+impl fmt::Debug for dyn LookupObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LookupObserver")
+    }
+}
+
+impl<T: LookupObserver + ?Sized> LookupObserver for Arc<T> {
+    fn lookup(&self, event: &LookupEvent) {
+        (**self).lookup(event);
+    }
+
+    fn cache(&self, hit: bool) {
+        (**self).cache(hit);
+    }
+}
+
+/// The result of a query to a backend or provider, see [`LookupEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LookupOutcome {
+    /// A home directory was found.
+    Found,
+    /// The user or their home directory was not found.
+    NotFound,
+    /// The query failed with an error.
+    Failed,
+}
+
+impl LookupOutcome {
+    /// The outcome of a lookup which returned the given result.
+    pub(crate) fn of<T>(result: &Result<Option<T>, GetHomeError>) -> Self {
+        match result {
+            Ok(Some(_)) => Self::Found,
+            Ok(None) => Self::NotFound,
+            Err(_) => Self::Failed,
+        }
+    }
+}
+
+/// A query to a backend or provider, passed to [`LookupObserver::lookup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LookupEvent {
+    backend: Option<Backend>,
+    current_user: bool,
+    outcome: LookupOutcome,
+    elapsed: Duration,
+}
+
+impl LookupEvent {
+    /// The backend which was queried, or `None` for a [`HomeProvider`](crate::HomeProvider),
+    /// such as those added with [`HomeResolver::push_backend`](crate::HomeResolver::push_backend)
+    /// and the one of a [`CachedResolver`](crate::CachedResolver).
+    pub fn backend(&self) -> Option<Backend> {
+        self.backend
+    }
+
+    /// Whether the home directory of the process' current user was looked up, rather than that
+    /// of a user given by their username or identifier.
+    pub fn current_user(&self) -> bool {
+        self.current_user
+    }
+
+    /// The result of the query.
+    pub fn outcome(&self) -> LookupOutcome {
+        self.outcome
+    }
+
+    /// The time the query took.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// A [`LookupObserver`] which counts the lookups, their outcomes, and the cache hits and misses,
+/// and adds up the time spent in the lookups. It can be shared with the resolvers through an
+/// [`Arc`], and read at any time.
+///
+/// # Example
+/// ```no_run
+/// use homedir::{HomeResolver, LookupCounters};
+/// use std::sync::Arc;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// let counters = Arc::new(LookupCounters::new());
+/// let resolver = HomeResolver::new().cache(true).observer(Arc::clone(&counters));
+/// for _ in 0..10 {
+///     resolver.home("alice")?;
+/// }
+/// assert_eq!(9, counters.cache_hits());
+/// println!("{} failed lookups", counters.failed());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct LookupCounters {
+    lookups: AtomicU64,
+    found: AtomicU64,
+    not_found: AtomicU64,
+    failed: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    time_nanos: AtomicU64,
+}
+
+impl LookupCounters {
+    /// Construct counters which are all zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of queries to backends and providers.
+    pub fn lookups(&self) -> u64 {
+        self.lookups.load(Ordering::Relaxed)
+    }
+
+    /// The number of queries which found a home directory.
+    pub fn found(&self) -> u64 {
+        self.found.load(Ordering::Relaxed)
+    }
+
+    /// The number of queries which did not find a home directory.
+    pub fn not_found(&self) -> u64 {
+        self.not_found.load(Ordering::Relaxed)
+    }
+
+    /// The number of queries which failed.
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// The number of home directories found in a cache.
+    pub fn cache_hits(&self) -> u64 {
+        self.cache_hits.load(Ordering::Relaxed)
+    }
+
+    /// The number of home directories which were not in a cache, or had expired.
+    pub fn cache_misses(&self) -> u64 {
+        self.cache_misses.load(Ordering::Relaxed)
+    }
+
+    /// The total time spent in the queries.
+    pub fn total_time(&self) -> Duration {
+        Duration::from_nanos(self.time_nanos.load(Ordering::Relaxed))
+    }
+}
+
+impl LookupObserver for LookupCounters {
+    fn lookup(&self, event: &LookupEvent) {
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        let outcome = match event.outcome {
+            LookupOutcome::Found => &self.found,
+            LookupOutcome::NotFound => &self.not_found,
+            LookupOutcome::Failed => &self.failed,
+        };
+        outcome.fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(event.elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.time_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    fn cache(&self, hit: bool) {
+        let counter = if hit {
+            &self.cache_hits
+        } else {
+            &self.cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Notify an observer of a query, if there is one.
+pub(crate) fn observe<T>(
+    observer: Option<&dyn LookupObserver>,
+    backend: Option<Backend>,
+    current_user: bool,
+    elapsed: Duration,
+    result: &Result<Option<T>, GetHomeError>,
+) {
+    if let Some(observer) = observer {
+        observer.lookup(&LookupEvent {
+            backend,
+            current_user,
+            outcome: LookupOutcome::of(result),
+            elapsed,
+        });
+    }
+}
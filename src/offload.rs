@@ -0,0 +1,161 @@
+// src/offload.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Asynchronous lookups, which run the blocking lookups on a separate thread so that they do not
+//! stall the asynchronous runtime, for instance when NSS queries a slow LDAP server.
+//!
+//! These do not depend on any executor. With the `tokio` feature, the lookups which are awaited
+//! within a Tokio runtime run on its blocking thread pool. Otherwise, each lookup starts a new
+//! thread, which notifies the task awaiting it when it completes.
+
+use std::{
+    future::Future,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll, Waker},
+    thread,
+    thread::Result as ThreadResult,
+};
+
+use crate::{GetHomeError, HomeResolver};
+
+/// Run a blocking function on another thread.
+///
+/// # Panics
+/// This panics if the function panics, or if the Tokio runtime running it shuts down before it
+/// completes.
+async fn unblock<T, F>(f: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    #[cfg(feature = "tokio")]
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return match tokio::task::spawn_blocking(f).await {
+            Ok(v) => v,
+            Err(e) => resume_unwind(e.into_panic()),
+        };
+    }
+    let state = Arc::new(Mutex::new(State {
+        result: None,
+        waker: None,
+    }));
+    let thread_state = Arc::clone(&state);
+    thread::spawn(move || {
+        let result = catch_unwind(AssertUnwindSafe(f));
+        let mut state = thread_state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+    match (Pending { state }).await {
+        Ok(v) => v,
+        Err(e) => resume_unwind(e),
+    }
+}
+
+/// The state shared by a thread started by [`unblock`] and the task awaiting it.
+struct State<T> {
+    result: Option<ThreadResult<T>>,
+    waker: Option<Waker>,
+}
+
+/// The future of the result of a thread started by [`unblock`].
+struct Pending<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Future for Pending<T> {
+    type Output = ThreadResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match state.result.take() {
+            Some(v) => Poll::Ready(v),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Get the home directory of an arbitrary user without blocking the asynchronous runtime. This
+/// runs [`home`](crate::home) on another thread, which works with any executor. With the `tokio`
+/// feature, the blocking thread pool of the current Tokio runtime is used if there is one.
+///
+/// This requires the `async` feature, which the `tokio` feature enables.
+///
+/// # Panics
+/// This panics if the Tokio runtime running the lookup shuts down before it completes.
+///
+/// # Example
+/// ```no_run
+/// use homedir::home_async;
+///
+/// # async fn f() -> Result<(), homedir::GetHomeError> {
+/// if let Some(home) = home_async("alice").await? {
+///     println!("{}", home.display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn home_async<S: Into<String>>(username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    let username = username.into();
+    unblock(move || crate::home(username)).await
+}
+
+/// Get the home directory of the process' current user without blocking the asynchronous
+/// runtime. This runs [`my_home`](crate::my_home) on another thread, like
+/// [`home_async`](crate::home_async).
+///
+/// This requires the `async` feature, which the `tokio` feature enables.
+///
+/// # Panics
+/// This panics if the Tokio runtime running the lookup shuts down before it completes.
+pub async fn my_home_async() -> Result<Option<PathBuf>, GetHomeError> {
+    unblock(crate::my_home).await
+}
+
+impl HomeResolver {
+    /// Get the home directory of an arbitrary user without blocking the asynchronous runtime,
+    /// like [`HomeResolver::home`]. The resolver is shared with the thread of the lookup, so it
+    /// must be in an [`Arc`].
+    ///
+    /// This requires the `async` feature, and panics under the same conditions as
+    /// [`home_async`](crate::home_async).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::HomeResolver;
+    /// use std::sync::Arc;
+    ///
+    /// # async fn f() -> Result<(), homedir::GetHomeError> {
+    /// let resolver = Arc::new(HomeResolver::new().cache(true));
+    /// println!("{:?}", Arc::clone(&resolver).home_async("alice").await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn home_async<S: Into<String>>(
+        self: Arc<Self>,
+        username: S,
+    ) -> Result<Option<PathBuf>, GetHomeError> {
+        let username = username.into();
+        unblock(move || self.home(username)).await
+    }
+
+    /// Get the home directory of the process' current user without blocking the asynchronous
+    /// runtime, like [`HomeResolver::my_home`]. The resolver is shared with the thread of the
+    /// lookup, so it must be in an [`Arc`].
+    ///
+    /// This requires the `async` feature, and panics under the same conditions as
+    /// [`my_home_async`](crate::my_home_async).
+    pub async fn my_home_async(self: Arc<Self>) -> Result<Option<PathBuf>, GetHomeError> {
+        unblock(move || self.my_home()).await
+    }
+}
@@ -0,0 +1,116 @@
+// src/options.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The options of the lookup of the current user's home directory, see [`LookupOptions`].
+
+use std::{ffi::OsString, path::Path, path::PathBuf};
+
+use crate::{default_backends_imp, env_var, Backend, EnvSource, GetHomeError, HomeResolver};
+
+/// The order in which [`LookupOptions::my_home`] reads the environment and the user database.
+///
+/// On the targets where [`my_home`](crate::my_home) does not read the environment, such as
+/// Windows, all the orders are the same. On those where it only reads the environment, such as
+/// the WebAssembly System Interface, [`DatabaseOnly`](LookupOrder::DatabaseOnly) never finds a
+/// home directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LookupOrder {
+    /// Read the environment, then the user database if the home directory is not set. This is
+    /// the behaviour of [`my_home`](crate::my_home), and the default.
+    #[default]
+    EnvFirst,
+    /// Read the user database, then the environment if the current user cannot be found in it.
+    /// This is the behaviour of `my_home` in version 0.1.0 with the `check_env` feature.
+    DatabaseFirst,
+    /// Only read the user database. This is the behaviour of `my_home` in version 0.1.0.
+    DatabaseOnly,
+}
+
+/// Options controlling how the home directory of the current user is looked up, for the programs
+/// which need a different behaviour than [`my_home`](crate::my_home).
+///
+/// # Example
+/// ```no_run
+/// use homedir::{LookupOptions, LookupOrder};
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// let home = LookupOptions::new()
+///     .order(LookupOrder::DatabaseFirst)
+///     .validate_env(true)
+///     .my_home()?;
+/// println!("{home:?}");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LookupOptions {
+    order: LookupOrder,
+    require_absolute_env: bool,
+    validate_env: bool,
+}
+
+impl LookupOptions {
+    /// Construct the options of [`my_home`](crate::my_home).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the order in which the environment and the user database are read.
+    pub fn order(mut self, order: LookupOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Set whether a home directory read from the environment must be an absolute path. If it is
+    /// not, such as an empty `HOME` or `HOME=.` as exported by some init systems and cron
+    /// implementations, it is ignored as if it was not set. This is disabled by default, as in
+    /// [`my_home`](crate::my_home), which returns `HOME` unchanged.
+    pub fn require_absolute_env(mut self, require_absolute_env: bool) -> Self {
+        self.require_absolute_env = require_absolute_env;
+        self
+    }
+
+    /// Set whether a home directory read from the environment must be an existing directory. If
+    /// it is not, it is ignored as if it was not set. This implies
+    /// [`require_absolute_env`](Self::require_absolute_env).
+    pub fn validate_env(mut self, validate_env: bool) -> Self {
+        self.validate_env = validate_env;
+        self
+    }
+
+    /// Get the home directory of the process' current user with these options.
+    pub fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        let mut backends = default_backends_imp();
+        if let Some(i) = backends.iter().position(|&v| v == Backend::Env) {
+            match self.order {
+                LookupOrder::EnvFirst => {}
+                LookupOrder::DatabaseFirst => {
+                    let env = backends.remove(i);
+                    backends.push(env);
+                }
+                LookupOrder::DatabaseOnly => {
+                    backends.remove(i);
+                }
+            }
+        }
+        let resolver = HomeResolver::new()
+            .backends(backends)
+            .require_absolute_env(self.require_absolute_env || self.validate_env);
+        if self.validate_env {
+            resolver.env_source(ExistingDirs).my_home()
+        } else {
+            resolver.my_home()
+        }
+    }
+}
+
+/// The environment of the process, without the variables which are not existing directories.
+struct ExistingDirs;
+
+impl EnvSource for ExistingDirs {
+    fn var(&self, key: &str) -> Option<OsString> {
+        env_var(key).filter(|v| Path::new(v).is_dir())
+    }
+}
@@ -0,0 +1,142 @@
+// src/ownership.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Checks that home directories belong to their users, see [`verify_ownership`].
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use cfg_if::cfg_if;
+
+use crate::{GetHomeError, UserIdentifier};
+
+/// The error type returned by [`verify_ownership`] and [`UserIdentifier::to_home_verified`].
+#[derive(Debug)]
+pub enum OwnershipError {
+    /// The home directory or its owner could not be looked up. On targets where the owner of a
+    /// file cannot be determined, this is an error for which [`GetHomeError::is_unsupported`] is
+    /// true.
+    Lookup(GetHomeError),
+    /// The metadata of the directory could not be read, for instance because it does not exist.
+    Io(io::Error),
+    /// The directory is owned by another user.
+    NotOwned {
+        /// The directory which was checked.
+        path: PathBuf,
+        /// The user who owns it.
+        owner: UserIdentifier,
+    },
+}
+
+/// Check that a directory, typically a home directory, is owned by the given user: on Unix, that
+/// its owner is the user's id, and on Windows, that its owner is the user's SID. Symbolic links
+/// are followed, so that the directory they lead to is checked. If it is owned by another user,
+/// [`OwnershipError::NotOwned`] is returned.
+///
+/// Privileged programs which write into the home directories of other users, such as daemons
+/// running as `root`, should check this first, as a home directory which was reassigned or
+/// replaced by another user's directory would otherwise let them write files on behalf of the
+/// wrong user. On Windows, profile directories created by an administrator may be owned by the
+/// `Administrators` group rather than by the user, and fail this check.
+///
+/// # Example
+/// ```no_run
+/// use homedir::{verify_ownership, OwnershipError, UserIdentifier};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let id = UserIdentifier::with_username("alice")?.ok_or("no such user")?;
+/// if let Some(home) = id.to_home()? {
+///     match verify_ownership(&home, &id) {
+///         Ok(()) => println!("{} belongs to alice", home.display()),
+///         Err(OwnershipError::NotOwned { owner, .. }) => println!("owned by {owner:?}"),
+///         Err(e) => return Err(e.into()),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify_ownership<P: AsRef<Path>>(
+    path: P,
+    id: &UserIdentifier,
+) -> Result<(), OwnershipError> {
+    let path = path.as_ref();
+    let owner = owner_of(path)?;
+    if owner == *id {
+        Ok(())
+    } else {
+        Err(OwnershipError::NotOwned {
+            path: path.to_owned(),
+            owner,
+        })
+    }
+}
+
+impl UserIdentifier {
+    /// Get the home directory of the user with this identifier, and check that it is owned by
+    /// them with [`verify_ownership`]. If the user has no home directory, `Ok(None)` is returned.
+    pub fn to_home_verified(&self) -> Result<Option<PathBuf>, OwnershipError> {
+        let Some(home) = self.to_home()? else {
+            return Ok(None);
+        };
+        verify_ownership(&home, self)?;
+        Ok(Some(home))
+    }
+}
+
+/// Get the owner of a file or directory.
+fn owner_of(path: &Path) -> Result<UserIdentifier, OwnershipError> {
+    let metadata = fs::metadata(path).map_err(OwnershipError::Io)?;
+    cfg_if! {
+        if #[cfg(all(unix, not(feature = "env-only"), not(any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos",
+            target_os = "visionos",
+        ))))] {
+            use crate::unix::{Uid, UserIdentifier as UnixIdentifier};
+            use std::os::unix::fs::MetadataExt;
+
+            Ok(UnixIdentifier::from(Uid::from_raw(metadata.uid())).into())
+        } else if #[cfg(all(windows, not(feature = "env-only")))] {
+            let _ = metadata;
+            crate::windows::file_security(path)
+                .map(|(owner, _)| UserIdentifier::from(owner))
+                .map_err(|e| OwnershipError::Lookup(e.into()))
+        } else {
+            let _ = metadata;
+            Err(OwnershipError::Lookup(GetHomeError(
+                crate::GetHomeErrorImp::Unsupported,
+            )))
+        }
+    }
+}
+
+impl From<GetHomeError> for OwnershipError {
+    fn from(value: GetHomeError) -> Self {
+        Self::Lookup(value)
+    }
+}
+
+impl fmt::Display for OwnershipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lookup(e) => e.fmt(f),
+            Self::Io(e) => write!(f, "the directory cannot be read: {e}"),
+            Self::NotOwned { path, .. } => write!(f, "{} is owned by another user", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for OwnershipError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Lookup(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::NotOwned { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,155 @@
+// src/provider.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A trait for the sources of home directories, see [`HomeProvider`].
+
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use crate::{GetHomeError, HomeResolver, UserIdentifier};
+
+/// A source of home directories. Applications which need their own policy, such as overrides
+/// configured by an administrator or a remote agent which knows the users, can implement this
+/// trait, and code which only needs home directories can take any implementation of it.
+///
+/// This crate provides [`SystemProvider`], which does the same lookups as the functions at the
+/// root of this crate, and implements this trait for [`HomeResolver`].
+///
+/// # Example
+/// ```no_run
+/// use homedir::{GetHomeError, HomeProvider, SystemProvider, UserIdentifier};
+/// use std::collections::HashMap;
+/// use std::path::PathBuf;
+///
+/// /// Users whose home directories are configured by the administrator.
+/// struct Overrides {
+///     homes: HashMap<String, PathBuf>,
+/// }
+///
+/// impl HomeProvider for Overrides {
+///     fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+///         match self.homes.get(username) {
+///             Some(home) => Ok(Some(home.clone())),
+///             None => SystemProvider.home(username),
+///         }
+///     }
+///
+///     fn home_of(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+///         match id.to_user_info()?.as_ref().and_then(|v| v.username()) {
+///             Some(username) => self.home(username),
+///             None => SystemProvider.home_of(id),
+///         }
+///     }
+///
+///     fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+///         self.home_of(&UserIdentifier::my_id()?)
+///     }
+/// }
+/// ```
+pub trait HomeProvider: Send + Sync {
+    /// Get the home directory of a user given their username. If there is no such user,
+    /// `Ok(None)` is returned.
+    ///
+    /// By default, the user is looked up with [`UserIdentifier::with_username`], and their home
+    /// directory with [`home_of`](HomeProvider::home_of).
+    fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+        match UserIdentifier::with_username(username)? {
+            Some(id) => self.home_of(&id),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the home directory of the user with the given identifier. If there is no such user,
+    /// `Ok(None)` is returned.
+    fn home_of(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError>;
+
+    /// Get the home directory of the process' current user.
+    fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError>;
+}
+
+impl fmt::Debug for dyn HomeProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HomeProvider")
+    }
+}
+
+/// The lookups of the current target, as done by [`home`](crate::home),
+/// [`UserIdentifier::to_home`], and [`my_home`](crate::my_home).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SystemProvider;
+
+impl HomeProvider for SystemProvider {
+    fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+        crate::home(username)
+    }
+
+    fn home_of(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+        id.to_home()
+    }
+
+    fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        crate::my_home()
+    }
+}
+
+/// The users are looked up through the backends of the resolver. A user given by their
+/// identifier is looked up by the username obtained with [`UserIdentifier::to_user_info`].
+impl HomeProvider for HomeResolver {
+    fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+        HomeResolver::home(self, username)
+    }
+
+    fn home_of(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+        match id.to_user_info()?.as_ref().and_then(|v| v.username()) {
+            Some(username) => HomeResolver::home(self, username),
+            None => Ok(None),
+        }
+    }
+
+    fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        HomeResolver::my_home(self)
+    }
+}
+
+impl<T: HomeProvider + ?Sized> HomeProvider for &T {
+    fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+        (**self).home(username)
+    }
+
+    fn home_of(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+        (**self).home_of(id)
+    }
+
+    fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        (**self).my_home()
+    }
+}
+
+impl<T: HomeProvider + ?Sized> HomeProvider for Box<T> {
+    fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+        (**self).home(username)
+    }
+
+    fn home_of(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+        (**self).home_of(id)
+    }
+
+    fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        (**self).my_home()
+    }
+}
+
+impl<T: HomeProvider + ?Sized> HomeProvider for Arc<T> {
+    fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+        (**self).home(username)
+    }
+
+    fn home_of(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+        (**self).home_of(id)
+    }
+
+    fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        (**self).my_home()
+    }
+}
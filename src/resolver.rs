@@ -0,0 +1,579 @@
+// src/resolver.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A configurable lookup of home directories, see [`HomeResolver`].
+
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fmt,
+    hash::BuildHasher,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use cfg_if::cfg_if;
+
+use crate::audit::audit;
+use crate::observer::observe;
+#[cfg(feature = "test-util")]
+use crate::testing::MockUsers;
+use crate::{
+    backend_home_imp, default_backends_imp, env_var, AuditedUser, Explanation, GetHomeError,
+    HomeProvider, LookupObserver, LookupOutcome,
+};
+
+/// The environment variable read by [`Backend::Env`].
+const HOME_VAR: &str = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+
+/// A mechanism through which a home directory can be looked up, see
+/// [`HomeResolver::backends`], [`home_with_backend`](crate::home_with_backend), and
+/// [`my_home_with_backend`](crate::my_home_with_backend).
+///
+/// All the variants exist on every target. Looking up a home directory through a backend which
+/// is not available on the current target, or which requires a feature which is not enabled,
+/// fails with an error for which [`GetHomeError::is_unsupported`] is true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Backend {
+    /// The `HOME` environment variable, or `USERPROFILE` on Windows. Values which are not
+    /// absolute paths are ignored, unless [`HomeResolver::require_absolute_env`] is disabled. This is only used for the current user, and never finds a
+    /// home directory with the `no-env` feature.
+    Env,
+    /// The C library on Unix, through `getpwnam_r(3)` or `getpwuid_r(3)`, which includes the
+    /// users provided by NSS modules.
+    Passwd,
+    /// The `/etc/passwd` file on Unix, read directly.
+    PasswdFile,
+    /// The `getent(1)` program on Unix. This requires the `getent` feature. It never finds a home
+    /// directory if the process is running setuid or setgid, as `getent` is found through the
+    /// `PATH` of the user who ran it.
+    Getent,
+    /// `systemd-homed` on Linux, queried directly through its Varlink interface, which finds the
+    /// users it manages even when the `nss-systemd` module cannot be loaded.
+    Homed,
+    /// The profile known folder on Windows, obtained with `SHGetKnownFolderPath`. This is only
+    /// used for the current user.
+    KnownFolder,
+    /// The Windows Management Instrumentation. This requires the `windows-rs` feature.
+    Wmi,
+    /// The `ProfileList` key of the Windows registry.
+    Registry,
+    /// The home directory configured for the account on Windows, obtained with
+    /// `NetUserGetInfo`, which queries a domain controller for domain accounts. This is
+    /// generally a network share, and differs from the profile directory the other backends
+    /// return. This requires the `windows-rs` feature.
+    NetApi,
+    /// The sandbox container of the application on iOS, tvOS, watchOS, and visionOS. This is
+    /// only used for the current user.
+    Container,
+    /// The fake users given to `HomeResolver::mock_users`, for tests. If none were given, no
+    /// home directory is found. This requires the `test-util` feature.
+    Mock,
+}
+
+/// Whether a [`HomeResolver`] uses [`Backend::Env`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EnvTrust {
+    /// Always read the environment. This is the behaviour of [`my_home`](crate::my_home), and
+    /// the default.
+    #[default]
+    Always,
+    /// Do not read the environment if the process is running with elevated privileges through
+    /// the setuid or setgid bits, as [`my_home_secure`](crate::my_home_secure) does. This only
+    /// has an effect on Unix.
+    UnlessPrivileged,
+    /// Never read the environment, even if [`Backend::Env`] is in the list of backends.
+    Never,
+}
+
+/// A source of environment variables, from which [`Backend::Env`] reads the home directory, see
+/// [`HomeResolver::env_source`].
+///
+/// Tests which fake `HOME` by modifying the environment of the process race with each other when
+/// they run in parallel. Instead, each of them can give its own resolver a [`HashMap`] of the
+/// variables it needs.
+pub trait EnvSource: Send + Sync {
+    /// Get the value of a variable, or `None` if it is not set.
+    fn var(&self, key: &str) -> Option<OsString>;
+}
+
+/// The environment of the process, which is the default [`EnvSource`]. With the `no-env` feature,
+/// no variable is ever set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn var(&self, key: &str) -> Option<OsString> {
+        env_var(key)
+    }
+}
+
+impl<S: BuildHasher + Send + Sync> EnvSource for HashMap<String, String, S> {
+    fn var(&self, key: &str) -> Option<OsString> {
+        self.get(key).map(OsString::from)
+    }
+}
+
+impl fmt::Debug for dyn EnvSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EnvSource")
+    }
+}
+
+/// A step of the lookup of a [`HomeResolver`].
+#[derive(Debug)]
+enum Step {
+    /// One of the backends of this crate.
+    Backend(Backend),
+    /// A provider added by the application.
+    Provider(Box<dyn HomeProvider>),
+}
+
+/// A lookup of home directories configured once and used for many queries, for programs which
+/// need a different policy than the free functions of this crate, such as daemons, setuid tools,
+/// and tests.
+///
+/// The backends, followed by the providers added with
+/// [`push_backend`](HomeResolver::push_backend), are tried in order until one of them finds a
+/// home directory. Backends which do not apply to a query, such as [`Backend::Env`] for a user
+/// other than the current one, are skipped. If a backend fails, its error is returned without
+/// trying the following ones.
+///
+/// By default, the backends are those used by [`my_home`](crate::my_home) and
+/// [`home`](crate::home) on the current target, the environment is trusted, the home
+/// directories are not checked, nothing is cached, and the environment is that of the process.
+///
+/// # Example
+/// ```no_run
+/// use homedir::{Backend, EnvTrust, HomeResolver};
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// let resolver = HomeResolver::new()
+///     .backends([Backend::Env, Backend::Passwd, Backend::PasswdFile])
+///     .env_trust(EnvTrust::UnlessPrivileged)
+///     .require_existing(true)
+///     .cache(true);
+/// println!("{:?}", resolver.my_home()?);
+/// println!("{:?}", resolver.home("alice")?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HomeResolver {
+    backends: Vec<Step>,
+    env_trust: EnvTrust,
+    env: Box<dyn EnvSource>,
+    require_absolute_env: bool,
+    require_existing: bool,
+    timeout: Option<Duration>,
+    observer: Option<Box<dyn LookupObserver>>,
+    #[cfg(feature = "test-util")]
+    mock: Option<MockUsers>,
+    // `None` is the key of the current user.
+    cache: Option<Mutex<HashMap<Option<String>, Option<PathBuf>>>>,
+}
+
+impl HomeResolver {
+    /// Construct a resolver with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            backends: default_backends_imp()
+                .into_iter()
+                .map(Step::Backend)
+                .collect(),
+            env_trust: EnvTrust::Always,
+            env: Box::new(ProcessEnv),
+            require_absolute_env: true,
+            require_existing: false,
+            timeout: None,
+            observer: None,
+            #[cfg(feature = "test-util")]
+            mock: None,
+            cache: None,
+        }
+    }
+
+    /// Set the backends to try, in order. This removes the providers added with
+    /// [`push_backend`](Self::push_backend).
+    pub fn backends<I: IntoIterator<Item = Backend>>(mut self, backends: I) -> Self {
+        self.backends = backends.into_iter().map(Step::Backend).collect();
+        self
+    }
+
+    /// Add a provider of home directories after the backends, so that it is tried if they do
+    /// not find a home directory. This lets applications add their own sources, such as the
+    /// web API of a directory, to the lookup. Errors returned by the provider are returned by
+    /// the resolver, as those of the backends are. The cache is cleared, as the users which
+    /// were not found may now be.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::{GetHomeError, HomeProvider, HomeResolver, UserIdentifier};
+    /// use std::path::PathBuf;
+    ///
+    /// /// The users of a build farm, whose home directories are on a shared volume.
+    /// struct BuildFarm;
+    ///
+    /// impl HomeProvider for BuildFarm {
+    ///     fn home(&self, username: &str) -> Result<Option<PathBuf>, GetHomeError> {
+    ///         let home = PathBuf::from("/srv/build").join(username);
+    ///         Ok(home.is_dir().then_some(home))
+    ///     }
+    ///
+    ///     fn home_of(&self, _id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+    ///         Ok(None)
+    ///     }
+    ///
+    ///     fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+    ///         Ok(None)
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), homedir::GetHomeError> {
+    /// let mut resolver = HomeResolver::new();
+    /// resolver.push_backend(Box::new(BuildFarm));
+    /// println!("{:?}", resolver.home("ci-runner")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn push_backend(&mut self, provider: Box<dyn HomeProvider>) {
+        self.backends.push(Step::Provider(provider));
+        self.clear_cache();
+    }
+
+    /// Set a hook called after each backend or provider is queried, and whenever the cache is
+    /// checked, see [`LookupObserver`].
+    pub fn observer<O: LookupObserver + 'static>(mut self, observer: O) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Set whether the environment is read, see [`EnvTrust`].
+    pub fn env_trust(mut self, env_trust: EnvTrust) -> Self {
+        self.env_trust = env_trust;
+        self
+    }
+
+    /// Set the source of the environment variables read by [`Backend::Env`], instead of the
+    /// environment of the process.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::{Backend, HomeResolver};
+    /// use std::collections::HashMap;
+    /// use std::path::PathBuf;
+    ///
+    /// # fn main() -> Result<(), homedir::GetHomeError> {
+    /// let env = HashMap::from([("HOME".to_owned(), "/home/alice".to_owned())]);
+    /// let resolver = HomeResolver::new()
+    ///     .backends([Backend::Env])
+    ///     .env_source(env);
+    /// assert_eq!(Some(PathBuf::from("/home/alice")), resolver.my_home()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn env_source<E: EnvSource + 'static>(mut self, env: E) -> Self {
+        self.env = Box::new(env);
+        self
+    }
+
+    /// Set whether a home directory read by [`Backend::Env`] must be an absolute path. If it is
+    /// not, such as an empty `HOME`, it is ignored as if it was not set. This is enabled by
+    /// default; [`my_home`](crate::my_home) does not check it.
+    pub fn require_absolute_env(mut self, require_absolute_env: bool) -> Self {
+        self.require_absolute_env = require_absolute_env;
+        self
+    }
+
+    /// Set whether the home directories found are checked to exist and be directories. If
+    /// enabled, a home directory which does not exist is skipped, and the next backend is tried.
+    pub fn require_existing(mut self, require_existing: bool) -> Self {
+        self.require_existing = require_existing;
+        self
+    }
+
+    /// Set the timeout of the lookups which can block, which are the queries of
+    /// [`Backend::Wmi`]. Lookups through the C library on Unix cannot be interrupted.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the fake users looked up by [`Backend::Mock`]. There is an example in the
+    /// documentation of [`MockUsers`].
+    #[cfg(feature = "test-util")]
+    pub fn mock_users(mut self, users: MockUsers) -> Self {
+        self.mock = Some(users);
+        self
+    }
+
+    /// Set whether the results of the lookups are cached, including the users who were not
+    /// found. Errors are not cached.
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache.then(Mutex::default);
+        self
+    }
+
+    /// Remove all the results from the cache.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap_or_else(PoisonError::into_inner).clear();
+        }
+    }
+
+    /// Remove the result of a user from the cache, so that the next lookup of this username
+    /// tries the backends again.
+    pub fn invalidate<S: AsRef<str>>(&self, username: S) {
+        if let Some(cache) = &self.cache {
+            cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&Some(username.as_ref().to_owned()));
+        }
+    }
+
+    /// Remove the result of the process' current user from the cache.
+    pub fn invalidate_my_home(&self) {
+        if let Some(cache) = &self.cache {
+            cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&None);
+        }
+    }
+
+    /// Look up all the users of the cache again. If a lookup fails, the result of the user is
+    /// removed, and the first error is returned after the other users are looked up.
+    pub fn refresh(&self) -> Result<(), GetHomeError> {
+        let Some(cache) = &self.cache else {
+            return Ok(());
+        };
+        let keys = cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut ret = Ok(());
+        for key in keys {
+            // the lock is not held during the lookups, which may be slow.
+            let home = self.resolve(key.as_deref());
+            let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+            match home {
+                Ok(home) => {
+                    cache.insert(key, home);
+                }
+                Err(e) => {
+                    cache.remove(&key);
+                    if ret.is_ok() {
+                        ret = Err(e);
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Get the home directory of the process' current user. With the `test-util` feature, this
+    /// returns the home directory set with
+    /// [`override_my_home`](crate::testing::override_my_home) instead, if there is one.
+    pub fn my_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        #[cfg(feature = "test-util")]
+        if let Some(home) = crate::testing::overridden_home() {
+            return Ok(Some(home));
+        }
+        self.cached(None)
+    }
+
+    /// Get the home directory of an arbitrary user. If no user with the given username can be
+    /// found by any of the backends, `Ok(None)` is returned.
+    pub fn home<S: AsRef<str>>(&self, username: S) -> Result<Option<PathBuf>, GetHomeError> {
+        self.cached(Some(username.as_ref()))
+    }
+
+    /// Resolve a home directory through the cache, if it is enabled.
+    fn cached(&self, username: Option<&str>) -> Result<Option<PathBuf>, GetHomeError> {
+        let Some(cache) = &self.cache else {
+            return self.resolve(username);
+        };
+        let key = username.map(str::to_owned);
+        let cached = cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .cloned();
+        if let Some(observer) = &self.observer {
+            observer.cache(cached.is_some());
+        }
+        if let Some(v) = cached {
+            return Ok(v);
+        }
+        // the lock is not held during the lookup, which may be slow.
+        let ret = self.resolve(username)?;
+        cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, ret.clone());
+        Ok(ret)
+    }
+
+    /// Report how the home directory of an arbitrary user is looked up: every backend and
+    /// provider is queried, in order, even after one of them found a home directory or failed,
+    /// and the results and timings are recorded, along with the result [`home`](Self::home)
+    /// returns. The cache is not used. With the `serde` feature, the report can be serialized,
+    /// for instance to be attached to a bug report about the wrong home directory being used.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::HomeResolver;
+    ///
+    /// let explanation = HomeResolver::new().explain("alice");
+    /// for step in explanation.steps() {
+    ///     println!("{:?}: {:?} in {:?}", step.backend(), step.outcome(), step.elapsed());
+    /// }
+    /// println!("{:?}", explanation.home());
+    /// ```
+    pub fn explain<S: AsRef<str>>(&self, username: S) -> Explanation {
+        self.explain_imp(Some(username.as_ref()))
+    }
+
+    /// Report how the home directory of the process' current user is looked up. This is
+    /// otherwise identical to [`explain`](Self::explain).
+    pub fn explain_my_home(&self) -> Explanation {
+        self.explain_imp(None)
+    }
+
+    /// Query every backend, and record the results.
+    fn explain_imp(&self, username: Option<&str>) -> Explanation {
+        let mut explanation = Explanation::new(username, self.trusts_env(), self.require_existing);
+        for step in &self.backends {
+            let (backend, home, elapsed) = self.query(step, username);
+            let ignored = matches!(&home, Ok(Some(v)) if self.require_existing && !v.is_dir());
+            explanation.push(backend, home, ignored, elapsed);
+        }
+        explanation
+    }
+
+    /// Try each backend in order.
+    fn resolve(&self, username: Option<&str>) -> Result<Option<PathBuf>, GetHomeError> {
+        for step in &self.backends {
+            let (_, home, _) = self.query(step, username);
+            match home? {
+                Some(home) if !self.require_existing || home.is_dir() => return Ok(Some(home)),
+                Some(home) => {
+                    log_debug!("ignoring {}, which is not a directory", home.display());
+                }
+                None => log_debug!("{step:?} did not find a home directory"),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Query a single backend or provider, and notify the observer and the audit hook.
+    fn query(
+        &self,
+        step: &Step,
+        username: Option<&str>,
+    ) -> (
+        Option<Backend>,
+        Result<Option<PathBuf>, GetHomeError>,
+        Duration,
+    ) {
+        let start = Instant::now();
+        let (backend, home) = match (step, username) {
+            (Step::Backend(backend), _) => (Some(*backend), self.backend_home(*backend, username)),
+            (Step::Provider(provider), Some(username)) => (None, provider.home(username)),
+            (Step::Provider(provider), None) => (None, provider.my_home()),
+        };
+        let elapsed = start.elapsed();
+        observe(
+            self.observer.as_deref(),
+            backend,
+            username.is_none(),
+            elapsed,
+            &home,
+        );
+        // providers which use this crate report their own lookups, and the environment holds no
+        // data about other users.
+        if let (Some(backend), Some(username)) = (backend, username) {
+            if backend != Backend::Env {
+                audit(
+                    AuditedUser::Username(username.as_ref()),
+                    Some(backend),
+                    LookupOutcome::of(&home),
+                );
+            }
+        }
+        (backend, home, elapsed)
+    }
+
+    /// Look up a home directory through one of the backends of this crate.
+    fn backend_home(
+        &self,
+        backend: Backend,
+        username: Option<&str>,
+    ) -> Result<Option<PathBuf>, GetHomeError> {
+        match (backend, username) {
+            (Backend::Env, Some(_)) => Ok(None),
+            (Backend::Env, None) if !self.trusts_env() => Ok(None),
+            (Backend::Env, None) => Ok(self
+                .env
+                .var(HOME_VAR)
+                .map(PathBuf::from)
+                .filter(|v| !self.require_absolute_env || v.is_absolute())),
+            (Backend::Mock, _) => self.mock_home(username),
+            _ => backend_home_imp(backend, username, self.timeout).map_err(GetHomeError),
+        }
+    }
+
+    /// Look up a home directory among the fake users.
+    fn mock_home(&self, username: Option<&str>) -> Result<Option<PathBuf>, GetHomeError> {
+        cfg_if! {
+            if #[cfg(feature = "test-util")] {
+                let Some(mock) = &self.mock else {
+                    return Ok(None);
+                };
+                let home = match username {
+                    Some(username) => mock.home(username),
+                    None => mock.my_home(),
+                };
+                Ok(home.map(PathBuf::from))
+            } else {
+                let _ = username;
+                Err(GetHomeError(crate::GetHomeErrorImp::Unsupported))
+            }
+        }
+    }
+
+    /// Check whether the environment is read under the current policy.
+    fn trusts_env(&self) -> bool {
+        match self.env_trust {
+            EnvTrust::Always => true,
+            EnvTrust::Never => false,
+            EnvTrust::UnlessPrivileged => {
+                cfg_if! {
+                    if #[cfg(all(unix, not(feature = "env-only"), not(any(
+                        target_os = "ios",
+                        target_os = "tvos",
+                        target_os = "watchos",
+                        target_os = "visionos",
+                    ))))] {
+                        !crate::unix::is_privileged()
+                    } else {
+                        true
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for HomeResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,171 @@
+// src/sandbox.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Helpers for applications packaged with Flatpak or Snap, whose home directory depends on the
+//! sandbox they run in.
+//!
+//! Inside Snap, `HOME` is set to a directory private to the application and its revision, such
+//! as `~/snap/firefox/4173`, so that [`my_home`](crate::my_home) returns that directory rather
+//! than the user's home directory. Inside Flatpak, `HOME` is the user's home directory, but the
+//! application's data is kept in `~/.var/app/<application id>`, and the home directory itself is
+//! only visible if the application was granted access to it.
+//!
+//! [`my_home_in`](crate::sandbox::my_home_in) lets the application choose between the two with
+//! a [`HomeView`](crate::sandbox::HomeView). Outside of a sandbox, and on targets other than
+//! Linux, both are the home directory returned by [`my_home`](crate::my_home).
+//!
+//! # Example
+//! ```no_run
+//! use homedir::sandbox::{detect, my_home_in, HomeView};
+//!
+//! # fn main() -> Result<(), homedir::GetHomeError> {
+//! if let Some(sandbox) = detect() {
+//!     println!("running inside {sandbox:?}");
+//! }
+//! println!("application data: {:?}", my_home_in(HomeView::Sandbox)?);
+//! println!("user's files: {:?}", my_home_in(HomeView::Host)?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::PathBuf;
+
+use cfg_if::cfg_if;
+
+use crate::GetHomeError;
+
+/// The file which Flatpak creates at the root of the sandbox.
+#[cfg(target_os = "linux")]
+const FLATPAK_INFO: &str = "/.flatpak-info";
+
+/// The sandbox the process runs in, as returned by [`detect`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Sandbox {
+    /// [Flatpak](https://flatpak.org), detected by the `/.flatpak-info` file or the
+    /// `FLATPAK_ID` environment variable.
+    Flatpak,
+    /// [Snap](https://snapcraft.io), detected by the `SNAP` and `SNAP_NAME` environment
+    /// variables.
+    Snap,
+}
+
+/// The home directory requested from [`my_home_in`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HomeView {
+    /// The directory in which the sandbox keeps the application's data: `$SNAP_USER_DATA`,
+    /// which is also `HOME`, inside Snap, and `~/.var/app/<application id>` inside Flatpak.
+    Sandbox,
+    /// The user's home directory on the host system: `$SNAP_REAL_HOME` inside Snap, or the home
+    /// directory in the user database if it is not set, and `HOME` inside Flatpak. The
+    /// application may not have access to it.
+    Host,
+}
+
+/// Detect whether the process runs inside a Flatpak or Snap sandbox. `None` is returned outside
+/// of them, and on targets other than Linux. With the `no-env` feature, Snap is not detected, as
+/// it is only recognizable by its environment variables.
+pub fn detect() -> Option<Sandbox> {
+    cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            let flatpak_info = std::path::Path::new(FLATPAK_INFO);
+            if flatpak_info.exists() || crate::env_var("FLATPAK_ID").is_some() {
+                Some(Sandbox::Flatpak)
+            } else if crate::env_var("SNAP").is_some() && crate::env_var("SNAP_NAME").is_some() {
+                Some(Sandbox::Snap)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Get the identifier of the sandboxed application: the application id inside Flatpak, such as
+/// `org.mozilla.firefox`, read from `FLATPAK_ID` or `/.flatpak-info`, and the name of the snap
+/// inside Snap, such as `firefox`, including its instance key if it has one. `None` is returned
+/// outside of a sandbox.
+pub fn app_id() -> Option<String> {
+    cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            match detect()? {
+                Sandbox::Flatpak => crate::env_var("FLATPAK_ID")
+                    .and_then(|v| v.into_string().ok())
+                    .filter(|v| !v.is_empty())
+                    .or_else(flatpak_info_name),
+                Sandbox::Snap => crate::env_var("SNAP_INSTANCE_NAME")
+                    .or_else(|| crate::env_var("SNAP_NAME"))
+                    .and_then(|v| v.into_string().ok())
+                    .filter(|v| !v.is_empty()),
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Get the home directory of the process' current user as seen from the given view of the
+/// sandbox it runs in, see [`HomeView`]. Outside of a sandbox, and on targets other than Linux,
+/// this is identical to [`my_home`](crate::my_home).
+///
+/// Inside Flatpak, if the application id cannot be determined, the [`HomeView::Sandbox`] view is
+/// the home directory itself.
+pub fn my_home_in(view: HomeView) -> Result<Option<PathBuf>, GetHomeError> {
+    // an overridden home directory stands in for both views.
+    #[cfg(feature = "test-util")]
+    if let Some(home) = crate::testing::overridden_home() {
+        return Ok(Some(home));
+    }
+    match (detect(), view) {
+        (Some(Sandbox::Snap), HomeView::Sandbox) => {
+            match crate::env_var("SNAP_USER_DATA")
+                .map(PathBuf::from)
+                .filter(|v| v.is_absolute())
+            {
+                Some(v) => Ok(Some(v)),
+                None => crate::my_home(),
+            }
+        }
+        (Some(Sandbox::Snap), HomeView::Host) => {
+            match crate::env_var("SNAP_REAL_HOME")
+                .map(PathBuf::from)
+                .filter(|v| v.is_absolute())
+            {
+                Some(v) => Ok(Some(v)),
+                // HOME is overridden by Snap, but the user database is not.
+                None => crate::UserIdentifier::my_id()?.to_home(),
+            }
+        }
+        (Some(Sandbox::Flatpak), HomeView::Sandbox) => {
+            let Some(home) = crate::my_home()? else {
+                return Ok(None);
+            };
+            Ok(Some(match app_id() {
+                Some(id) => home.join(".var").join("app").join(id),
+                None => home,
+            }))
+        }
+        _ => crate::my_home(),
+    }
+}
+
+/// Read the application id from the `name` key of the `[Application]` section of
+/// `/.flatpak-info`.
+#[cfg(target_os = "linux")]
+fn flatpak_info_name() -> Option<String> {
+    let info = std::fs::read_to_string(FLATPAK_INFO).ok()?;
+    let mut in_application = false;
+    for line in info.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            in_application = section == "Application";
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| in_application) {
+            if key.trim() == "name" && !value.trim().is_empty() {
+                return Some(value.trim().to_owned());
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,218 @@
+// src/testing.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Helpers for the tests of programs which use this crate. This module requires the `test-util`
+//! feature, which should only be enabled in the `[dev-dependencies]` of a crate.
+//!
+//! [`override_my_home`] changes the home directory of the current user for the whole process,
+//! while [`MockUsers`] provides a set of fake users to a [`HomeResolver`], so that code which
+//! handles several users can be tested on a machine which only has one account.
+//!
+//! [`override_my_home`]: crate::testing::override_my_home
+//! [`MockUsers`]: crate::testing::MockUsers
+//! [`HomeResolver`]: crate::HomeResolver
+//!
+//! # Example
+//! ```no_run
+//! use homedir::{my_home, testing::override_my_home};
+//! use std::path::PathBuf;
+//!
+//! # fn main() -> Result<(), homedir::GetHomeError> {
+//! let guard = override_my_home("/tmp/fake-home");
+//! assert_eq!(Some(PathBuf::from("/tmp/fake-home")), my_home()?);
+//! drop(guard);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError, RwLock},
+};
+
+/// Held by the live [`Guard`], so that only one override exists at a time.
+static LOCK: Mutex<()> = Mutex::new(());
+/// The home directory returned by [`my_home`](crate::my_home), if it is overridden.
+static HOME: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Make [`my_home`](crate::my_home), and the functions of the root of this crate which rely on
+/// it such as [`expand_tilde`](crate::expand_tilde), [`my_home_secure`](crate::my_home_secure),
+/// and [`my_home_with_source`](crate::my_home_with_source), return the given path until the
+/// returned guard is dropped, on every platform. The latter reports it as coming from
+/// [`HomeSource::Env`](crate::HomeSource::Env). [`HomeResolver::my_home`], and so
+/// [`LookupOptions::my_home`](crate::LookupOptions::my_home), as well as
+/// [`sandbox::my_home_in`](crate::sandbox::my_home_in) in every view, return it as well. The
+/// functions of the platform-specific modules and the lookups of other users are not affected.
+///
+/// The override applies to every thread. If another thread already holds a guard, this blocks
+/// until that guard is dropped, so that tests which run in parallel do not see each other's
+/// home directory. Calling this again on a thread which holds a guard deadlocks.
+///
+/// [`HomeResolver::my_home`]: crate::HomeResolver::my_home
+pub fn override_my_home<P: Into<PathBuf>>(path: P) -> Guard {
+    // a test which panicked while holding the guard does not leave the override in place, as the
+    // guard resets it when it is dropped during unwinding.
+    let lock = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    *HOME.write().unwrap_or_else(PoisonError::into_inner) = Some(path.into());
+    Guard { _lock: lock }
+}
+
+/// Restores the home directory of the current user when it is dropped, see
+/// [`override_my_home`].
+#[derive(Debug)]
+#[must_use = "the override is removed when the guard is dropped"]
+pub struct Guard {
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        *HOME.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+/// Get the overridden home directory of the current user, if there is one.
+pub(crate) fn overridden_home() -> Option<PathBuf> {
+    HOME.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// A set of fake users and their home directories, looked up through [`Backend::Mock`] by a
+/// [`HomeResolver`] given to [`HomeResolver::mock_users`].
+///
+/// Users are registered by username, and optionally by id. The ids are opaque numbers chosen by
+/// the test, such as Unix user ids, which can be looked up with [`MockUsers::home_by_id`].
+///
+/// # Example
+/// ```no_run
+/// use homedir::{testing::MockUsers, Backend, HomeResolver};
+/// use std::path::PathBuf;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// let users = MockUsers::new()
+///     .user("alice", "/home/alice")
+///     .user("bob", "/srv/bob")
+///     .id(1001, "/srv/bob")
+///     .current("alice");
+/// let resolver = HomeResolver::new()
+///     .backends([Backend::Mock])
+///     .mock_users(users);
+/// assert_eq!(Some(PathBuf::from("/home/alice")), resolver.my_home()?);
+/// assert_eq!(Some(PathBuf::from("/srv/bob")), resolver.home("bob")?);
+/// assert_eq!(None, resolver.home("carol")?);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Backend::Mock`]: crate::Backend::Mock
+/// [`HomeResolver`]: crate::HomeResolver
+/// [`HomeResolver::mock_users`]: crate::HomeResolver::mock_users
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockUsers {
+    by_name: HashMap<String, PathBuf>,
+    by_id: HashMap<u32, PathBuf>,
+    current: Option<String>,
+}
+
+impl MockUsers {
+    /// Construct an empty set of users.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the home directory of a user given their username.
+    pub fn user<S: Into<String>, P: Into<PathBuf>>(mut self, username: S, home: P) -> Self {
+        self.by_name.insert(username.into(), home.into());
+        self
+    }
+
+    /// Register the home directory of a user given their id.
+    pub fn id<P: Into<PathBuf>>(mut self, id: u32, home: P) -> Self {
+        self.by_id.insert(id, home.into());
+        self
+    }
+
+    /// Set the username of the current user, whose home directory is returned by
+    /// [`HomeResolver::my_home`](crate::HomeResolver::my_home). If it is not set, or if the
+    /// user is not registered, the current user has no home directory.
+    pub fn current<S: Into<String>>(mut self, username: S) -> Self {
+        self.current = Some(username.into());
+        self
+    }
+
+    /// Get the home directory of a user given their username.
+    pub fn home(&self, username: &str) -> Option<&Path> {
+        self.by_name.get(username).map(PathBuf::as_path)
+    }
+
+    /// Get the home directory of a user given their id.
+    pub fn home_by_id(&self, id: u32) -> Option<&Path> {
+        self.by_id.get(&id).map(PathBuf::as_path)
+    }
+
+    /// Get the home directory of the current user.
+    pub fn my_home(&self) -> Option<&Path> {
+        self.home(self.current.as_deref()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sandbox::HomeView, HomeResolver, HomeSource, LookupOptions, LookupOrder};
+
+    const FAKE_HOME: &str = "/tmp/homedir-fake-home";
+
+    #[test]
+    fn my_home_is_overridden() {
+        let _guard = override_my_home(FAKE_HOME);
+        assert_eq!(crate::my_home().unwrap(), Some(PathBuf::from(FAKE_HOME)));
+        let mut buf = PathBuf::new();
+        assert!(crate::my_home_into(&mut buf).unwrap());
+        assert_eq!(buf, Path::new(FAKE_HOME));
+    }
+
+    #[test]
+    fn my_home_with_source_is_overridden() {
+        let _guard = override_my_home(FAKE_HOME);
+        let expected = (PathBuf::from(FAKE_HOME), HomeSource::Env);
+        assert_eq!(crate::my_home_with_source().unwrap(), Some(expected));
+    }
+
+    #[test]
+    fn my_home_secure_is_overridden() {
+        let _guard = override_my_home(FAKE_HOME);
+        assert_eq!(
+            crate::my_home_secure().unwrap(),
+            Some(PathBuf::from(FAKE_HOME))
+        );
+    }
+
+    #[test]
+    fn resolver_is_overridden() {
+        let _guard = override_my_home(FAKE_HOME);
+        let home = HomeResolver::new().backends([]).my_home().unwrap();
+        assert_eq!(home, Some(PathBuf::from(FAKE_HOME)));
+    }
+
+    #[test]
+    fn lookup_options_are_overridden() {
+        let _guard = override_my_home(FAKE_HOME);
+        let home = LookupOptions::new()
+            .order(LookupOrder::DatabaseOnly)
+            .my_home()
+            .unwrap();
+        assert_eq!(home, Some(PathBuf::from(FAKE_HOME)));
+    }
+
+    #[test]
+    fn sandbox_is_overridden() {
+        let _guard = override_my_home(FAKE_HOME);
+        for view in [HomeView::Sandbox, HomeView::Host] {
+            let home = crate::sandbox::my_home_in(view).unwrap();
+            assert_eq!(home, Some(PathBuf::from(FAKE_HOME)));
+        }
+    }
+}
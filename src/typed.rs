@@ -0,0 +1,59 @@
+// src/typed.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Functions returning home directories as [`TypedPathBuf`], enabled by the `typed-path`
+//! feature.
+
+use std::path::PathBuf;
+
+use typed_path::TypedPathBuf;
+
+use crate::{home, my_home, GetHomeError, UserIdentifier};
+
+/// Get the home directory of an arbitrary user as a [`TypedPathBuf`], tagged with the path
+/// syntax of the system it was looked up on: [`TypedPathBuf::Windows`] on Windows, and
+/// [`TypedPathBuf::Unix`] elsewhere. The path is converted losslessly, so it can be stored or
+/// sent to a system whose path syntax differs without being misinterpreted. If no user with the
+/// given username can be found, `Ok(None)` is returned.
+///
+/// # Example
+/// ```no_run
+/// use homedir::home_typed;
+///
+/// # fn main() -> Result<(), homedir::GetHomeError> {
+/// if let Some(home) = home_typed("alice")? {
+///     println!("{} ({})", home.to_string_lossy(), if home.is_windows() { "windows" } else { "unix" });
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_typed<S: AsRef<str>>(username: S) -> Result<Option<TypedPathBuf>, GetHomeError> {
+    Ok(home(username)?.map(to_typed))
+}
+
+/// Get the home directory of the process' current user as a [`TypedPathBuf`]. This is otherwise
+/// identical to [`home_typed`].
+pub fn my_home_typed() -> Result<Option<TypedPathBuf>, GetHomeError> {
+    Ok(my_home()?.map(to_typed))
+}
+
+impl UserIdentifier {
+    /// Get the home directory of the user with this identifier as a [`TypedPathBuf`]. This is
+    /// otherwise identical to [`home_typed`].
+    pub fn to_home_typed(&self) -> Result<Option<TypedPathBuf>, GetHomeError> {
+        Ok(self.to_home()?.map(to_typed))
+    }
+}
+
+/// Convert a native path to a path tagged with the native path syntax.
+fn to_typed(path: PathBuf) -> TypedPathBuf {
+    // on Windows, the encoded bytes are WTF-8, a superset of the UTF-8 `WindowsPathBuf` expects.
+    let bytes = path.into_os_string().into_encoded_bytes();
+    if cfg!(windows) {
+        TypedPathBuf::from_windows(bytes)
+    } else {
+        TypedPathBuf::from_unix(bytes)
+    }
+}
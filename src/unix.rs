@@ -3,17 +3,91 @@
 // Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
 // Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
 
-use std::env::var_os;
-use std::path::PathBuf;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use nix::unistd::Uid;
-use nix::unistd::User;
+use cfg_if::cfg_if;
+
+use crate::{env_var, replace_path, replace_path_opt, Backend};
+
+pub use base_dirs::{base_dirs, my_base_dirs, BaseDirs};
+pub use cache::PasswdCache;
+#[cfg(target_os = "cygwin")]
+pub use cygwin::{convert_path, home_with_style, PathStyle};
+pub use find::find_users;
+pub use id::{Gid, Uid};
+pub use info::{login_shell, primary_group, user_info, PrimaryGroup, UserInfo};
+#[cfg(feature = "ldap")]
+pub use ldap::{ldap_home, LdapConfig};
+pub use login_defs::UidRange;
+pub use passwd::{PasswdDatabase, PasswdEntry};
+pub use pwd::{passwd_entries, PasswdEntries, PasswdLookup};
+pub use source::{HomeResult, Source};
+pub use user_dirs::{user_dirs, UserDir, UserDirs};
+pub use useradd::{projected_home, ProjectedHome};
+
+pub(crate) use fs_type::filesystem_type;
+
+mod base_dirs;
+mod cache;
+#[cfg(target_os = "cygwin")]
+mod cygwin;
+mod find;
+mod fs_type;
+// only needed by `getpwent(3)`, where `getpwent_r(3)` is not used.
+#[cfg(not(any(
+    all(target_os = "linux", target_env = "gnu"),
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "nuttx",
+    target_os = "rtems",
+    target_os = "vita",
+)))]
+mod errno;
+#[cfg(feature = "getent")]
+mod getent;
+#[cfg(target_os = "linux")]
+mod homed;
+mod id;
+mod info;
+#[cfg(feature = "ldap")]
+mod ldap;
+mod login_defs;
+#[cfg(feature = "unicode-normalization")]
+mod normalize;
+mod passwd;
+mod pwd;
+mod source;
+mod user_dirs;
+mod useradd;
 
 /// The error type returned by this library when errors occur.
-pub type GetHomeError = nix::errno::Errno;
+///
+/// On illumos and Solaris, the C library accepts buffers of at most `INT_MAX` bytes, so a
+/// lookup fails with `ERANGE` if an entry does not fit in one, whatever the maximum set with
+/// [`PasswdLookup::max_buffer_size`]. As well, older versions report a missing user by failing
+/// with `ENOENT` or `ESRCH`, so these are never returned by lookups, which return `Ok(None)`
+/// instead, and an error of the name service which happens to have one of these codes is
+/// reported as a missing user.
+#[derive(Debug)]
+pub enum GetHomeError {
+    /// An error returned by a library function, such as `getpwnam_r(3)`, or an I/O error, for
+    /// instance when reading `/etc/passwd` or running the `getent` program.
+    Io(io::Error),
+    /// The user database cannot be read on this target, which does not provide `getpwnam_r(3)`
+    /// and the related functions. The functions which only read files, such as
+    /// [`passwd_entry`], still work.
+    Unsupported,
+    /// The relative path passed to [`home_join`](crate::home_join) was absolute or escaped the home directory.
+    InvalidPath,
+}
 
 /// An identifier for a user.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct UserIdentifier(Uid);
 
@@ -23,10 +97,17 @@ pub struct UserIdentifier(Uid);
 /// associated with `username` could be found, `Ok(None)` is returned. Otherwise,
 /// the path to the user's home directory is returned.
 ///
-/// This function uses the [`User::from_name`](nix::unistd::User::from_name)
-/// method provided by the nix crate. That method uses the
+/// This function uses the
 /// [`getpwnam_r(3)`](https://man7.org/linux/man-pages/man3/getpwnam.3.html)
-/// library function to get the home directory from the `/etc/passwd` file.
+/// library function to get the home directory from the `/etc/passwd` file. To configure the
+/// size of the buffer passed to it, see [`PasswdLookup`].
+///
+/// If the `passwd-file` feature is enabled and `getpwnam_r(3)` does not find the user,
+/// `/etc/passwd` is read directly, see [`passwd_entry`]. Then, if the `getent` feature is
+/// enabled, the
+/// [`getent(1)`](https://man7.org/linux/man-pages/man1/getent.1.html) program is run.
+/// This is useful for statically linked binaries, which cannot load NSS modules and therefore
+/// only find the users listed in `/etc/passwd`.
 ///
 /// # Example
 /// ```no_run
@@ -45,20 +126,235 @@ pub struct UserIdentifier(Uid);
 /// # }
 /// ```
 pub fn home<S: AsRef<str>>(username: S) -> Result<Option<PathBuf>, GetHomeError> {
-    Ok(User::from_name(username.as_ref())?.map(|user| user.dir))
+    home_os(username.as_ref())
+}
+
+/// Get a user's home directory path given a username which may not be valid UTF-8. Usernames
+/// read from the filesystem or from the command line are not guaranteed to be UTF-8 on Unix.
+/// This is otherwise identical to [`home`].
+pub fn home_os<S: AsRef<OsStr>>(username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    Ok(lookup_os(username.as_ref())?.map(|entry| entry.dir().to_owned()))
+}
+
+/// Get a user's home directory path along with the source it was obtained from, such as the C
+/// library or one of the fallbacks enabled by this crate's features. This is otherwise
+/// identical to [`home_os`].
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::home_with_source;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// if let Some(result) = home_with_source("alice")? {
+///     println!("{:?} (from {:?})", result.path(), result.source());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_with_source<S: AsRef<OsStr>>(username: S) -> Result<Option<HomeResult>, GetHomeError> {
+    Ok(lookup_os_with_source(username.as_ref())?
+        .map(|(entry, source)| HomeResult::new(entry.dir().to_owned(), source)))
+}
+
+/// Get a user's home directory path given their username as raw bytes, for instance as read
+/// from a tar header. The bytes are passed as is to `getpwnam_r(3)`. This is identical to
+/// [`home_os`].
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::home_bytes;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// println!("{:?}", home_bytes(b"root")?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_bytes(username: &[u8]) -> Result<Option<PathBuf>, GetHomeError> {
+    home_os(OsStr::from_bytes(username))
+}
+
+/// Look up a user given a username which may not be valid UTF-8, with the fallbacks enabled by
+/// this crate's features.
+fn lookup_os(username: &OsStr) -> Result<Option<PasswdEntry>, GetHomeError> {
+    Ok(lookup_os_with_source(username)?.map(|(entry, _)| entry))
+}
+
+/// Look up a user like [`lookup_os`], also returning the source which found them. With the
+/// `unicode-normalization` feature, the NFC and NFD forms of the username are tried if it is not
+/// found as is.
+fn lookup_os_with_source(username: &OsStr) -> Result<Option<(PasswdEntry, Source)>, GetHomeError> {
+    if let Some(found) = lookup_exact(username)? {
+        return Ok(Some(found));
+    }
+    #[cfg(feature = "unicode-normalization")]
+    for username in normalize::alternatives(username) {
+        if let Some(found) = lookup_exact(&username)? {
+            return Ok(Some(found));
+        }
+    }
+    Ok(None)
+}
+
+/// Look up a user like [`lookup_os_with_source`], without normalizing the username.
+fn lookup_exact(username: &OsStr) -> Result<Option<(PasswdEntry, Source)>, GetHomeError> {
+    if let Some(entry) = PasswdLookup::new().entry(username)? {
+        return Ok(Some((entry, Source::Nss)));
+    }
+    // the fallbacks only support UTF-8 usernames.
+    match username.to_str() {
+        Some(username) => fallback_by_name(username),
+        None => Ok(None),
+    }
+}
+
+/// Get a user's entry in `/etc/passwd`, reading the file directly instead of using the C
+/// library. Unlike [`home`], this does not find users provided by NSS modules, such as those in
+/// LDAP. It is mostly useful for statically linked binaries and for systems with unusual C
+/// libraries.
+///
+/// Comments and malformed lines are skipped. If multiple entries have the same name, the first
+/// one is returned, as is done by the C library. If `/etc/passwd` does not exist, `Ok(None)` is
+/// returned.
+///
+/// If the `passwd-file` feature is enabled, this is used as a fallback by [`home`] and
+/// [`UserIdentifier::with_username`].
+pub fn passwd_entry<S: AsRef<str>>(username: S) -> Result<Option<PasswdEntry>, GetHomeError> {
+    let username = username.as_ref();
+    passwd::find_system_entry(|e| e.name() == username)
+}
+
+/// Get a user's entry in `/etc/passwd` given their identifier. This behaves like
+/// [`passwd_entry`].
+pub fn passwd_entry_by_id(id: &UserIdentifier) -> Result<Option<PasswdEntry>, GetHomeError> {
+    passwd::find_system_entry(|e| e.uid() == id.0)
+}
+
+/// Get a user's home directory path from an arbitrary file in the format of `/etc/passwd`, for
+/// instance a test fixture. The file is read until the user is found, behaving like
+/// [`passwd_entry`]. Unlike [`passwd_entry`], an error is returned if the file does not exist.
+///
+/// To look up multiple users in the same file, [`PasswdDatabase`] is more efficient.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::home_from_file;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// println!("{:?}", home_from_file("tests/passwd", "alice")?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_from_file<P: AsRef<Path>, S: AsRef<OsStr>>(
+    path: P,
+    username: S,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    let username = username.as_ref();
+    let entry = passwd::find_entry(path.as_ref(), |e| e.name() == username)?;
+    Ok(entry.map(|e| e.dir().to_owned()))
+}
+
+/// Look up a user by name with the fallbacks enabled by this crate's features, after the C
+/// library did not find them.
+#[cfg_attr(
+    not(any(feature = "getent", feature = "passwd-file")),
+    allow(unused_variables)
+)]
+fn fallback_by_name(username: &str) -> Result<Option<(PasswdEntry, Source)>, GetHomeError> {
+    #[cfg(feature = "passwd-file")]
+    {
+        log_debug!("getpwnam_r did not find {username}, reading /etc/passwd");
+        if let Some(entry) = passwd_entry(username)? {
+            return Ok(Some((entry, Source::PasswdFile)));
+        }
+    }
+    #[cfg(feature = "getent")]
+    {
+        log_debug!("{username} was not found, running getent");
+        if let Some(entry) = getent::getent_passwd(username)? {
+            return Ok(Some((entry, Source::Getent)));
+        }
+    }
+    Ok(None)
+}
+
+/// Look up a user by id with the fallbacks enabled by this crate's features, after the C
+/// library did not find them.
+#[cfg_attr(
+    not(any(feature = "getent", feature = "passwd-file")),
+    allow(unused_variables)
+)]
+fn fallback_by_id(id: &UserIdentifier) -> Result<Option<(PasswdEntry, Source)>, GetHomeError> {
+    #[cfg(feature = "passwd-file")]
+    {
+        log_debug!("getpwuid_r did not find uid {}, reading /etc/passwd", id.0);
+        if let Some(entry) = passwd_entry_by_id(id)? {
+            return Ok(Some((entry, Source::PasswdFile)));
+        }
+    }
+    #[cfg(feature = "getent")]
+    {
+        log_debug!("uid {} was not found, running getent", id.0);
+        if let Some(entry) = getent::getent_passwd(&id.0.to_string())? {
+            return Ok(Some((entry, Source::Getent)));
+        }
+    }
+    Ok(None)
+}
+
+/// The backends used by [`my_home`] and [`home`], in order, see
+/// [`HomeResolver`](crate::HomeResolver).
+pub(crate) fn default_backends() -> Vec<Backend> {
+    let mut ret = vec![Backend::Env, Backend::Passwd];
+    if cfg!(feature = "passwd-file") {
+        ret.push(Backend::PasswdFile);
+    }
+    if cfg!(feature = "getent") {
+        ret.push(Backend::Getent);
+    }
+    ret
+}
+
+/// Look up a home directory through a single backend, see
+/// [`HomeResolver`](crate::HomeResolver). `None` stands for the current user.
+pub(crate) fn backend_home(
+    backend: Backend,
+    username: Option<&str>,
+    _timeout: Option<Duration>,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    let id = UserIdentifier(Uid::current());
+    let entry = match (backend, username) {
+        (Backend::Passwd, Some(name)) => PasswdLookup::new().entry(name)?,
+        (Backend::Passwd, None) => PasswdLookup::new().entry_by_id(&id)?,
+        (Backend::PasswdFile, Some(name)) => passwd_entry(name)?,
+        (Backend::PasswdFile, None) => passwd_entry_by_id(&id)?,
+        #[cfg(feature = "getent")]
+        (Backend::Getent, Some(name)) => getent::getent_passwd(name)?,
+        #[cfg(feature = "getent")]
+        (Backend::Getent, None) => getent::getent_passwd(&id.0.to_string())?,
+        #[cfg(target_os = "linux")]
+        (Backend::Homed, _) => return homed::homed_home(username, Some(id.0)),
+        _ => return Err(GetHomeError::Unsupported),
+    };
+    Ok(entry.map(|entry| entry.dir().to_owned()))
 }
 
 /// Get this process' user's home directory path.
 ///
 /// This function will first check the `$HOME` environment variable. If this variable
-/// does not exist, then the `/etc/passwd` file is checked.
+/// does not exist, then the `/etc/passwd` file is checked. Its value is returned unchanged, even
+/// if it is empty or is not an absolute path, as exported by some init systems and cron
+/// implementations; [`LookupOptions::require_absolute_env`](crate::LookupOptions) ignores such
+/// values instead. With the `no-env` feature, `$HOME` is never read.
 ///
 /// The behaviour of this function is different from that of version 0.1.0.
 /// Previously, this function would check the `/etc/passwd` file first, and,
 /// should that fail, it would only check the `$HOME` environemnt variable if
 /// the `check_env` feature was set. Now, it will check the `$HOME` environment
 /// variable first, falling back on the `/etc/passwd` file should that fail.
-/// To replicate the original behaviour of the function, do `UserIdentifier::my_id()?.to_home()?`.
+/// To replicate the original behaviour of the function, use
+/// [`LookupOptions`](crate::LookupOptions) with
+/// [`LookupOrder::DatabaseOnly`](crate::LookupOrder::DatabaseOnly), or
+/// [`LookupOrder::DatabaseFirst`](crate::LookupOrder::DatabaseFirst) for the `check_env` feature.
 /// Note that this can still return `None`, should the `/etc/passwd` file be missing an
 /// entry for the user id of the program.
 ///
@@ -76,24 +372,168 @@ pub fn home<S: AsRef<str>>(username: S) -> Result<Option<PathBuf>, GetHomeError>
 /// # }
 /// ```
 pub fn my_home() -> Result<Option<PathBuf>, GetHomeError> {
-    match var_os("HOME") {
-        Some(s) => Ok(Some(PathBuf::from(s))),
-        None => Ok(User::from_uid(Uid::current())?.map(|user| user.dir)),
+    match env_home() {
+        Some(s) => Ok(Some(s)),
+        None => UserIdentifier(Uid::current()).to_home(),
+    }
+}
+
+/// Get this process' user's home directory path, writing it into `buf`. This is otherwise
+/// identical to [`my_home`], see [`my_home_into`](crate::my_home_into).
+pub fn my_home_into(buf: &mut PathBuf) -> Result<bool, GetHomeError> {
+    match env_home() {
+        Some(s) => {
+            replace_path(buf, &s);
+            Ok(true)
+        }
+        None => UserIdentifier(Uid::current()).to_home_into(buf),
+    }
+}
+
+/// Get this process' user's home directory path along with the source it was obtained from,
+/// which is [`Source::Env`] if it was read from `$HOME`. This is otherwise identical to
+/// [`my_home`], and is useful to find out why a directory was picked, for instance in a
+/// container.
+pub fn my_home_with_source() -> Result<Option<HomeResult>, GetHomeError> {
+    match env_home() {
+        Some(s) => Ok(Some(HomeResult::new(s, Source::Env))),
+        None => UserIdentifier(Uid::current()).to_home_with_source(),
+    }
+}
+
+/// Read the `$HOME` environment variable.
+fn env_home() -> Option<PathBuf> {
+    let Some(home) = env_var("HOME") else {
+        log_debug!("HOME is not set, reading the user database");
+        return None;
+    };
+    Some(PathBuf::from(home))
+}
+
+/// Get this process' user's home directory path, ignoring the `$HOME` environment variable if
+/// this process is running with elevated privileges through the setuid or setgid bits.
+///
+/// A setuid or setgid program must not trust `$HOME`, as it is controlled by the user who runs
+/// it, who could use it to make the program read or write files they could not otherwise
+/// access. This function detects this case by comparing the real and effective user and group
+/// ids, as well as with `getauxval(AT_SECURE)` on Linux and `issetugid(2)` on the BSDs, macOS,
+/// illumos, and Solaris. If it is detected, the home directory of the real user is read from the
+/// passwd database. Otherwise, this is identical to [`my_home`].
+pub fn my_home_secure() -> Result<Option<PathBuf>, GetHomeError> {
+    if is_privileged() {
+        UserIdentifier::my_id()?.to_home()
+    } else {
+        my_home()
+    }
+}
+
+/// Get the home directory of this process' effective user, read from the passwd database. In a
+/// setuid program, this is the home directory of the owner of the program, rather than that of
+/// the user who ran it. The `$HOME` environment variable is not used, as it belongs to the real
+/// user.
+pub fn my_effective_home() -> Result<Option<PathBuf>, GetHomeError> {
+    UserIdentifier::my_effective_id().to_home()
+}
+
+/// Check whether this process is running with elevated privileges through the setuid or setgid
+/// bits, or through file capabilities on Linux.
+pub(crate) fn is_privileged() -> bool {
+    if Uid::current() != Uid::effective() || Gid::current() != Gid::effective() {
+        return true;
+    }
+    cfg_if! {
+        if #[cfg(any(target_os = "linux", target_os = "android"))] {
+            unsafe { libc::getauxval(libc::AT_SECURE) != 0 }
+        } else if #[cfg(any(
+            target_vendor = "apple",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "openbsd",
+            target_os = "netbsd",
+        ))] {
+            unsafe { libc::issetugid() != 0 }
+        } else if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+            extern "C" {
+                // not declared by the libc crate on these systems.
+                fn issetugid() -> libc::c_int;
+            }
+            unsafe { issetugid() != 0 }
+        } else {
+            false
+        }
+    }
+}
+
+/// Get the home directory of the user who invoked this process through `sudo(8)`, `doas(1)`, or
+/// `pkexec(1)`, so that tools run with elevated privileges can write to the invoking user's
+/// configuration instead of root's. See [`UserIdentifier::invoking_id`] for how the user is
+/// found. If the process was not invoked this way, this is identical to [`my_home`].
+///
+/// Unlike [`my_home`], the `$HOME` environment variable is not used for the invoking user, as
+/// `sudo` may or may not preserve it.
+pub fn invoking_user_home() -> Result<Option<PathBuf>, GetHomeError> {
+    match UserIdentifier::invoking_id()? {
+        Some(id) => id.to_home(),
+        None => my_home(),
     }
 }
 
 impl UserIdentifier {
+    /// Get the identifier of the user who invoked this process through `sudo(8)`, `doas(1)`,
+    /// or `pkexec(1)`. The `SUDO_UID`, `SUDO_USER`, `DOAS_USER`, and `PKEXEC_UID` environment
+    /// variables are checked, in that order. If none of them is set to a valid user, or if the
+    /// `no-env` feature is enabled, `Ok(None)` is returned.
+    ///
+    /// As these are environment variables, they can be set by anyone. They should not be used
+    /// to make security decisions.
+    pub fn invoking_id() -> Result<Option<UserIdentifier>, GetHomeError> {
+        let uid_var = |var| env_var(var).and_then(|v| v.to_str()?.parse().ok());
+        if let Some(uid) = uid_var("SUDO_UID") {
+            return Ok(Some(Self(Uid::from_raw(uid))));
+        }
+        for var in ["SUDO_USER", "DOAS_USER"] {
+            if let Some(name) = env_var(var).as_ref().and_then(|v| v.to_str()) {
+                if let Some(id) = Self::with_username(name)? {
+                    return Ok(Some(id));
+                }
+            }
+        }
+        Ok(uid_var("PKEXEC_UID").map(|uid| Self(Uid::from_raw(uid))))
+    }
+
     /// Get a user's id from their username. This function operates identically to
     /// the [`home`] function, except it reads the `uid` field
-    /// from the [`User`] structure instead of the `dir` field. Because of this,
+    /// of the user's entry instead of the `dir` field. Because of this,
     /// doing `UserIdentifier::with_username(name)?.unwrap().to_home()` is not recommended. Instead,
     /// `home(name)` should be used.
     pub fn with_username<S: AsRef<str>>(username: S) -> Result<Option<Self>, GetHomeError> {
-        Ok(User::from_name(username.as_ref())?.map(|user| UserIdentifier(user.uid)))
+        Self::with_username_os(username.as_ref())
+    }
+
+    /// Get a user's id given a username which may not be valid UTF-8. This is otherwise
+    /// identical to [`UserIdentifier::with_username`].
+    pub fn with_username_os<S: AsRef<OsStr>>(username: S) -> Result<Option<Self>, GetHomeError> {
+        Ok(lookup_os(username.as_ref())?.map(|entry| UserIdentifier(entry.uid())))
     }
 
-    /// Get the current process' real user id. This uses the nix crate's [`Uid::current`](nix::unistd::Uid::current)
-    /// method, which uses [`getuid(3)`](https://man7.org/linux/man-pages/man3/getuid.3p.html).
+    /// Get a user's id from their username, matching it case-insensitively with
+    /// [`PasswdLookup::ignore_case`], along with the canonical name of the account which was
+    /// matched. Unlike [`UserIdentifier::with_username`], this does not use the fallbacks
+    /// enabled by this crate's features.
+    pub fn with_username_ignore_case<S: AsRef<str>>(
+        username: S,
+    ) -> Result<Option<(Self, String)>, GetHomeError> {
+        Ok(PasswdLookup::new()
+            .ignore_case(true)
+            .entry(username.as_ref())?
+            .map(|entry| {
+                let name = entry.name().to_string_lossy().into_owned();
+                (UserIdentifier(entry.uid()), name)
+            }))
+    }
+
+    /// Get the current process' real user id. This uses [`Uid::current`],
+    /// which uses [`getuid(3)`](https://man7.org/linux/man-pages/man3/getuid.3p.html).
     /// This function will never return the `Err` variant on Unix systems. However,
     /// the error is kept so that the API remains the same on both Unix and Windows.
     ///
@@ -103,21 +543,27 @@ impl UserIdentifier {
         Ok(Self(Uid::current()))
     }
 
+    /// Get the current process' effective user id, using
+    /// [`geteuid(2)`](https://man7.org/linux/man-pages/man2/geteuid.2.html). This differs from
+    /// [`UserIdentifier::my_id`] in setuid programs.
+    pub fn my_effective_id() -> UserIdentifier {
+        Self(Uid::effective())
+    }
+
     /// Get a user's home directory path from their user identifier.
     ///
     /// If some error cocurs when obtaining the path, `Err` is returned. If no user
     /// is associated with `id` could be found, `Ok(None)` is returned. Otherwise,
     /// the path to the user's home directory is returned.
     ///
-    /// This function uses the [`User::from_uid`](nix::unistd::User::from_uid)
-    /// method provided by the nix crate. That method uses the
+    /// This function uses the
     /// [`getpwuid_r(3)`](https://man7.org/linux/man-pages/man3/getpwnam.3.html)
     /// library function to get the home directory from the `/etc/passwd` file.
     ///
     /// # Example
     /// ```no_run
     /// use homedir::unix::UserIdentifier;
-    /// use nix::unistd::Uid;
+    /// use homedir::unix::Uid;
     /// use std::path::PathBuf;
     ///
     /// # fn main() -> Result<(), homedir::unix::GetHomeError> {
@@ -125,14 +571,82 @@ impl UserIdentifier {
     /// // directory is located at /root.
     /// assert_eq!(
     ///     Some(PathBuf::from("/root".to_owned())),
-    ///     // See nix::unistd::Uid::from_raw
     ///     UserIdentifier::from(Uid::from_raw(0)).to_home()?,
     /// );
     /// # Ok(())
     /// # }
     /// ```
     pub fn to_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
-        Ok(User::from_uid(self.0)?.map(|user| user.dir))
+        Ok(self.lookup()?.map(|entry| entry.dir().to_owned()))
+    }
+
+    /// Get a user's home directory path from their user identifier, writing it into `buf`. Only
+    /// the home directory is copied out of the entry returned by `getpwuid_r(3)`. This is
+    /// otherwise identical to [`UserIdentifier::to_home`], see
+    /// [`my_home_into`](crate::my_home_into).
+    pub fn to_home_into(&self, buf: &mut PathBuf) -> Result<bool, GetHomeError> {
+        if PasswdLookup::new().home_into_by_id(self, buf)? {
+            return Ok(true);
+        }
+        let found = fallback_by_id(self)?;
+        Ok(replace_path_opt(
+            buf,
+            found.as_ref().map(|(entry, _)| entry.dir()),
+        ))
+    }
+
+    /// Get a user's home directory path from their user identifier, along with the source it was
+    /// obtained from. This is otherwise identical to [`UserIdentifier::to_home`].
+    pub fn to_home_with_source(&self) -> Result<Option<HomeResult>, GetHomeError> {
+        Ok(self
+            .lookup_with_source()?
+            .map(|(entry, source)| HomeResult::new(entry.dir().to_owned(), source)))
+    }
+
+    /// Get information about the user with this identifier, such as their login shell. See
+    /// [`user_info`].
+    pub fn to_user_info(&self) -> Result<Option<UserInfo>, GetHomeError> {
+        Ok(self.lookup()?.map(UserInfo::from))
+    }
+
+    /// Look up the user with this identifier, with the fallbacks enabled by this crate's
+    /// features.
+    fn lookup(&self) -> Result<Option<PasswdEntry>, GetHomeError> {
+        Ok(self.lookup_with_source()?.map(|(entry, _)| entry))
+    }
+
+    /// Look up the user with this identifier like [`UserIdentifier::lookup`], also returning the
+    /// source which found them.
+    fn lookup_with_source(&self) -> Result<Option<(PasswdEntry, Source)>, GetHomeError> {
+        if let Some(entry) = PasswdLookup::new().entry_by_id(self)? {
+            return Ok(Some((entry, Source::Nss)));
+        }
+        fallback_by_id(self)
+    }
+}
+
+impl From<io::Error> for GetHomeError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl fmt::Display for GetHomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "io error: {e}"),
+            Self::Unsupported => write!(f, "the user database is not supported on this target"),
+            Self::InvalidPath => write!(f, "the path is absolute or escapes the home directory"),
+        }
+    }
+}
+
+impl std::error::Error for GetHomeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Unsupported | Self::InvalidPath => None,
+        }
     }
 }
 
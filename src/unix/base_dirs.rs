@@ -0,0 +1,108 @@
+// src/unix/base_dirs.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The XDG base directories of arbitrary users, relative to their home directory.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use super::{home_os, my_home, GetHomeError};
+use crate::env_var;
+
+/// The XDG base directories of a user, as defined by the
+/// [XDG Base Directory Specification](https://specifications.freedesktop.org/basedir-spec/latest/).
+/// See [`base_dirs`] and [`my_base_dirs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseDirs {
+    home: PathBuf,
+    config: PathBuf,
+    data: PathBuf,
+    cache: PathBuf,
+    state: PathBuf,
+}
+
+/// Get the XDG base directories of a user given their username. The `XDG_CONFIG_HOME`,
+/// `XDG_DATA_HOME`, `XDG_CACHE_HOME`, and `XDG_STATE_HOME` variables of another user's session
+/// are not known, so the defaults of the specification are used, such as `~/.config`. If the
+/// user does not exist, `Ok(None)` is returned.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::base_dirs;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// if let Some(dirs) = base_dirs("alice")? {
+///     println!("{:?}", dirs.config());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn base_dirs<S: AsRef<OsStr>>(username: S) -> Result<Option<BaseDirs>, GetHomeError> {
+    Ok(home_os(username)?.map(BaseDirs::with_home))
+}
+
+/// Get the XDG base directories of this process' user. The home directory is obtained with
+/// [`my_home`], and the `XDG_CONFIG_HOME`, `XDG_DATA_HOME`, `XDG_CACHE_HOME`, and
+/// `XDG_STATE_HOME` environment variables override the defaults if they are set to absolute
+/// paths, as required by the specification. They are not read with the `no-env` feature.
+pub fn my_base_dirs() -> Result<Option<BaseDirs>, GetHomeError> {
+    let Some(mut dirs) = my_home()?.map(BaseDirs::with_home) else {
+        return Ok(None);
+    };
+    for (var, dir) in [
+        ("XDG_CONFIG_HOME", &mut dirs.config),
+        ("XDG_DATA_HOME", &mut dirs.data),
+        ("XDG_CACHE_HOME", &mut dirs.cache),
+        ("XDG_STATE_HOME", &mut dirs.state),
+    ] {
+        if let Some(path) = env_var(var).map(PathBuf::from) {
+            if path.is_absolute() {
+                *dir = path;
+            }
+        }
+    }
+    Ok(Some(dirs))
+}
+
+impl BaseDirs {
+    /// Compute the default base directories of a user given their home directory.
+    pub fn with_home<P: Into<PathBuf>>(home: P) -> Self {
+        let home = home.into();
+        Self {
+            config: home.join(".config"),
+            data: home.join(".local/share"),
+            cache: home.join(".cache"),
+            state: home.join(".local/state"),
+            home,
+        }
+    }
+
+    /// The home directory of the user.
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// The directory of the user's configuration files, `XDG_CONFIG_HOME`.
+    pub fn config(&self) -> &Path {
+        &self.config
+    }
+
+    /// The directory of the user's data files, `XDG_DATA_HOME`.
+    pub fn data(&self) -> &Path {
+        &self.data
+    }
+
+    /// The directory of the user's cache files, `XDG_CACHE_HOME`.
+    pub fn cache(&self) -> &Path {
+        &self.cache
+    }
+
+    /// The directory of the user's state files, such as logs and history, `XDG_STATE_HOME`.
+    pub fn state(&self) -> &Path {
+        &self.state
+    }
+}
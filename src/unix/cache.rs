@@ -0,0 +1,140 @@
+// src/unix/cache.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A snapshot of the user database, for resolving many users without querying NSS each time.
+
+use std::{
+    collections::HashMap,
+    ffi::{OsStr, OsString},
+    mem,
+    path::Path,
+};
+
+use super::{
+    passwd_entries, GetHomeError, PasswdEntry, PasswdLookup, Uid, UidRange, UserIdentifier,
+};
+
+/// This structure contains a snapshot of the user database, taken with a single enumeration
+/// (see [`passwd_entries`]), so that multiple queries can be answered from memory. This is much
+/// faster than looking up users one by one when they are provided by a directory service such
+/// as LDAP. It serves the same purpose as `GetHomeInstance` on Windows.
+///
+/// Some NSS modules do not support enumeration, or only enumerate part of their users, so
+/// users which are found by [`home`](super::home) may be missing from the snapshot. If
+/// multiple entries have the same name or user id, the first one is used.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::PasswdCache;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// let cache = PasswdCache::new()?;
+/// for name in ["root", "alice", "bob"] {
+///     println!("{name}: {:?}", cache.home(name));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PasswdCache {
+    entries: Vec<PasswdEntry>,
+    by_name: HashMap<OsString, usize>,
+    by_uid: HashMap<Uid, usize>,
+}
+
+impl PasswdCache {
+    /// Construct this structure by enumerating the user database.
+    pub fn new() -> Result<Self, GetHomeError> {
+        Ok(Self::from_entries(
+            passwd_entries()?.collect::<Result<Vec<_>, _>>()?,
+        ))
+    }
+
+    /// Take a new snapshot of the user database, for instance after accounts were added or
+    /// removed. If the enumeration fails, the previous snapshot is kept.
+    pub fn refresh(&mut self) -> Result<(), GetHomeError> {
+        *self = Self::new()?;
+        Ok(())
+    }
+
+    /// Look up a single user again, with [`PasswdLookup`], and update their entry in the
+    /// snapshot. If they no longer exist, their entry is removed, and if they were not in the
+    /// snapshot, their entry is added. This is cheaper than [`PasswdCache::refresh`] when the
+    /// accounts which changed are known.
+    pub fn invalidate<S: AsRef<OsStr>>(&mut self, username: S) -> Result<(), GetHomeError> {
+        let username = username.as_ref();
+        let fresh = PasswdLookup::new().entry(username)?;
+        let mut entries = mem::take(&mut self.entries);
+        match (entries.iter().position(|v| v.name() == username), fresh) {
+            (Some(i), Some(entry)) => entries[i] = entry,
+            (Some(i), None) => {
+                entries.remove(i);
+            }
+            (None, Some(entry)) => entries.push(entry),
+            (None, None) => {}
+        }
+        *self = Self::from_entries(entries);
+        Ok(())
+    }
+
+    /// Index the entries of a snapshot.
+    fn from_entries(entries: Vec<PasswdEntry>) -> Self {
+        let mut by_name = HashMap::with_capacity(entries.len());
+        let mut by_uid = HashMap::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            by_name.entry(entry.name().to_owned()).or_insert(i);
+            by_uid.entry(entry.uid()).or_insert(i);
+        }
+        Self {
+            entries,
+            by_name,
+            by_uid,
+        }
+    }
+
+    /// All the entries of the snapshot, in the order in which they were enumerated.
+    pub fn entries(&self) -> &[PasswdEntry] {
+        &self.entries
+    }
+
+    /// Get the entries whose user id is within a range, such as the regular users configured in
+    /// `/etc/login.defs` (see [`UidRange::from_login_defs`]).
+    pub fn entries_in_uid_range(&self, range: UidRange) -> impl Iterator<Item = &PasswdEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| range.contains(entry.uid()))
+    }
+
+    /// Get the entry of a user given their name. With the `unicode-normalization` feature, names
+    /// which only differ in their Unicode normalization form match as well.
+    pub fn entry<S: AsRef<OsStr>>(&self, username: S) -> Option<&PasswdEntry> {
+        let username = username.as_ref();
+        match self.by_name.get(username) {
+            Some(&i) => Some(&self.entries[i]),
+            #[cfg(feature = "unicode-normalization")]
+            None => self
+                .entries
+                .iter()
+                .find(|e| super::normalize::eq(e.name(), username)),
+            #[cfg(not(feature = "unicode-normalization"))]
+            None => None,
+        }
+    }
+
+    /// Get the entry of a user given their identifier.
+    pub fn entry_by_id(&self, id: &UserIdentifier) -> Option<&PasswdEntry> {
+        Some(&self.entries[*self.by_uid.get(&id.0)?])
+    }
+
+    /// Get the home directory of a user given their name.
+    pub fn home<S: AsRef<OsStr>>(&self, username: S) -> Option<&Path> {
+        self.entry(username).map(PasswdEntry::dir)
+    }
+
+    /// Get the home directory of a user given their identifier.
+    pub fn query_home(&self, id: &UserIdentifier) -> Option<&Path> {
+        self.entry_by_id(id).map(PasswdEntry::dir)
+    }
+}
@@ -0,0 +1,99 @@
+// src/unix/cygwin.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Conversion of home directories between the POSIX paths used by Cygwin, such as
+//! `/home/alice`, and the Windows paths they correspond to, such as `C:\cygwin64\home\alice`.
+
+use std::{
+    ffi::{c_void, CString, OsStr},
+    io,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    ptr::null_mut,
+};
+
+use super::{home_os, GetHomeError};
+
+/// Convert a POSIX path to a Windows path, with the narrow character set.
+const CCP_POSIX_TO_WIN_A: libc::c_uint = 0;
+
+/// Convert a Windows path to a POSIX path, with the narrow character set.
+const CCP_WIN_A_TO_POSIX: libc::c_uint = 2;
+
+extern "C" {
+    fn cygwin_conv_path(
+        what: libc::c_uint,
+        from: *const c_void,
+        to: *mut c_void,
+        size: libc::size_t,
+    ) -> libc::ssize_t;
+}
+
+/// The style of a path on Cygwin. See [`convert_path`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathStyle {
+    /// A POSIX path, such as `/home/alice` or `/cygdrive/c/Users/alice`, as used by Cygwin
+    /// programs.
+    Posix,
+    /// A Windows path, such as `C:\cygwin64\home\alice`, as used by native Windows programs.
+    Windows,
+}
+
+/// Get a user's home directory path in the given style. The home directory is looked up in the
+/// Cygwin user database like [`home_os`], which yields a POSIX path, and is then converted with
+/// [`convert_path`] if the Windows style is requested. This is useful to tools which pass the
+/// home directory to native Windows programs.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::{home_with_style, PathStyle};
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// println!("{:?}", home_with_style("alice", PathStyle::Windows)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_with_style<S: AsRef<OsStr>>(
+    username: S,
+    style: PathStyle,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    home_os(username)?
+        .map(|home| convert_path(home, style))
+        .transpose()
+}
+
+/// Convert an absolute path to the given style, using
+/// [`cygwin_conv_path`](https://cygwin.com/cygwin-api/func-cygwin-conv-path.html). Paths
+/// are converted through the mount table of the Cygwin installation, so `/home` maps to the
+/// `home` directory of the installation, and `C:\Users` maps to `/cygdrive/c/Users`.
+pub fn convert_path<P: AsRef<Path>>(path: P, style: PathStyle) -> Result<PathBuf, GetHomeError> {
+    let what = match style {
+        PathStyle::Posix => CCP_WIN_A_TO_POSIX,
+        PathStyle::Windows => CCP_POSIX_TO_WIN_A,
+    };
+    let Ok(from) = CString::new(path.as_ref().as_os_str().as_bytes()) else {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput).into());
+    };
+    unsafe {
+        // with a size of zero, the size of the buffer required is returned.
+        let size = cygwin_conv_path(what, from.as_ptr().cast(), null_mut(), 0);
+        if size < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        let mut buf = vec![0u8; size as usize];
+        if cygwin_conv_path(
+            what,
+            from.as_ptr().cast(),
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+        ) < 0
+        {
+            return Err(io::Error::last_os_error().into());
+        }
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        buf.truncate(len);
+        Ok(PathBuf::from(OsStr::from_bytes(&buf)))
+    }
+}
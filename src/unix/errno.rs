@@ -0,0 +1,59 @@
+// src/unix/errno.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Access to `errno`, for the functions of the C library which only report errors through it.
+
+use std::{ffi::c_int, io};
+
+use cfg_if::cfg_if;
+
+/// Get the location of `errno` for the current thread, or `None` if it is not known on this
+/// target.
+fn location() -> Option<*mut c_int> {
+    cfg_if! {
+        if #[cfg(any(
+            target_os = "linux",
+            target_os = "l4re",
+            target_os = "emscripten",
+            target_os = "fuchsia",
+            target_os = "hurd",
+            target_os = "redox",
+            target_os = "dragonfly",
+        ))] {
+            Some(unsafe { libc::__errno_location() })
+        } else if #[cfg(any(
+            target_os = "android",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "cygwin",
+        ))] {
+            Some(unsafe { libc::__errno() })
+        } else if #[cfg(any(target_vendor = "apple", target_os = "freebsd"))] {
+            Some(unsafe { libc::__error() })
+        } else if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+            Some(unsafe { libc::___errno() })
+        } else if #[cfg(target_os = "haiku")] {
+            Some(unsafe { libc::_errnop() })
+        } else {
+            None
+        }
+    }
+}
+
+/// Set `errno` to zero, so that a function which only sets it on failure can be checked.
+pub(super) fn clear() {
+    if let Some(errno) = location() {
+        unsafe { *errno = 0 };
+    }
+}
+
+/// Get the value of `errno`. If it cannot be cleared on this target, zero is returned, as its
+/// value could be left over from a previous call.
+pub(super) fn last() -> c_int {
+    match location() {
+        Some(_) => io::Error::last_os_error().raw_os_error().unwrap_or(0),
+        None => 0,
+    }
+}
@@ -0,0 +1,74 @@
+// src/unix/find.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Searching the user database for usernames matching a pattern.
+
+use std::collections::HashSet;
+
+use super::{passwd_entries, GetHomeError, UserInfo};
+
+/// Find the users whose username matches a glob pattern, in which `*` matches any sequence of
+/// characters and `?` matches a single character, so that `adm*` finds all the usernames
+/// starting with `adm`. The matching is case-sensitive. The user database is enumerated with
+/// [`passwd_entries`], so this fails with `EBUSY` while a
+/// [`PasswdEntries`](super::PasswdEntries) iterator exists. If a username appears several
+/// times, as when it is provided by several NSS modules, only its first entry is returned.
+/// Usernames which are not valid UTF-8 never match.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::find_users;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// for info in find_users("adm*")? {
+///     println!("{:?}: {:?}", info.username(), info.home());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_users(pattern: &str) -> Result<Vec<UserInfo>, GetHomeError> {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let mut seen = HashSet::new();
+    let mut ret = Vec::new();
+    for entry in passwd_entries()? {
+        let entry = entry?;
+        let Some(name) = entry.name().to_str() else {
+            continue;
+        };
+        if glob_match(&pattern, &name.chars().collect::<Vec<_>>()) && seen.insert(name.to_owned()) {
+            ret.push(UserInfo::from(entry));
+        }
+    }
+    Ok(ret)
+}
+
+/// Check whether a name matches a glob pattern. After a `*`, the rest of the pattern is retried
+/// at each following position of the name.
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // the position of the last `*` in the pattern, and of the name when it was reached.
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp + 1;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
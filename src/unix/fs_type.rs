@@ -0,0 +1,112 @@
+// src/unix/fs_type.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The type of the filesystem a path is on, with `statfs(2)` or `statvfs(2)`, as reported by
+//! [`home_metadata`](crate::home_metadata).
+
+use std::path::Path;
+
+use cfg_if::cfg_if;
+
+/// Get the name of the type of the filesystem which contains a path, such as `nfs` or `zfs`.
+/// `None` is returned if it cannot be determined, or on the targets where it is not supported.
+pub(crate) fn filesystem_type(path: &Path) -> Option<String> {
+    cfg_if! {
+        if #[cfg(any(target_os = "linux", target_os = "android"))] {
+            use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+
+            let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+            let mut buf = MaybeUninit::<libc::statfs>::uninit();
+            if unsafe { libc::statfs(path.as_ptr(), buf.as_mut_ptr()) } != 0 {
+                return None;
+            }
+            // the type of this field differs between the C libraries.
+            #[allow(clippy::unnecessary_cast)]
+            let magic = unsafe { buf.assume_init() }.f_type as u32;
+            Some(match linux_name(magic) {
+                Some(v) => v.to_owned(),
+                None => format!("{magic:#x}"),
+            })
+        } else if #[cfg(any(
+            target_vendor = "apple",
+            target_os = "freebsd",
+            target_os = "openbsd",
+            target_os = "dragonfly",
+        ))] {
+            use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+
+            let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+            let mut buf = MaybeUninit::<libc::statfs>::uninit();
+            if unsafe { libc::statfs(path.as_ptr(), buf.as_mut_ptr()) } != 0 {
+                return None;
+            }
+            from_c_name(&unsafe { buf.assume_init() }.f_fstypename)
+        } else if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+            use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+
+            let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+            let mut buf = MaybeUninit::<libc::statvfs>::uninit();
+            if unsafe { libc::statvfs(path.as_ptr(), buf.as_mut_ptr()) } != 0 {
+                return None;
+            }
+            from_c_name(&unsafe { buf.assume_init() }.f_basetype)
+        } else {
+            let _ = path;
+            None
+        }
+    }
+}
+
+/// Convert a NUL-terminated name stored in a fixed-size array.
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_os = "solaris",
+))]
+fn from_c_name(name: &[libc::c_char]) -> Option<String> {
+    let bytes = name
+        .iter()
+        .take_while(|&&v| v != 0)
+        .map(|&v| v as u8)
+        .collect::<Vec<_>>();
+    (!bytes.is_empty()).then(|| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The name of a filesystem given the magic number Linux reports for it, for the filesystems
+/// home directories are commonly on. See `statfs(2)`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn linux_name(magic: u32) -> Option<&'static str> {
+    Some(match magic {
+        0xEF53 => "ext2/ext3/ext4",
+        0x9123_683E => "btrfs",
+        0x5846_5342 => "xfs",
+        0x2FC1_2FC1 => "zfs",
+        0xCA45_1A4E => "bcachefs",
+        0xF2F5_2010 => "f2fs",
+        0x3153_464A => "jfs",
+        0x5265_4973 => "reiserfs",
+        0x0102_1994 => "tmpfs",
+        0x8584_58F6 => "ramfs",
+        0x794C_7630 => "overlayfs",
+        0x6969 => "nfs",
+        0xFF53_4D42 => "cifs",
+        0xFE53_4D42 => "smb2",
+        0x517B => "smb",
+        0x0102_1997 => "9p",
+        0x00C3_6400 => "ceph",
+        0x4750_4653 => "gpfs",
+        0x0BD0_0BD0 => "lustre",
+        0x6B41_4653 => "afs",
+        0x6573_5546 => "fuse",
+        0xF15F => "ecryptfs",
+        0x4D44 => "vfat",
+        0x2011_BAB0 => "exfat",
+        0x5346_544E => "ntfs",
+        _ => return None,
+    })
+}
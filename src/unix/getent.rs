@@ -0,0 +1,69 @@
+// src/unix/getent.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A fallback which runs the
+//! [`getent(1)`](https://man7.org/linux/man-pages/man1/getent.1.html) program. Statically linked
+//! binaries, notably those linked against musl, cannot load NSS modules, so `getpwnam_r(3)` only
+//! finds the users in `/etc/passwd`. The system's `getent` program is dynamically linked, so it
+//! also finds the users provided by LDAP, SSSD, systemd-homed, etc.
+
+use std::{io, process::Command};
+
+use super::{GetHomeError, PasswdEntry};
+
+/// Run `getent passwd <key>`, where `key` is a username or a numeric user id. `Ok(None)` is
+/// returned if the entry does not exist, or if `getent` is not installed.
+///
+/// `getent` is found through `PATH`, which is controlled by the user who runs this process, so it
+/// is never run if this process is running with elevated privileges through the setuid or setgid
+/// bits, and `Ok(None)` is returned instead.
+pub(super) fn getent_passwd(key: &str) -> Result<Option<PasswdEntry>, GetHomeError> {
+    getent_passwd_as(key, super::is_privileged())
+}
+
+/// Run `getent passwd <key>` as [`getent_passwd`] does, with the privileges of this process
+/// given by `privileged`.
+fn getent_passwd_as(key: &str, privileged: bool) -> Result<Option<PasswdEntry>, GetHomeError> {
+    if privileged {
+        log_debug!("not running getent, as this process is running with elevated privileges");
+        return Ok(None);
+    }
+    // such keys could be mistaken for options, or could never match a valid entry.
+    if key.is_empty() || key.starts_with('-') || key.contains([':', '\n']) {
+        return Ok(None);
+    }
+    let output = match Command::new("getent").arg("passwd").arg(key).output() {
+        Ok(v) => v,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    // getent exits with 2 if the entry was not found.
+    match output.status.code() {
+        Some(0) => (),
+        Some(2) => return Ok(None),
+        _ => {
+            let message = String::from_utf8_lossy(&output.stderr).trim().to_owned();
+            return Err(io::Error::other(message).into());
+        }
+    }
+    let line = output
+        .stdout
+        .split(|&c| c == b'\n')
+        .next()
+        .unwrap_or_default();
+    Ok(PasswdEntry::parse(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn privileged_process_never_runs_getent() {
+        // root exists on every system on which getent would find it.
+        assert!(matches!(getent_passwd_as("root", true), Ok(None)));
+        assert!(matches!(getent_passwd_as("0", true), Ok(None)));
+    }
+}
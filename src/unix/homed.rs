@@ -0,0 +1,142 @@
+// src/unix/homed.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Lookups which query
+//! [`systemd-homed`](https://www.freedesktop.org/software/systemd/man/latest/systemd-homed.service.html)
+//! directly, through the `io.systemd.UserDatabase` Varlink interface it serves on
+//! `/run/systemd/userdb/io.systemd.Home`. The same users are normally provided by the
+//! `nss-systemd` module, which statically linked binaries cannot load.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::PathBuf,
+    str::Chars,
+};
+
+use super::{GetHomeError, Uid};
+
+/// The socket on which `systemd-homed` serves its user records.
+const SOCKET: &str = "/run/systemd/userdb/io.systemd.Home";
+
+/// Get the home directory of a user managed by `systemd-homed`, given either their name or their
+/// id. `Ok(None)` is returned if the user does not exist, or if `systemd-homed` is not running.
+pub(super) fn homed_home(
+    username: Option<&str>,
+    uid: Option<Uid>,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    let key = match (username, uid) {
+        (Some(name), _) => format!("\"userName\":{}", json_string(name)),
+        (None, Some(uid)) => format!("\"uid\":{uid}"),
+        (None, None) => return Ok(None),
+    };
+    let mut stream = match UnixStream::connect(SOCKET) {
+        Ok(v) => v,
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            return Ok(None)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    // Varlink messages are JSON objects terminated by a NUL byte.
+    let request = format!(
+        "{{\"method\":\"io.systemd.UserDatabase.GetUserRecord\",\"parameters\":{{{key},\"service\":\"io.systemd.Home\"}}}}\0"
+    );
+    stream.write_all(request.as_bytes())?;
+    let mut reply = Vec::new();
+    BufReader::new(stream).read_until(0, &mut reply)?;
+    if reply.last() == Some(&0) {
+        reply.pop();
+    }
+    let reply =
+        String::from_utf8(reply).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+    if let Some(error) = string_field(&reply, "error") {
+        return match error.as_str() {
+            "io.systemd.UserDatabase.NoRecordFound" => Ok(None),
+            _ => Err(io::Error::other(error).into()),
+        };
+    }
+    Ok(string_field(&reply, "homeDirectory").map(PathBuf::from))
+}
+
+/// Encode a string as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            c if c < ' ' => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+/// Find the first field with the given name whose value is a string in a JSON document, and
+/// decode it. This is enough for user records, in which the fields which are looked up are not
+/// nested under other names.
+fn string_field(json: &str, name: &str) -> Option<String> {
+    let key = format!("\"{name}\"");
+    let mut rest = json;
+    loop {
+        let i = rest.find(&key)?;
+        rest = rest[i + key.len()..].trim_start();
+        if let Some(value) = rest.strip_prefix(':') {
+            if let Some(value) = value.trim_start().strip_prefix('"') {
+                return decode_string(value);
+            }
+        }
+    }
+}
+
+/// Decode the contents of a JSON string, up to its closing quote.
+fn decode_string(s: &str) -> Option<String> {
+    let mut ret = String::new();
+    let mut chars = s.chars();
+    loop {
+        match chars.next()? {
+            '"' => return Some(ret),
+            '\\' => match chars.next()? {
+                'b' => ret.push('\u{8}'),
+                'f' => ret.push('\u{c}'),
+                'n' => ret.push('\n'),
+                'r' => ret.push('\r'),
+                't' => ret.push('\t'),
+                'u' => {
+                    let high = hex4(&mut chars)?;
+                    let c = if (0xd800..0xdc00).contains(&high) {
+                        // a high surrogate must be followed by an escaped low surrogate.
+                        if chars.next()? != '\\' || chars.next()? != 'u' {
+                            return None;
+                        }
+                        let low = hex4(&mut chars)?.checked_sub(0xdc00)?;
+                        0x10000 + ((high - 0xd800) << 10) + low
+                    } else {
+                        high
+                    };
+                    ret.push(char::from_u32(c)?);
+                }
+                c => ret.push(c),
+            },
+            c => ret.push(c),
+        }
+    }
+}
+
+/// Decode four hexadecimal digits.
+fn hex4(chars: &mut Chars<'_>) -> Option<u32> {
+    let digits = chars.by_ref().take(4).collect::<String>();
+    if digits.len() != 4 || !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(&digits, 16).ok()
+}
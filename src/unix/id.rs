@@ -0,0 +1,102 @@
+// src/unix/id.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! User and group ids, as wrappers over the types of the C library.
+
+use std::fmt;
+
+/// A user id, wrapping the `uid_t` type of the C library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Uid(libc::uid_t);
+
+/// A group id, wrapping the `gid_t` type of the C library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Gid(libc::gid_t);
+
+impl Uid {
+    /// Create a user id from its raw value.
+    pub const fn from_raw(uid: libc::uid_t) -> Self {
+        Self(uid)
+    }
+
+    /// The raw value of this user id.
+    pub const fn as_raw(self) -> libc::uid_t {
+        self.0
+    }
+
+    /// The real user id of this process, using
+    /// [`getuid(2)`](https://man7.org/linux/man-pages/man2/getuid.2.html).
+    pub fn current() -> Self {
+        Self(unsafe { libc::getuid() })
+    }
+
+    /// The effective user id of this process, using
+    /// [`geteuid(2)`](https://man7.org/linux/man-pages/man2/geteuid.2.html).
+    pub fn effective() -> Self {
+        Self(unsafe { libc::geteuid() })
+    }
+}
+
+impl Gid {
+    /// Create a group id from its raw value.
+    pub const fn from_raw(gid: libc::gid_t) -> Self {
+        Self(gid)
+    }
+
+    /// The raw value of this group id.
+    pub const fn as_raw(self) -> libc::gid_t {
+        self.0
+    }
+
+    /// The real group id of this process, using
+    /// [`getgid(2)`](https://man7.org/linux/man-pages/man2/getgid.2.html).
+    pub fn current() -> Self {
+        Self(unsafe { libc::getgid() })
+    }
+
+    /// The effective group id of this process, using
+    /// [`getegid(2)`](https://man7.org/linux/man-pages/man2/getegid.2.html).
+    pub fn effective() -> Self {
+        Self(unsafe { libc::getegid() })
+    }
+}
+
+impl From<libc::uid_t> for Uid {
+    fn from(value: libc::uid_t) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Uid> for libc::uid_t {
+    fn from(value: Uid) -> Self {
+        value.0
+    }
+}
+
+impl From<libc::gid_t> for Gid {
+    fn from(value: libc::gid_t) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Gid> for libc::gid_t {
+    fn from(value: Gid) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for Gid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
@@ -0,0 +1,152 @@
+// src/unix/info.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Information about a user beyond their home directory, obtained from the same lookup.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use super::{lookup_os, GetHomeError, Gid, PasswdEntry, PasswdLookup};
+
+/// Information about a user, as returned by [`user_info`]. This is obtained from their entry in
+/// the user database, so it is retrieved in a single lookup.
+#[derive(Debug, Clone)]
+pub struct UserInfo {
+    entry: PasswdEntry,
+    full_name: Option<String>,
+}
+
+/// The primary group of a user, as returned by [`primary_group`].
+#[derive(Debug, Clone)]
+pub struct PrimaryGroup {
+    gid: Gid,
+    name: Option<String>,
+}
+
+/// Get information about a user given their username, such as their home directory and login
+/// shell. This uses the same lookup as [`home`](super::home), including its fallbacks.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::user_info;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// if let Some(info) = user_info("alice")? {
+///     println!("{:?} {:?}", info.home(), info.shell());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn user_info<S: AsRef<OsStr>>(username: S) -> Result<Option<UserInfo>, GetHomeError> {
+    Ok(lookup_os(username.as_ref())?.map(UserInfo::from))
+}
+
+/// Get the login shell of a user given their username. If the user has no login shell set,
+/// `Ok(None)` is returned, in which case `/bin/sh` is generally used.
+pub fn login_shell<S: AsRef<OsStr>>(username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    Ok(user_info(username)?.and_then(|info| info.shell().map(Path::to_owned)))
+}
+
+/// Get the primary group of a user given their username. This is the group id stored in their
+/// entry of the user database, along with the name of that group if it exists in the group
+/// database. If the user does not exist, `Ok(None)` is returned.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::primary_group;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// if let Some(group) = primary_group("alice")? {
+///     println!("{} {:?}", group.gid(), group.name());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn primary_group<S: AsRef<OsStr>>(username: S) -> Result<Option<PrimaryGroup>, GetHomeError> {
+    let Some(entry) = lookup_os(username.as_ref())? else {
+        return Ok(None);
+    };
+    let gid = entry.gid();
+    let name = PasswdLookup::new()
+        .group_name(gid)?
+        .map(|name| name.to_string_lossy().into_owned());
+    Ok(Some(PrimaryGroup { gid, name }))
+}
+
+impl PrimaryGroup {
+    /// The id of the group.
+    pub fn gid(&self) -> Gid {
+        self.gid
+    }
+
+    /// The name of the group, or `None` if the group id is not in the group database.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl UserInfo {
+    /// The home directory of the user, or `None` if it is empty.
+    pub fn home(&self) -> Option<&Path> {
+        non_empty(self.entry.dir())
+    }
+
+    /// The login shell of the user, or `None` if it is empty.
+    pub fn shell(&self) -> Option<&Path> {
+        non_empty(self.entry.shell())
+    }
+
+    /// The username of the user, or `None` if it is not valid UTF-8.
+    pub fn username(&self) -> Option<&str> {
+        self.entry.name().to_str()
+    }
+
+    /// The full name of the user, such as `Jane Doe`, or `None` if it is not set. This is the
+    /// first comma-separated field of the GECOS field, in which `&` is replaced by the
+    /// capitalized username, as is done by `finger(1)`.
+    pub fn full_name(&self) -> Option<&str> {
+        self.full_name.as_deref()
+    }
+
+    /// The entry of the user database this information was obtained from.
+    pub fn entry(&self) -> &PasswdEntry {
+        &self.entry
+    }
+}
+
+impl From<PasswdEntry> for UserInfo {
+    fn from(value: PasswdEntry) -> Self {
+        Self {
+            full_name: full_name(&value),
+            entry: value,
+        }
+    }
+}
+
+/// Extract the full name from the GECOS field of an entry.
+fn full_name(entry: &PasswdEntry) -> Option<String> {
+    let gecos = entry.gecos().to_string_lossy();
+    let name = gecos.split(',').next().unwrap_or_default();
+    if name.is_empty() {
+        return None;
+    }
+    if !name.contains('&') {
+        return Some(name.to_owned());
+    }
+    let username = entry.name().to_string_lossy();
+    let mut chars = username.chars();
+    let capitalized: String = chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default();
+    Some(name.replace('&', &capitalized))
+}
+
+/// Return `None` if a path is empty.
+fn non_empty(path: &Path) -> Option<&Path> {
+    (!path.as_os_str().is_empty()).then_some(path)
+}
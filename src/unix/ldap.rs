@@ -0,0 +1,277 @@
+// src/unix/ldap.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Looking up home directories directly from an LDAP server, bypassing NSS. This is enabled by
+//! the `ldap` feature.
+
+use std::{fmt, fs, io, path::PathBuf, time::Duration};
+
+use ldap3::{ldap_escape, LdapConn, LdapConnSettings, Scope, SearchEntry};
+
+use super::GetHomeError;
+
+/// The configuration files of the OpenLDAP client library and of `nss_ldap`, in the order in
+/// which they are tried by [`LdapConfig::from_system`].
+const LDAP_CONF_PATHS: [&str; 3] = [
+    "/etc/ldap/ldap.conf",
+    "/etc/openldap/ldap.conf",
+    "/etc/ldap.conf",
+];
+
+/// The configuration file of SSSD.
+const SSSD_CONF_PATH: &str = "/etc/sssd/sssd.conf";
+
+/// The configuration used by [`ldap_home`] to connect to an LDAP server.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::{ldap_home, LdapConfig};
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// let config = LdapConfig::new("ldap://ldap.example.com", "dc=example,dc=com")
+///     .bind("cn=reader,dc=example,dc=com", "secret");
+/// println!("{:?}", ldap_home(&config, "alice")?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct LdapConfig {
+    uri: String,
+    base_dn: String,
+    bind: Option<(String, String)>,
+    timeout: Option<Duration>,
+}
+
+impl LdapConfig {
+    /// Construct a configuration which connects anonymously to the server at `uri`, searching
+    /// for users under `base_dn`.
+    ///
+    /// Only `ldap://` URIs are supported, unless one of the TLS features of the
+    /// [`ldap3`](https://crates.io/crates/ldap3) crate is enabled.
+    pub fn new<U: Into<String>, B: Into<String>>(uri: U, base_dn: B) -> Self {
+        Self {
+            uri: uri.into(),
+            base_dn: base_dn.into(),
+            bind: None,
+            timeout: None,
+        }
+    }
+
+    /// Bind with the given distinguished name and password instead of connecting anonymously.
+    pub fn bind<D: Into<String>, P: Into<String>>(mut self, dn: D, password: P) -> Self {
+        self.bind = Some((dn.into(), password.into()));
+        self
+    }
+
+    /// Set the maximum amount of time to wait for the connection to be established.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Read the configuration of this system's LDAP client. The first LDAP domain of
+    /// `/etc/sssd/sssd.conf` is tried first, then `/etc/ldap/ldap.conf`,
+    /// `/etc/openldap/ldap.conf`, and `/etc/ldap.conf`. Files which do not exist or cannot be
+    /// read because of their permissions are skipped. If no file contains both a URI and a base
+    /// distinguished name, `Ok(None)` is returned.
+    pub fn from_system() -> Result<Option<Self>, GetHomeError> {
+        if let Some(contents) = read_config(SSSD_CONF_PATH)? {
+            if let Some(config) = parse_sssd_conf(&contents) {
+                return Ok(Some(config));
+            }
+        }
+        for path in LDAP_CONF_PATHS {
+            if let Some(contents) = read_config(path)? {
+                if let Some(config) = parse_ldap_conf(&contents) {
+                    return Ok(Some(config));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// The URI of the LDAP server.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// The distinguished name under which users are searched for.
+    pub fn base_dn(&self) -> &str {
+        &self.base_dn
+    }
+}
+
+// the bind password is not printed, as this may end up in logs or panic messages.
+impl fmt::Debug for LdapConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LdapConfig")
+            .field("uri", &self.uri)
+            .field("base_dn", &self.base_dn)
+            .field(
+                "bind",
+                &self.bind.as_ref().map(|(dn, _)| (dn, "<redacted>")),
+            )
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// Get the home directory of a user from the `homeDirectory` attribute of their `posixAccount`
+/// entry on an LDAP server. This does not use NSS, so it works in environments where `nss_ldap`
+/// or SSSD is not installed, such as containers.
+///
+/// If no entry is found, or if the entry has no `homeDirectory` attribute, `Ok(None)` is
+/// returned. Errors from the LDAP client are returned as [`GetHomeError::Io`].
+pub fn ldap_home<S: AsRef<str>>(
+    config: &LdapConfig,
+    username: S,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    let mut settings = LdapConnSettings::new();
+    if let Some(timeout) = config.timeout {
+        settings = settings.set_conn_timeout(timeout);
+    }
+    let mut conn = LdapConn::with_settings(settings, &config.uri).map_err(io::Error::other)?;
+    let ret = search_home(&mut conn, config, username.as_ref());
+    let _ = conn.unbind();
+    ret
+}
+
+/// Bind if necessary, then search for the home directory of `username`.
+fn search_home(
+    conn: &mut LdapConn,
+    config: &LdapConfig,
+    username: &str,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    if let Some((dn, password)) = &config.bind {
+        conn.simple_bind(dn, password)
+            .and_then(|v| v.success())
+            .map_err(io::Error::other)?;
+    }
+    let filter = format!(
+        "(&(objectClass=posixAccount)(uid={}))",
+        ldap_escape(username)
+    );
+    let (entries, _) = conn
+        .search(&config.base_dn, Scope::Subtree, &filter, ["homeDirectory"])
+        .and_then(|v| v.success())
+        .map_err(io::Error::other)?;
+    for entry in entries {
+        let mut entry = SearchEntry::construct(entry);
+        if let Some(dir) = entry
+            .attrs
+            .remove("homeDirectory")
+            .and_then(|v| v.into_iter().next())
+        {
+            return Ok(Some(dir.into()));
+        }
+    }
+    Ok(None)
+}
+
+/// Read a configuration file. `Ok(None)` is returned if it does not exist or cannot be read
+/// because of its permissions, which is the case of `sssd.conf` for users other than root.
+fn read_config(path: &str) -> Result<Option<String>, GetHomeError> {
+    match fs::read_to_string(path) {
+        Ok(v) => Ok(Some(v)),
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied
+            ) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Parse a file in the format of `ldap.conf(5)`, in which each line contains a case-insensitive
+/// keyword followed by whitespace and a value. Only the first of multiple URIs is used.
+fn parse_ldap_conf(contents: &str) -> Option<LdapConfig> {
+    let mut uri = None;
+    let mut base = None;
+    let mut bind_dn = None;
+    let mut bind_pw = None;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((keyword, value)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let value = value.trim();
+        match keyword.to_ascii_lowercase().as_str() {
+            "uri" => uri = value.split_whitespace().next(),
+            "base" => base = Some(value),
+            "binddn" => bind_dn = Some(value),
+            "bindpw" => bind_pw = Some(value),
+            _ => (),
+        }
+    }
+    let mut config = LdapConfig::new(uri?, base?);
+    if let (Some(dn), Some(password)) = (bind_dn, bind_pw) {
+        config = config.bind(dn, password);
+    }
+    Some(config)
+}
+
+/// Parse the first domain of `sssd.conf(5)` whose `id_provider` is `ldap`. Only the first of
+/// multiple URIs is used.
+fn parse_sssd_conf(contents: &str) -> Option<LdapConfig> {
+    let mut in_domain = false;
+    let mut is_ldap = false;
+    let mut uri = None;
+    let mut base = None;
+    let mut bind_dn = None;
+    let mut bind_pw = None;
+    // a line past the end closes the last section.
+    for line in contents.lines().map(str::trim).chain(["[]"]) {
+        if line.starts_with(['#', ';']) {
+            continue;
+        }
+        if line.starts_with('[') {
+            if in_domain && is_ldap {
+                if let (Some(uri), Some(base)) = (uri, base) {
+                    let mut config = LdapConfig::new(uri, base);
+                    if let (Some(dn), Some(password)) = (bind_dn, bind_pw) {
+                        config = config.bind(dn, password);
+                    }
+                    return Some(config);
+                }
+            }
+            in_domain = line.starts_with("[domain/");
+            is_ldap = false;
+            (uri, base, bind_dn, bind_pw) = (None, None, None, None);
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "id_provider" => is_ldap = value == "ldap",
+            "ldap_uri" => uri = value.split(',').next().map(str::trim),
+            "ldap_search_base" => base = Some(value),
+            "ldap_default_bind_dn" => bind_dn = Some(value),
+            "ldap_default_authtok" => bind_pw = Some(value),
+            _ => (),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_password() {
+        let config = LdapConfig::new("ldap://ldap.example.com", "dc=example,dc=com")
+            .bind("cn=reader,dc=example,dc=com", "hunter2");
+        let debug = format!("{config:?}");
+        assert!(debug.contains("cn=reader,dc=example,dc=com"));
+        assert!(!debug.contains("hunter2"));
+    }
+}
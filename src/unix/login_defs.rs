@@ -0,0 +1,118 @@
+// src/unix/login_defs.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The range of user ids assigned to regular users, as configured in `/etc/login.defs`.
+
+use std::{fs, io, path::Path};
+
+use super::{GetHomeError, Uid};
+
+/// The path of the file configuring the shadow password suite.
+const LOGIN_DEFS_PATH: &str = "/etc/login.defs";
+
+/// The default of `UID_MIN` used by `useradd(8)`.
+const DEFAULT_UID_MIN: u32 = 1000;
+
+/// The default of `UID_MAX` used by `useradd(8)`.
+const DEFAULT_UID_MAX: u32 = 60000;
+
+/// An inclusive range of user ids, used to tell the accounts of people apart from system accounts
+/// such as `daemon` and `nobody`.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::{passwd_entries, UidRange};
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// let range = UidRange::from_login_defs()?;
+/// for entry in passwd_entries()?.in_uid_range(range) {
+///     println!("{:?}", entry?.name());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UidRange {
+    min: Uid,
+    max: Uid,
+}
+
+impl UidRange {
+    /// Create a range from its smallest and largest user ids, both inclusive.
+    pub fn new(min: Uid, max: Uid) -> Self {
+        Self { min, max }
+    }
+
+    /// Read the range of user ids assigned to regular users from the `UID_MIN` and `UID_MAX`
+    /// settings of `/etc/login.defs`. Settings which are missing, as well as the file itself,
+    /// default to those of `useradd(8)`, which are 1000 and 60000.
+    pub fn from_login_defs() -> Result<Self, GetHomeError> {
+        match Self::from_file(LOGIN_DEFS_PATH) {
+            Err(GetHomeError::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            ret => ret,
+        }
+    }
+
+    /// Read the `UID_MIN` and `UID_MAX` settings from a file in the format of `login.defs(5)`.
+    /// This is otherwise identical to [`UidRange::from_login_defs`], except that an error is
+    /// returned if the file does not exist.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, GetHomeError> {
+        let contents = fs::read_to_string(path)?;
+        let mut ret = Self::default();
+        for line in contents.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(key), Some(value)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let Some(value) = parse_number(value) else {
+                continue;
+            };
+            match key {
+                "UID_MIN" => ret.min = Uid::from_raw(value),
+                "UID_MAX" => ret.max = Uid::from_raw(value),
+                _ => {}
+            }
+        }
+        Ok(ret)
+    }
+
+    /// The smallest user id in the range.
+    pub fn min(&self) -> Uid {
+        self.min
+    }
+
+    /// The largest user id in the range.
+    pub fn max(&self) -> Uid {
+        self.max
+    }
+
+    /// Whether a user id is within the range.
+    pub fn contains(&self, uid: Uid) -> bool {
+        (self.min.as_raw()..=self.max.as_raw()).contains(&uid.as_raw())
+    }
+}
+
+impl Default for UidRange {
+    /// The range used by `useradd(8)` when `/etc/login.defs` does not configure one, 1000 to
+    /// 60000.
+    fn default() -> Self {
+        Self::new(
+            Uid::from_raw(DEFAULT_UID_MIN),
+            Uid::from_raw(DEFAULT_UID_MAX),
+        )
+    }
+}
+
+/// Parse a number as `login.defs(5)` does, which accepts hexadecimal with a `0x` prefix and
+/// octal with a `0` prefix.
+fn parse_number(s: &str) -> Option<u32> {
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if s.len() > 1 && s.starts_with('0') {
+        u32::from_str_radix(&s[1..], 8).ok()
+    } else {
+        s.parse().ok()
+    }
+}
@@ -0,0 +1,35 @@
+// src/unix/normalize.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Unicode normalization of usernames, enabled by the `unicode-normalization` feature. Account
+//! names may be stored in a different normalization form than the one typed by the user, for
+//! instance in NFD on macOS, where `ü` may be stored as `u` followed by a combining diaeresis.
+
+use std::ffi::{OsStr, OsString};
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Get the NFC and NFD forms of a username, in this order, which differ from it. Usernames which
+/// are not valid UTF-8 have none.
+pub(super) fn alternatives(username: &OsStr) -> Vec<OsString> {
+    let Some(username) = username.to_str() else {
+        return Vec::new();
+    };
+    let mut ret = Vec::new();
+    for form in [username.nfc().collect::<String>(), username.nfd().collect()] {
+        if form != username && !ret.iter().any(|v: &OsString| *v == *form) {
+            ret.push(form.into());
+        }
+    }
+    ret
+}
+
+/// Check whether two usernames are equal once they are both in NFC.
+pub(super) fn eq(a: &OsStr, b: &OsStr) -> bool {
+    match (a.to_str(), b.to_str()) {
+        (Some(a), Some(b)) => a.nfc().eq(b.nfc()),
+        _ => a == b,
+    }
+}
@@ -0,0 +1,260 @@
+// src/unix/passwd.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A parser for files in the format of `/etc/passwd`, which does not depend on the C library.
+
+use std::{
+    ffi::{CStr, OsStr, OsString},
+    fs::File,
+    io::{self, BufRead, BufReader},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use super::{GetHomeError, Gid, Uid};
+
+/// The path of the system's passwd file.
+pub(super) const PASSWD_PATH: &str = "/etc/passwd";
+
+/// An entry of a passwd file, as described in
+/// [`passwd(5)`](https://man7.org/linux/man-pages/man5/passwd.5.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswdEntry {
+    name: OsString,
+    uid: Uid,
+    gid: Gid,
+    gecos: OsString,
+    dir: PathBuf,
+    shell: PathBuf,
+}
+
+impl PasswdEntry {
+    /// Parse a line of a passwd file, which has the format
+    /// `name:password:uid:gid:gecos:dir:shell`. A trailing newline is ignored.
+    ///
+    /// `None` is returned for comments (lines starting with `#`), blank lines, NIS compatibility
+    /// entries (names starting with `+` or `-`), and malformed lines: those without exactly
+    /// seven fields, with an empty name, or with a user or group id which is not a decimal
+    /// number.
+    ///
+    /// # Example
+    /// ```
+    /// use homedir::unix::PasswdEntry;
+    /// use std::path::Path;
+    ///
+    /// let entry = PasswdEntry::parse(b"root:x:0:0:root:/root:/bin/sh").unwrap();
+    /// assert_eq!(entry.dir(), Path::new("/root"));
+    /// assert!(PasswdEntry::parse(b"# a comment").is_none());
+    /// assert!(PasswdEntry::parse(b"root:x:zero:0:root:/root:/bin/sh").is_none());
+    /// ```
+    pub fn parse(line: &[u8]) -> Option<Self> {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if matches!(line.first(), None | Some(b'#' | b'+' | b'-')) {
+            return None;
+        }
+        let fields: Vec<&[u8]> = line.split(|&c| c == b':').collect();
+        let &[name, _, uid, gid, gecos, dir, shell] = fields.as_slice() else {
+            return None;
+        };
+        if name.is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: OsStr::from_bytes(name).to_owned(),
+            uid: Uid::from_raw(parse_id(uid)?),
+            gid: Gid::from_raw(parse_id(gid)?),
+            gecos: OsStr::from_bytes(gecos).to_owned(),
+            dir: OsStr::from_bytes(dir).into(),
+            shell: OsStr::from_bytes(shell).into(),
+        })
+    }
+
+    /// Copy an entry returned by one of the `getpw*` functions.
+    ///
+    /// # Safety
+    /// The strings of `pw` must be valid, or null.
+    pub(super) unsafe fn from_raw(pw: &libc::passwd) -> Self {
+        let string = |ptr: *const libc::c_char| {
+            if ptr.is_null() {
+                OsString::new()
+            } else {
+                OsStr::from_bytes(CStr::from_ptr(ptr).to_bytes()).to_owned()
+            }
+        };
+        Self {
+            name: string(pw.pw_name),
+            uid: Uid::from_raw(pw.pw_uid),
+            gid: Gid::from_raw(pw.pw_gid),
+            #[cfg(not(all(target_os = "android", target_pointer_width = "32")))]
+            gecos: string(pw.pw_gecos),
+            #[cfg(all(target_os = "android", target_pointer_width = "32"))]
+            gecos: OsString::new(),
+            dir: string(pw.pw_dir).into(),
+            shell: string(pw.pw_shell).into(),
+        }
+    }
+
+    /// The name of the user.
+    pub fn name(&self) -> &OsStr {
+        &self.name
+    }
+
+    /// The user id of the user.
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    /// The id of the user's primary group.
+    pub fn gid(&self) -> Gid {
+        self.gid
+    }
+
+    /// The GECOS field of the entry, which generally contains the user's full name.
+    pub fn gecos(&self) -> &OsStr {
+        &self.gecos
+    }
+
+    /// The home directory of the user.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The login shell of the user.
+    pub fn shell(&self) -> &Path {
+        &self.shell
+    }
+}
+
+/// The entries of a passwd file, read into memory at once. This can be used to look up users
+/// in a different root filesystem, such as a mounted container image or virtual machine disk,
+/// or in a file which is not at the usual location.
+///
+/// Lookups behave like [`passwd_entry`](super::passwd_entry): comments and malformed lines
+/// are skipped, and if multiple entries have the same name or id, the first one is used.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::PasswdDatabase;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// let db = PasswdDatabase::open_at("/mnt/image")?;
+/// // The home directory as recorded in the image, for instance `/home/alice`.
+/// println!("{:?}", db.home("alice"));
+/// // Where that directory is on this system, for instance `/mnt/image/home/alice`.
+/// println!("{:?}", db.host_home("alice"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PasswdDatabase {
+    root: PathBuf,
+    entries: Vec<PasswdEntry>,
+}
+
+impl PasswdDatabase {
+    /// Read the passwd file at `path`. The root of the filesystem is assumed to be `/`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, GetHomeError> {
+        Ok(Self {
+            root: PathBuf::from("/"),
+            entries: read_entries(path.as_ref())?,
+        })
+    }
+
+    /// Read the `etc/passwd` file of the filesystem whose root is `root`.
+    pub fn open_at<P: AsRef<Path>>(root: P) -> Result<Self, GetHomeError> {
+        let root = root.as_ref();
+        Ok(Self {
+            root: root.to_owned(),
+            entries: read_entries(&root.join(PASSWD_PATH.trim_start_matches('/')))?,
+        })
+    }
+
+    /// The root of the filesystem this database belongs to.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// All the valid entries of the file, in order.
+    pub fn entries(&self) -> &[PasswdEntry] {
+        &self.entries
+    }
+
+    /// Get the entry of a user given their name. With the `unicode-normalization` feature, names
+    /// which only differ in their Unicode normalization form match as well.
+    pub fn entry<S: AsRef<OsStr>>(&self, username: S) -> Option<&PasswdEntry> {
+        let username = username.as_ref();
+        let entry = self.entries.iter().find(|e| e.name() == username);
+        #[cfg(feature = "unicode-normalization")]
+        let entry = entry.or_else(|| {
+            self.entries
+                .iter()
+                .find(|e| super::normalize::eq(e.name(), username))
+        });
+        entry
+    }
+
+    /// Get the entry of a user given their user id.
+    pub fn entry_by_uid(&self, uid: Uid) -> Option<&PasswdEntry> {
+        self.entries.iter().find(|e| e.uid() == uid)
+    }
+
+    /// Get the home directory of a user given their name, as recorded in the file.
+    pub fn home<S: AsRef<OsStr>>(&self, username: S) -> Option<&Path> {
+        self.entry(username).map(PasswdEntry::dir)
+    }
+
+    /// Get the home directory of a user given their name, relative to the root of this
+    /// database. If this database was opened with [`PasswdDatabase::open_at`], this is the
+    /// location of the home directory on the current system.
+    pub fn host_home<S: AsRef<OsStr>>(&self, username: S) -> Option<PathBuf> {
+        let dir = self.home(username)?;
+        Some(self.root.join(dir.strip_prefix("/").unwrap_or(dir)))
+    }
+}
+
+/// Parse a user or group id, which must only contain decimal digits.
+fn parse_id(field: &[u8]) -> Option<u32> {
+    if field.is_empty() || !field.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    std::str::from_utf8(field).ok()?.parse().ok()
+}
+
+/// Read all the valid entries of the passwd file at `path`.
+fn read_entries(path: &Path) -> Result<Vec<PasswdEntry>, GetHomeError> {
+    let mut ret = Vec::new();
+    for line in BufReader::new(File::open(path)?).split(b'\n') {
+        ret.extend(PasswdEntry::parse(&line?));
+    }
+    Ok(ret)
+}
+
+/// Find the first entry of the passwd file at `path` which satisfies `pred`. Taking the first
+/// entry when there are duplicates matches the behaviour of the C library.
+pub(super) fn find_entry<P: FnMut(&PasswdEntry) -> bool>(
+    path: &Path,
+    mut pred: P,
+) -> Result<Option<PasswdEntry>, GetHomeError> {
+    for line in BufReader::new(File::open(path)?).split(b'\n') {
+        if let Some(entry) = PasswdEntry::parse(&line?) {
+            if pred(&entry) {
+                return Ok(Some(entry));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Find the first entry of the system's passwd file which satisfies `pred`. If the file does
+/// not exist, `Ok(None)` is returned.
+pub(super) fn find_system_entry<P: FnMut(&PasswdEntry) -> bool>(
+    pred: P,
+) -> Result<Option<PasswdEntry>, GetHomeError> {
+    match find_entry(Path::new(PASSWD_PATH), pred) {
+        Err(GetHomeError::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        v => v,
+    }
+}
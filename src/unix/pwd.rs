@@ -0,0 +1,490 @@
+// src/unix/pwd.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Wrappers over the functions of the C library which read the user and group databases. These
+//! accept usernames which are not valid UTF-8.
+
+use std::{
+    ffi::{c_char, c_int, CStr, CString, OsStr, OsString},
+    io,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    ptr::null_mut,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use cfg_if::cfg_if;
+
+use super::{GetHomeError, Gid, PasswdEntry, UidRange, UserIdentifier};
+use crate::replace_path;
+
+cfg_if! {
+    if #[cfg(any(
+        target_os = "espidf",
+        target_os = "horizon",
+        target_os = "nuttx",
+        target_os = "rtems",
+        target_os = "vita",
+    ))] {
+        // these targets do not provide the functions which read the user database, or only
+        // provide stubs of them, so lookups fail with `GetHomeError::Unsupported`.
+        unsafe fn getpwnam_r(
+            _name: *const c_char,
+            _pwd: *mut libc::passwd,
+            _buf: *mut c_char,
+            _len: libc::size_t,
+            _res: *mut *mut libc::passwd,
+        ) -> c_int {
+            libc::ENOSYS
+        }
+
+        unsafe fn getpwuid_r(
+            _uid: libc::uid_t,
+            _pwd: *mut libc::passwd,
+            _buf: *mut c_char,
+            _len: libc::size_t,
+            _res: *mut *mut libc::passwd,
+        ) -> c_int {
+            libc::ENOSYS
+        }
+
+        unsafe fn getgrgid_r(
+            _gid: libc::gid_t,
+            _grp: *mut libc::group,
+            _buf: *mut c_char,
+            _len: libc::size_t,
+            _res: *mut *mut libc::group,
+        ) -> c_int {
+            libc::ENOSYS
+        }
+
+        unsafe fn setpwent() {}
+
+        unsafe fn endpwent() {}
+    } else {
+        use libc::{endpwent, getgrgid_r, getpwnam_r, getpwuid_r, setpwent};
+    }
+}
+
+/// The default size of the buffer above which lookups fail with `ERANGE`.
+const DEFAULT_MAX_BUFFER_SIZE: usize = 16 << 20;
+
+/// The initial size of the buffer if the C library does not recommend one.
+const FALLBACK_BUFFER_SIZE: usize = 16384;
+
+/// Lookups in the user database with
+/// [`getpwnam_r(3)`](https://man7.org/linux/man-pages/man3/getpwnam.3.html) and
+/// `getpwuid_r(3)`, with a configurable buffer size.
+///
+/// These functions store the strings of an entry in a buffer provided by the caller, and fail
+/// with `ERANGE` if it is too small. Whenever this happens, the size of the buffer is doubled
+/// and the lookup is retried, until it exceeds the maximum size. Entries provided by directory
+/// services may have very large GECOS fields, so the maximum may need to be raised.
+///
+/// On illumos and Solaris, `sysconf(_SC_GETPW_R_SIZE_MAX)` recommends 1024 bytes, which is too
+/// small for many directory entries, so the initial size is at least 16 KiB there. As well, the
+/// maximum size is limited to `INT_MAX` bytes on these systems, as their C library does not
+/// accept larger buffers.
+///
+/// The functions of this module, such as [`home`](super::home), use the default
+/// configuration. Unlike them, this does not use the fallbacks enabled by this crate's
+/// features.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::PasswdLookup;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// let lookup = PasswdLookup::new().max_buffer_size(256 << 20);
+/// println!("{:?}", lookup.entry("alice")?.map(|e| e.dir().to_owned()));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PasswdLookup {
+    initial_buffer_size: Option<usize>,
+    max_buffer_size: usize,
+    ignore_case: bool,
+}
+
+impl PasswdLookup {
+    /// Construct the default configuration. The initial size of the buffer is the one
+    /// recommended by `sysconf(_SC_GETPW_R_SIZE_MAX)`, and the maximum size is 16 MiB.
+    pub fn new() -> Self {
+        Self {
+            initial_buffer_size: None,
+            max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
+            ignore_case: false,
+        }
+    }
+
+    /// Set the initial size of the buffer, in bytes.
+    pub fn initial_buffer_size(mut self, size: usize) -> Self {
+        self.initial_buffer_size = Some(size.max(1));
+        self
+    }
+
+    /// Set the size of the buffer, in bytes, above which lookups fail with `ERANGE` instead of
+    /// being retried. This is capped at `INT_MAX` on illumos and Solaris.
+    pub fn max_buffer_size(mut self, size: usize) -> Self {
+        self.max_buffer_size = clamp_buffer_size(size);
+        self
+    }
+
+    /// Set whether usernames are matched case-insensitively, as some directory services, such as
+    /// Active Directory through Samba or SSSD, treat them. If no user has exactly the given
+    /// name, the user database is enumerated with [`passwd_entries`] to find one whose name only
+    /// differs in case, so this fails with `EBUSY` while a [`PasswdEntries`] iterator exists.
+    /// The name of the entry returned is the canonical name of the account.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::unix::PasswdLookup;
+    ///
+    /// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+    /// if let Some(entry) = PasswdLookup::new().ignore_case(true).entry("Alice")? {
+    ///     println!("{:?} is {:?}", entry.name(), entry.dir());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Look up a user by name. A name containing a NUL character never matches a user.
+    pub fn entry<S: AsRef<OsStr>>(&self, username: S) -> Result<Option<PasswdEntry>, GetHomeError> {
+        let username = username.as_ref();
+        let Ok(name) = CString::new(username.as_bytes()) else {
+            return Ok(None);
+        };
+        let entry = unsafe {
+            self.lookup(
+                |pwd, buf, len, res| getpwnam_r(name.as_ptr(), pwd, buf, len, res),
+                |pwd| PasswdEntry::from_raw(pwd),
+            )?
+        };
+        if entry.is_some() || !self.ignore_case {
+            return Ok(entry);
+        }
+        for entry in passwd_entries()? {
+            let entry = entry?;
+            if eq_ignore_case(entry.name(), username) {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Look up a user by identifier.
+    pub fn entry_by_id(&self, id: &UserIdentifier) -> Result<Option<PasswdEntry>, GetHomeError> {
+        let uid = id.0.as_raw();
+        unsafe {
+            self.lookup(
+                |pwd, buf, len, res| getpwuid_r(uid, pwd, buf, len, res),
+                |pwd| PasswdEntry::from_raw(pwd),
+            )
+        }
+    }
+
+    /// Look up the home directory of a user by identifier, writing it into `out` without copying
+    /// the other fields of the entry. `out` is left as it is if the user is not found.
+    pub(super) fn home_into_by_id(
+        &self,
+        id: &UserIdentifier,
+        out: &mut PathBuf,
+    ) -> Result<bool, GetHomeError> {
+        let uid = id.0.as_raw();
+        let found = unsafe {
+            self.lookup(
+                |pwd, buf, len, res| getpwuid_r(uid, pwd, buf, len, res),
+                |pwd: &libc::passwd| {
+                    let dir = if pwd.pw_dir.is_null() {
+                        OsStr::new("")
+                    } else {
+                        OsStr::from_bytes(CStr::from_ptr(pwd.pw_dir).to_bytes())
+                    };
+                    replace_path(out, Path::new(dir));
+                },
+            )?
+        };
+        Ok(found.is_some())
+    }
+
+    /// Look up the name of a group by identifier, using `getgrgid_r(3)`.
+    pub(super) fn group_name(&self, gid: Gid) -> Result<Option<OsString>, GetHomeError> {
+        unsafe {
+            self.lookup(
+                |grp, buf, len, res| getgrgid_r(gid.as_raw(), grp, buf, len, res),
+                |grp: &libc::group| {
+                    OsStr::from_bytes(CStr::from_ptr(grp.gr_name).to_bytes()).to_owned()
+                },
+            )
+        }
+    }
+
+    /// Call one of the `getpw*_r` or `getgr*_r` functions, doubling the size of the buffer
+    /// whenever it fails with `ERANGE`, and convert the entry it returns with `convert`.
+    ///
+    /// # Safety
+    /// `f` must behave like the `getpw*_r` functions, and `convert` must only read the strings
+    /// of the entry, which point into the buffer.
+    unsafe fn lookup<T, R, F, C>(&self, f: F, convert: C) -> Result<Option<R>, GetHomeError>
+    where
+        F: Fn(*mut T, *mut c_char, libc::size_t, *mut *mut T) -> c_int,
+        C: FnOnce(&T) -> R,
+    {
+        let mut size = clamp_buffer_size(
+            self.initial_buffer_size
+                .unwrap_or_else(recommended_buffer_size),
+        );
+        loop {
+            let mut buf: Vec<c_char> = Vec::with_capacity(size);
+            let mut ent = MaybeUninit::<T>::uninit();
+            let mut res = null_mut();
+            match f(ent.as_mut_ptr(), buf.as_mut_ptr(), size, &mut res) {
+                0 if res.is_null() => return Ok(None),
+                // older versions of illumos and Solaris report missing users as errors.
+                #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+                libc::ENOENT | libc::ESRCH if res.is_null() => return Ok(None),
+                // the strings of `ent` point into `buf`, so they are copied before it is dropped.
+                0 => return Ok(Some(convert(ent.assume_init_ref()))),
+                libc::ERANGE if size < self.max_buffer_size => {
+                    size = size.saturating_mul(2).min(self.max_buffer_size);
+                }
+                libc::ENOSYS => return Err(GetHomeError::Unsupported),
+                e => return Err(io::Error::from_raw_os_error(e).into()),
+            }
+        }
+    }
+}
+
+/// Compare two usernames case-insensitively. Names which are not valid UTF-8 are only compared
+/// case-insensitively in their ASCII characters.
+fn eq_ignore_case(a: &OsStr, b: &OsStr) -> bool {
+    match (a.to_str(), b.to_str()) {
+        (Some(a), Some(b)) => a
+            .chars()
+            .flat_map(char::to_lowercase)
+            .eq(b.chars().flat_map(char::to_lowercase)),
+        _ => a.as_bytes().eq_ignore_ascii_case(b.as_bytes()),
+    }
+}
+
+/// The initial size of the buffer recommended by the C library.
+fn recommended_buffer_size() -> usize {
+    let recommended = match unsafe { libc::sysconf(libc::_SC_GETPW_R_SIZE_MAX) } {
+        n if n > 0 => n as usize,
+        _ => return FALLBACK_BUFFER_SIZE,
+    };
+    cfg_if! {
+        if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+            // this is `NSS_BUFLEN_PASSWD`, which only fits entries of `/etc/passwd`.
+            recommended.max(FALLBACK_BUFFER_SIZE)
+        } else {
+            recommended
+        }
+    }
+}
+
+/// Limit the size of a buffer to the largest the C library accepts.
+fn clamp_buffer_size(size: usize) -> usize {
+    cfg_if! {
+        if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+            // the size is converted to an `int` internally.
+            size.min(c_int::MAX as usize)
+        } else {
+            size
+        }
+    }
+}
+
+impl Default for PasswdLookup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether a [`PasswdEntries`] iterator currently exists.
+static ENUMERATING: AtomicBool = AtomicBool::new(false);
+
+/// An iterator over all the entries of the user database, returned by [`passwd_entries`].
+///
+/// The C library keeps the position of the enumeration in global state, so only one such
+/// iterator can exist at a time in a process, and this type is neither `Send` nor `Sync`. As
+/// well, calling `getpwent(3)`, `setpwent(3)`, or `endpwent(3)` elsewhere in the process while
+/// it exists, for instance from another library, interferes with it.
+pub struct PasswdEntries {
+    done: bool,
+    // the C library's enumeration state must stay on the thread which started it.
+    _marker: PhantomData<*const ()>,
+}
+
+/// Enumerate all the entries of the user database, using
+/// [`getpwent(3)`](https://man7.org/linux/man-pages/man3/getpwent.3.html). Unlike reading
+/// `/etc/passwd`, this includes the users provided by NSS modules, such as LDAP, although some
+/// modules do not support enumeration.
+///
+/// If a [`PasswdEntries`] iterator already exists, this fails with `EBUSY`.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::passwd_entries;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// for entry in passwd_entries()? {
+///     let entry = entry?;
+///     println!("{:?}: {:?}", entry.name(), entry.dir());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn passwd_entries() -> Result<PasswdEntries, GetHomeError> {
+    if ENUMERATING.swap(true, Ordering::Acquire) {
+        return Err(io::Error::from_raw_os_error(libc::EBUSY).into());
+    }
+    unsafe { setpwent() };
+    Ok(PasswdEntries {
+        done: false,
+        _marker: PhantomData,
+    })
+}
+
+impl PasswdEntries {
+    /// Only yield the entries whose user id is within a range. Errors are still yielded.
+    pub fn in_uid_range(
+        self,
+        range: UidRange,
+    ) -> impl Iterator<Item = Result<PasswdEntry, GetHomeError>> {
+        self.filter(move |entry| match entry {
+            Ok(entry) => range.contains(entry.uid()),
+            Err(_) => true,
+        })
+    }
+
+    /// Only yield the entries of regular users, excluding system accounts such as `daemon` and
+    /// `nobody`. This uses the range of user ids configured in `/etc/login.defs`, or the default
+    /// range of [`UidRange`] if it cannot be read. Errors are still yielded.
+    pub fn humans_only(self) -> impl Iterator<Item = Result<PasswdEntry, GetHomeError>> {
+        self.in_uid_range(UidRange::from_login_defs().unwrap_or_default())
+    }
+}
+
+impl Iterator for PasswdEntries {
+    type Item = Result<PasswdEntry, GetHomeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let ret = unsafe { next_entry() }.transpose();
+        // errors end the enumeration, as the position within it is unknown.
+        self.done = !matches!(ret, Some(Ok(_)));
+        ret
+    }
+}
+
+impl Drop for PasswdEntries {
+    fn drop(&mut self) {
+        unsafe { endpwent() };
+        ENUMERATING.store(false, Ordering::Release);
+    }
+}
+
+/// Read the next entry of the user database.
+///
+/// # Safety
+/// `setpwent(3)` must have been called.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+unsafe fn next_entry() -> Result<Option<PasswdEntry>, GetHomeError> {
+    // getpwent_r fails with ENOENT at the end of the database.
+    let ret = PasswdLookup::new().lookup(
+        |pwd, buf, len, res| libc::getpwent_r(pwd, buf, len, res),
+        |pwd| PasswdEntry::from_raw(pwd),
+    );
+    match ret {
+        Err(GetHomeError::Io(e)) if e.raw_os_error() == Some(libc::ENOENT) => Ok(None),
+        v => v,
+    }
+}
+
+/// Read the next entry of the user database.
+///
+/// # Safety
+/// `setpwent(3)` must have been called.
+#[cfg(any(
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "nuttx",
+    target_os = "rtems",
+    target_os = "vita",
+))]
+unsafe fn next_entry() -> Result<Option<PasswdEntry>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Read the next entry of the user database.
+///
+/// # Safety
+/// `setpwent(3)` must have been called.
+#[cfg(not(any(
+    all(target_os = "linux", target_env = "gnu"),
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "nuttx",
+    target_os = "rtems",
+    target_os = "vita",
+)))]
+unsafe fn next_entry() -> Result<Option<PasswdEntry>, GetHomeError> {
+    // getpwent only sets errno if an error occurs.
+    super::errno::clear();
+    let pw = libc::getpwent();
+    if !pw.is_null() {
+        return Ok(Some(PasswdEntry::from_raw(&*pw)));
+    }
+    match super::errno::last() {
+        0 | libc::ENOENT => Ok(None),
+        e => Err(io::Error::from_raw_os_error(e).into()),
+    }
+}
+
+#[cfg(all(test, any(target_os = "illumos", target_os = "solaris")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_is_clamped_to_int_max() {
+        assert_eq!(clamp_buffer_size(usize::MAX), c_int::MAX as usize);
+        assert_eq!(
+            clamp_buffer_size(FALLBACK_BUFFER_SIZE),
+            FALLBACK_BUFFER_SIZE
+        );
+        let lookup = PasswdLookup::new().max_buffer_size(usize::MAX);
+        assert_eq!(lookup.max_buffer_size, c_int::MAX as usize);
+    }
+
+    #[test]
+    fn initial_buffer_is_at_least_16_kib() {
+        assert!(recommended_buffer_size() >= FALLBACK_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn missing_user_errors_are_not_found() {
+        for e in [libc::ENOENT, libc::ESRCH] {
+            let ret =
+                unsafe { PasswdLookup::new().lookup(|_: *mut libc::passwd, _, _, _| e, |_| ()) };
+            assert!(matches!(ret, Ok(None)), "{e}: {ret:?}");
+        }
+    }
+
+    #[test]
+    fn missing_user_is_not_found() {
+        let ret = PasswdLookup::new().entry("homedir-test-no-such-user");
+        assert!(matches!(ret, Ok(None)), "{ret:?}");
+    }
+}
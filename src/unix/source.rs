@@ -0,0 +1,65 @@
+// src/unix/source.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The provenance of a home directory, for debugging which lookup answered a query.
+
+use std::path::{Path, PathBuf};
+
+/// The source from which a home directory was obtained, as reported by
+/// [`home_with_source`](super::home_with_source) and
+/// [`my_home_with_source`](super::my_home_with_source).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Source {
+    /// The `$HOME` environment variable.
+    Env,
+    /// The C library, through `getpwnam_r(3)` or `getpwuid_r(3)`. This includes the users
+    /// provided by NSS modules, such as LDAP or `systemd-homed`.
+    Nss,
+    /// `/etc/passwd`, read directly because the `passwd-file` feature is enabled.
+    PasswdFile,
+    /// The `getent(1)` program, run because the `getent` feature is enabled.
+    Getent,
+}
+
+/// A home directory along with the source it was obtained from.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::my_home_with_source;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// if let Some(result) = my_home_with_source()? {
+///     println!("{:?} (from {:?})", result.path(), result.source());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HomeResult {
+    path: PathBuf,
+    source: Source,
+}
+
+impl HomeResult {
+    pub(super) fn new(path: PathBuf, source: Source) -> Self {
+        Self { path, source }
+    }
+
+    /// The home directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The source the home directory was obtained from.
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Convert this into the home directory, discarding its source.
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+}
@@ -0,0 +1,174 @@
+// src/unix/user_dirs.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The XDG user directories of arbitrary users, read from `~/.config/user-dirs.dirs`.
+
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs, io,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use super::{home_os, GetHomeError};
+
+/// The path of the file, relative to a user's home directory, which contains the locations of
+/// their user directories.
+const USER_DIRS_PATH: &str = ".config/user-dirs.dirs";
+
+/// A folder inside a user's home directory, whose location is configured by
+/// [`xdg-user-dirs`](https://www.freedesktop.org/wiki/Software/xdg-user-dirs/). See
+/// [`user_dirs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UserDir {
+    /// The user's desktop, `XDG_DESKTOP_DIR`.
+    Desktop,
+    /// The user's documents, `XDG_DOCUMENTS_DIR`.
+    Documents,
+    /// The user's downloads, `XDG_DOWNLOAD_DIR`.
+    Downloads,
+    /// The user's music, `XDG_MUSIC_DIR`.
+    Music,
+    /// The user's pictures, `XDG_PICTURES_DIR`.
+    Pictures,
+    /// The user's videos, `XDG_VIDEOS_DIR`.
+    Videos,
+    /// The user's templates, `XDG_TEMPLATES_DIR`.
+    Templates,
+    /// The user's public share, `XDG_PUBLICSHARE_DIR`.
+    PublicShare,
+}
+
+/// The user directories of a user, as returned by [`user_dirs`].
+#[derive(Debug, Clone, Default)]
+pub struct UserDirs {
+    dirs: HashMap<UserDir, PathBuf>,
+}
+
+/// Get the user directories of a user given their username, such as their documents and
+/// downloads, by reading `~/.config/user-dirs.dirs` in their home directory. This is the Unix
+/// counterpart of `UserIdentifier::known_folder` on Windows.
+///
+/// If the user does not exist, `Ok(None)` is returned. If the file does not exist, no
+/// directories are configured. Reading the file generally requires permission to read the
+/// user's home directory, so this fails with a permission error for other users unless this
+/// process is privileged.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::{user_dirs, UserDir};
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// if let Some(dirs) = user_dirs("alice")? {
+///     println!("{:?}", dirs.get(UserDir::Documents));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn user_dirs<S: AsRef<OsStr>>(username: S) -> Result<Option<UserDirs>, GetHomeError> {
+    let Some(home) = home_os(username)? else {
+        return Ok(None);
+    };
+    match UserDirs::from_file(home.join(USER_DIRS_PATH), &home) {
+        Err(GetHomeError::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+            Ok(Some(UserDirs::default()))
+        }
+        ret => ret.map(Some),
+    }
+}
+
+impl UserDir {
+    /// The name of the variable in `user-dirs.dirs` which contains the location of this folder.
+    fn variable(self) -> &'static str {
+        match self {
+            Self::Desktop => "XDG_DESKTOP_DIR",
+            Self::Documents => "XDG_DOCUMENTS_DIR",
+            Self::Downloads => "XDG_DOWNLOAD_DIR",
+            Self::Music => "XDG_MUSIC_DIR",
+            Self::Pictures => "XDG_PICTURES_DIR",
+            Self::Videos => "XDG_VIDEOS_DIR",
+            Self::Templates => "XDG_TEMPLATES_DIR",
+            Self::PublicShare => "XDG_PUBLICSHARE_DIR",
+        }
+    }
+
+    /// All the user directories.
+    const ALL: [Self; 8] = [
+        Self::Desktop,
+        Self::Documents,
+        Self::Downloads,
+        Self::Music,
+        Self::Pictures,
+        Self::Videos,
+        Self::Templates,
+        Self::PublicShare,
+    ];
+}
+
+impl UserDirs {
+    /// Read the user directories from a file in the format of `user-dirs.dirs`, substituting
+    /// `home` for `$HOME`. Lines which are not assignments of a known variable are skipped, as
+    /// are values which are neither absolute nor relative to `$HOME`.
+    pub fn from_file<P: AsRef<Path>, H: AsRef<Path>>(
+        path: P,
+        home: H,
+    ) -> Result<Self, GetHomeError> {
+        let contents = fs::read(path)?;
+        let mut dirs = HashMap::new();
+        for line in contents.split(|&b| b == b'\n') {
+            let Some(eq) = line.iter().position(|&b| b == b'=') else {
+                continue;
+            };
+            let (name, value) = (&line[..eq], &line[eq + 1..]);
+            let Some(dir) = UserDir::ALL
+                .into_iter()
+                .find(|d| d.variable().as_bytes() == name)
+            else {
+                continue;
+            };
+            if let Some(path) = parse_value(value, home.as_ref()) {
+                dirs.insert(dir, path);
+            }
+        }
+        Ok(Self { dirs })
+    }
+
+    /// The location of a user directory, or `None` if it is not configured.
+    pub fn get(&self, dir: UserDir) -> Option<&Path> {
+        self.dirs.get(&dir).map(PathBuf::as_path)
+    }
+}
+
+/// Parse the quoted value of a variable in `user-dirs.dirs`, which is either an absolute path or
+/// a path beginning with `$HOME`. Backslashes escape the following character.
+fn parse_value(value: &[u8], home: &Path) -> Option<PathBuf> {
+    let value = value
+        .trim_ascii()
+        .strip_prefix(b"\"")?
+        .strip_suffix(b"\"")?;
+    let mut unescaped = Vec::with_capacity(value.len());
+    let mut escaped = false;
+    for &b in value {
+        if !escaped && b == b'\\' {
+            escaped = true;
+        } else {
+            unescaped.push(b);
+            escaped = false;
+        }
+    }
+    if let Some(rest) = unescaped.strip_prefix(b"$HOME") {
+        let rest = OsStr::from_bytes(rest.strip_prefix(b"/").unwrap_or(rest));
+        if rest.is_empty() {
+            return Some(home.to_owned());
+        }
+        Some(home.join(rest))
+    } else if unescaped.starts_with(b"/") {
+        Some(PathBuf::from(OsStr::from_bytes(&unescaped)))
+    } else {
+        None
+    }
+}
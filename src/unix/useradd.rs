@@ -0,0 +1,97 @@
+// src/unix/useradd.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The home directory that `useradd(8)` would assign to a user who does not exist yet.
+
+use std::{
+    ffi::OsStr,
+    fs, io,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use super::{home_os, GetHomeError};
+
+/// The path of the file containing the defaults of `useradd(8)`.
+const USERADD_DEFAULTS_PATH: &str = "/etc/default/useradd";
+
+/// The base directory used by `useradd(8)` when none is configured.
+const DEFAULT_HOME_BASE: &str = "/home";
+
+/// The home directory of a user, which may not have been created yet, as returned by
+/// [`projected_home`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectedHome {
+    path: PathBuf,
+    exists: bool,
+}
+
+/// Get the home directory a user has or would have if they were created with `useradd(8)`. If
+/// the user exists, their home directory is returned, as with [`home_os`]. Otherwise, the
+/// username is appended to the base directory configured by the `HOME` setting of
+/// `/etc/default/useradd`, which defaults to `/home`. This is useful to provisioning tools, which
+/// need to know where a home directory will be before creating the user.
+///
+/// `Ok(None)` is returned if the user does not exist and their username cannot be the name of a
+/// directory, for instance if it contains a `/`.
+///
+/// # Example
+/// ```no_run
+/// use homedir::unix::projected_home;
+///
+/// # fn main() -> Result<(), homedir::unix::GetHomeError> {
+/// if let Some(home) = projected_home("alice")? {
+///     println!("{:?} (exists: {})", home.path(), home.exists());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn projected_home<S: AsRef<OsStr>>(username: S) -> Result<Option<ProjectedHome>, GetHomeError> {
+    let username = username.as_ref();
+    if let Some(path) = home_os(username)? {
+        return Ok(Some(ProjectedHome { path, exists: true }));
+    }
+    let name = username.as_bytes();
+    if name.is_empty() || name == b"." || name == b".." || name.contains(&b'/') {
+        return Ok(None);
+    }
+    Ok(Some(ProjectedHome {
+        path: home_base()?.join(username),
+        exists: false,
+    }))
+}
+
+/// Read the base directory of new home directories from `/etc/default/useradd`.
+fn home_base() -> Result<PathBuf, GetHomeError> {
+    let contents = match fs::read(USERADD_DEFAULTS_PATH) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(DEFAULT_HOME_BASE.into()),
+        Err(e) => return Err(e.into()),
+    };
+    let base = contents
+        .split(|&b| b == b'\n')
+        .filter_map(|line| line.trim_ascii().strip_prefix(b"HOME="))
+        .map(|value| Path::new(OsStr::from_bytes(value)))
+        .rfind(|path| path.is_absolute());
+    Ok(base.unwrap_or(Path::new(DEFAULT_HOME_BASE)).to_owned())
+}
+
+impl ProjectedHome {
+    /// The path of the home directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the user already exists. If this is `false`, the path is only where the home
+    /// directory is expected to be created.
+    pub fn exists(&self) -> bool {
+        self.exists
+    }
+
+    /// Convert this into the path of the home directory.
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+}
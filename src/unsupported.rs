@@ -0,0 +1,208 @@
+// src/unsupported.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! This is a stub implementation of the crate for targets which are not otherwise supported, such
+//! as `wasm32-unknown-unknown`, enabled by the `unsupported-fallback` feature. Every function
+//! fails with [`GetHomeError::Unsupported`], so that libraries with optional home directory
+//! functionality can still be compiled for these targets.
+//!
+//! On `wasm32-unknown-unknown` with the `wasm-bindgen` feature, [`my_home`] asks the JavaScript
+//! host instead, see [`crate::js`].
+
+use std::{
+    ffi::OsStr,
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use cfg_if::cfg_if;
+
+use crate::Backend;
+
+/// An identifier for a user. This is never constructed on unsupported targets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UserIdentifier {
+    _priv: (),
+}
+
+/// Information about a user, as returned by [`user_info`]. This is never constructed on
+/// unsupported targets.
+#[derive(Debug, Clone)]
+pub struct UserInfo {
+    _priv: (),
+}
+
+/// The primary group of a user, as returned by [`primary_group`]. This is never constructed on
+/// unsupported targets.
+#[derive(Debug, Clone)]
+pub struct PrimaryGroup {
+    _priv: (),
+}
+
+/// The error type returned by this library when errors occur.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GetHomeError {
+    /// The operation is not supported on this target.
+    Unsupported,
+    /// The relative path passed to [`home_join`](crate::home_join) was absolute or escaped the home directory.
+    InvalidPath,
+    /// The JavaScript host failed to provide the home directory, see the [`js`](crate::js)
+    /// module.
+    #[cfg(all(
+        target_arch = "wasm32",
+        target_os = "unknown",
+        feature = "wasm-bindgen"
+    ))]
+    Host(String),
+}
+
+/// Get a user's home directory path. This always fails with [`GetHomeError::Unsupported`].
+pub fn home<S: AsRef<str>>(_username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get a user's home directory path given a username which may not be valid UTF-8. This always
+/// fails with [`GetHomeError::Unsupported`].
+pub fn home_os<S: AsRef<OsStr>>(_username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get information about a user given their username. This always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn user_info<S: AsRef<str>>(_username: S) -> Result<Option<UserInfo>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get the primary group of a user given their username. This always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn primary_group<S: AsRef<str>>(_username: S) -> Result<Option<PrimaryGroup>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// The backends used by [`my_home`], in order, see [`HomeResolver`](crate::HomeResolver).
+pub(crate) fn default_backends() -> Vec<Backend> {
+    vec![Backend::Env]
+}
+
+/// Look up a home directory through a single backend, see
+/// [`HomeResolver`](crate::HomeResolver). [`Backend::Env`] is the only backend on this target,
+/// and it is handled by the resolver, so this always fails with [`GetHomeError::Unsupported`].
+pub(crate) fn backend_home(
+    _backend: Backend,
+    _username: Option<&str>,
+    _timeout: Option<Duration>,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Find the users whose username matches a glob pattern. This always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn find_users(_pattern: &str) -> Result<Vec<UserInfo>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get this process' user's home directory path. This fails with [`GetHomeError::Unsupported`],
+/// unless it is provided by the JavaScript host on `wasm32-unknown-unknown` with the
+/// `wasm-bindgen` feature.
+pub fn my_home() -> Result<Option<PathBuf>, GetHomeError> {
+    cfg_if! {
+        if #[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "wasm-bindgen"))] {
+            crate::js::my_home()
+        } else {
+            Err(GetHomeError::Unsupported)
+        }
+    }
+}
+
+impl UserIdentifier {
+    /// Get a user's id from their username. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn with_username<S: AsRef<str>>(_username: S) -> Result<Option<Self>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get a user's id given a username which may not be valid UTF-8. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn with_username_os<S: AsRef<OsStr>>(_username: S) -> Result<Option<Self>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get a user's id and the canonical name of their account, matching the username
+    /// case-insensitively. This always fails with [`GetHomeError::Unsupported`].
+    pub fn with_username_ignore_case<S: AsRef<str>>(
+        _username: S,
+    ) -> Result<Option<(Self, String)>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get the current process' user id. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn my_id() -> Result<Self, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get a user's home directory path from their user identifier. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn to_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get information about the user with this identifier. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn to_user_info(&self) -> Result<Option<UserInfo>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+}
+
+impl UserInfo {
+    /// The home directory of the user.
+    pub fn home(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The login shell of the user.
+    pub fn shell(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The username of the user.
+    pub fn username(&self) -> Option<&str> {
+        None
+    }
+
+    /// The full name of the user.
+    pub fn full_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl PrimaryGroup {
+    /// The name of the group.
+    pub fn name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl fmt::Display for GetHomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "home directories are not supported on this target"),
+            Self::InvalidPath => write!(f, "the path is absolute or escapes the home directory"),
+            #[cfg(all(
+                target_arch = "wasm32",
+                target_os = "unknown",
+                feature = "wasm-bindgen"
+            ))]
+            Self::Host(e) => write!(
+                f,
+                "the JavaScript host failed to provide the home directory: {e}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GetHomeError {}
@@ -0,0 +1,89 @@
+// src/utf8.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Functions returning home directories as [`Utf8PathBuf`], enabled by the `camino` feature.
+
+use std::{fmt, path::PathBuf};
+
+use camino::{FromPathBufError, Utf8PathBuf};
+
+use crate::{home, my_home, GetHomeError, UserIdentifier};
+
+/// The error type returned by [`home_utf8`], [`my_home_utf8`], and
+/// [`UserIdentifier::to_home_utf8`].
+#[derive(Debug)]
+pub enum Utf8HomeError {
+    /// The home directory could not be looked up.
+    Lookup(GetHomeError),
+    /// The home directory was found, but it is not valid UTF-8. The error contains the path.
+    NotUtf8(FromPathBufError),
+}
+
+/// Get the home directory of an arbitrary user as a [`Utf8PathBuf`]. If no user with the given
+/// username can be found, `Ok(None)` is returned. If their home directory is not valid UTF-8,
+/// [`Utf8HomeError::NotUtf8`] is returned.
+///
+/// # Example
+/// ```no_run
+/// use homedir::home_utf8;
+///
+/// # fn main() -> Result<(), homedir::Utf8HomeError> {
+/// if let Some(home) = home_utf8("alice")? {
+///     println!("{}", home.join(".config"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_utf8<S: AsRef<str>>(username: S) -> Result<Option<Utf8PathBuf>, Utf8HomeError> {
+    to_utf8(home(username)?)
+}
+
+/// Get the home directory of the process' current user as a [`Utf8PathBuf`]. This is otherwise
+/// identical to [`home_utf8`].
+pub fn my_home_utf8() -> Result<Option<Utf8PathBuf>, Utf8HomeError> {
+    to_utf8(my_home()?)
+}
+
+impl UserIdentifier {
+    /// Get the home directory of the user with this identifier as a [`Utf8PathBuf`]. This is
+    /// otherwise identical to [`home_utf8`].
+    pub fn to_home_utf8(&self) -> Result<Option<Utf8PathBuf>, Utf8HomeError> {
+        to_utf8(self.to_home()?)
+    }
+}
+
+fn to_utf8(path: Option<PathBuf>) -> Result<Option<Utf8PathBuf>, Utf8HomeError> {
+    path.map(Utf8PathBuf::try_from)
+        .transpose()
+        .map_err(Utf8HomeError::NotUtf8)
+}
+
+impl fmt::Display for Utf8HomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lookup(e) => e.fmt(f),
+            Self::NotUtf8(e) => write!(
+                f,
+                "home directory is not valid UTF-8: {}",
+                e.as_path().display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Utf8HomeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Lookup(e) => Some(e),
+            Self::NotUtf8(e) => Some(e),
+        }
+    }
+}
+
+impl From<GetHomeError> for Utf8HomeError {
+    fn from(value: GetHomeError) -> Self {
+        Self::Lookup(value)
+    }
+}
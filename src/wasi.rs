@@ -0,0 +1,186 @@
+// src/wasi.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! This is the implementation of the crate for the WebAssembly System Interface. WASI has no
+//! concept of users, so the home directory of the current user can only come from the `HOME`
+//! environment variable passed by the runtime (for instance with `wasmtime --env HOME=/home`),
+//! and looking up other users always fails with [`GetHomeError::Unsupported`].
+
+use std::{
+    ffi::OsStr,
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{env_var, Backend};
+
+/// An identifier for a user. WASI has no users, so this is never constructed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UserIdentifier {
+    _priv: (),
+}
+
+/// Information about a user, as returned by [`user_info`]. WASI has no users, so this is never
+/// constructed.
+#[derive(Debug, Clone)]
+pub struct UserInfo {
+    _priv: (),
+}
+
+/// The primary group of a user, as returned by [`primary_group`]. WASI has no users, so this is
+/// never constructed.
+#[derive(Debug, Clone)]
+pub struct PrimaryGroup {
+    _priv: (),
+}
+
+/// The error type returned by this library when errors occur.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GetHomeError {
+    /// The operation is not supported on WASI, which has no user database.
+    Unsupported,
+    /// The relative path passed to [`home_join`](crate::home_join) was absolute or escaped the home directory.
+    InvalidPath,
+}
+
+/// Get a user's home directory path. WASI has no user database, so this always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn home<S: AsRef<str>>(_username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get a user's home directory path given a username which may not be valid UTF-8. This always
+/// fails with [`GetHomeError::Unsupported`].
+pub fn home_os<S: AsRef<OsStr>>(_username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get information about a user given their username. This always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn user_info<S: AsRef<str>>(_username: S) -> Result<Option<UserInfo>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get the primary group of a user given their username. This always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn primary_group<S: AsRef<str>>(_username: S) -> Result<Option<PrimaryGroup>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// The backends used by [`my_home`], in order, see [`HomeResolver`](crate::HomeResolver).
+pub(crate) fn default_backends() -> Vec<Backend> {
+    vec![Backend::Env]
+}
+
+/// Look up a home directory through a single backend, see
+/// [`HomeResolver`](crate::HomeResolver). [`Backend::Env`] is the only backend on this target,
+/// and it is handled by the resolver, so this always fails with [`GetHomeError::Unsupported`].
+pub(crate) fn backend_home(
+    _backend: Backend,
+    _username: Option<&str>,
+    _timeout: Option<Duration>,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Find the users whose username matches a glob pattern. This always fails with
+/// [`GetHomeError::Unsupported`].
+pub fn find_users(_pattern: &str) -> Result<Vec<UserInfo>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get this process' user's home directory path from the `HOME` environment variable. WASI
+/// programs only see the environment variables the runtime chooses to pass, so if `HOME` is not
+/// set, is empty, or is not an absolute path, `Ok(None)` is returned. The directory is only
+/// accessible if the runtime also preopens it. With the `no-env` feature, `Ok(None)` is always
+/// returned.
+pub fn my_home() -> Result<Option<PathBuf>, GetHomeError> {
+    let Some(home) = env_var("HOME").map(PathBuf::from) else {
+        return Ok(None);
+    };
+    Ok(home.is_absolute().then_some(home))
+}
+
+impl UserIdentifier {
+    /// Get a user's id from their username. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn with_username<S: AsRef<str>>(_username: S) -> Result<Option<Self>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get a user's id given a username which may not be valid UTF-8. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn with_username_os<S: AsRef<OsStr>>(_username: S) -> Result<Option<Self>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get a user's id and the canonical name of their account, matching the username
+    /// case-insensitively. This always fails with [`GetHomeError::Unsupported`].
+    pub fn with_username_ignore_case<S: AsRef<str>>(
+        _username: S,
+    ) -> Result<Option<(Self, String)>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get the current process' user id. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn my_id() -> Result<Self, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get a user's home directory path from their user identifier. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn to_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+
+    /// Get information about the user with this identifier. This always fails with
+    /// [`GetHomeError::Unsupported`].
+    pub fn to_user_info(&self) -> Result<Option<UserInfo>, GetHomeError> {
+        Err(GetHomeError::Unsupported)
+    }
+}
+
+impl UserInfo {
+    /// The home directory of the user.
+    pub fn home(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The login shell of the user.
+    pub fn shell(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The username of the user.
+    pub fn username(&self) -> Option<&str> {
+        None
+    }
+
+    /// The full name of the user.
+    pub fn full_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl PrimaryGroup {
+    /// The name of the group.
+    pub fn name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl fmt::Display for GetHomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "user lookups are not supported on wasi"),
+            Self::InvalidPath => write!(f, "the path is absolute or escapes the home directory"),
+        }
+    }
+}
+
+impl std::error::Error for GetHomeError {}
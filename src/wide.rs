@@ -0,0 +1,62 @@
+// src/wide.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Helpers to convert between `OsStr` and the NUL-terminated UTF-16 strings used by the
+//! Windows API. Going through `OsString` is lossless, even for names that are not valid UTF-16.
+
+use std::{
+    ffi::{OsStr, OsString},
+    os::windows::ffi::{OsStrExt, OsStringExt},
+};
+
+/// Encode `s` as a NUL-terminated UTF-16 string. `None` is returned if `s` contains a NUL.
+pub(crate) fn to_wide(s: &OsStr) -> Option<Vec<u16>> {
+    let mut ret: Vec<u16> = s.encode_wide().collect();
+    if ret.contains(&0) {
+        return None;
+    }
+    ret.push(0);
+    Some(ret)
+}
+
+/// Read a NUL-terminated UTF-16 string.
+///
+/// # Safety
+/// `ptr` must point to a valid NUL-terminated UTF-16 string.
+pub(crate) unsafe fn from_wide_ptr(ptr: *const u16) -> OsString {
+    OsString::from_wide(wide_ptr_slice(ptr))
+}
+
+/// Get the characters of a NUL-terminated UTF-16 string, without the NUL.
+///
+/// # Safety
+/// `ptr` must point to a valid NUL-terminated UTF-16 string, which must outlive the slice.
+pub(crate) unsafe fn wide_ptr_slice<'a>(ptr: *const u16) -> &'a [u16] {
+    let mut len = 0;
+    while *ptr.add(len) != 0 {
+        len += 1;
+    }
+    std::slice::from_raw_parts(ptr, len)
+}
+
+/// Read a UTF-16 string which may or may not be NUL-terminated. Everything from the first NUL
+/// onwards is ignored.
+pub(crate) fn from_wide(s: &[u16]) -> OsString {
+    let len = s.iter().position(|&c| c == 0).unwrap_or(s.len());
+    OsString::from_wide(&s[..len])
+}
+
+/// Replace the contents of `buf` with a UTF-16 string like [`from_wide`], reusing its
+/// allocation. Only unpaired surrogates, which are rare, are converted through a new `OsString`.
+pub(crate) fn from_wide_into(buf: &mut OsString, s: &[u16]) {
+    let len = s.iter().position(|&c| c == 0).unwrap_or(s.len());
+    buf.clear();
+    for c in char::decode_utf16(s[..len].iter().copied()) {
+        match c {
+            Ok(c) => buf.push(c.encode_utf8(&mut [0; 4])),
+            Err(e) => buf.push(OsString::from_wide(&[e.unpaired_surrogate()])),
+        }
+    }
+}
@@ -6,47 +6,72 @@
 use core::fmt;
 use std::{
     alloc::{alloc_zeroed, dealloc, Layout},
+    ffi::{OsStr, OsString},
     mem::align_of,
-    path::PathBuf,
+    os::windows::{
+        ffi::OsStrExt,
+        io::{AsRawHandle, BorrowedHandle},
+    },
+    path::{Path, PathBuf},
     ptr::null_mut,
+    time::Duration,
 };
 
 use cfg_if::cfg_if;
-use widestring::{
-    error::{ContainsNul, Utf16Error},
-    U16CStr, U16CString, U16Str,
-};
 use windows::{
-    core::{w, Error as WinError, BSTR, PCWSTR, PWSTR, VARIANT},
+    core::{Error as WinError, PCWSTR, PWSTR},
     Win32::{
         Foundation::{
-            CloseHandle, LocalFree, ERROR_INSUFFICIENT_BUFFER, ERROR_NONE_MAPPED, E_OUTOFMEMORY, E_UNEXPECTED, HANDLE, HLOCAL, PSID
+            CloseHandle, LocalFree, ERROR_INSUFFICIENT_BUFFER, ERROR_NONE_MAPPED, E_INVALIDARG,
+            E_OUTOFMEMORY, E_UNEXPECTED, HANDLE, HLOCAL, PSID,
         },
         Security::{
-            Authorization::ConvertSidToStringSidW, GetTokenInformation, LookupAccountNameW,
-            TokenUser, SID, SID_NAME_USE, TOKEN_QUERY, TOKEN_USER,
+            Authentication::Identity::{NameSamCompatible, NameUserPrincipal, TranslateNameW},
+            Authorization::{
+                ConvertSidToStringSidW, ConvertStringSidToSidW, GetNamedSecurityInfoW,
+                SE_FILE_OBJECT,
+            },
+            GetTokenInformation, LookupAccountNameW, LookupAccountSidW, TokenUser, ACL,
+            DACL_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, SID,
+            SID_NAME_USE, TOKEN_QUERY, TOKEN_USER,
         },
+        Storage::FileSystem::{GetVolumeInformationW, GetVolumePathNameW},
         System::{
-            Com::{
-                CoCreateInstance, CoSetProxyBlanket, CoTaskMemFree, CLSCTX_INPROC_SERVER,
-                EOAC_NONE, RPC_C_AUTHN_LEVEL_CALL, RPC_C_IMP_LEVEL_IMPERSONATE,
-            },
-            Rpc::{RPC_C_AUTHN_WINNT, RPC_C_AUTHZ_NONE},
+            Com::CoTaskMemFree,
             Threading::{GetCurrentProcess, OpenProcessToken},
-            Wmi::{
-                IWbemLocator, IWbemServices, WbemLocator, WBEM_FLAG_CONNECT_USE_MAX_WAIT,
-                WBEM_FLAG_FORWARD_ONLY, WBEM_FLAG_RETURN_IMMEDIATELY, WBEM_INFINITE,
-            },
         },
         UI::Shell::{FOLDERID_Profile, SHGetKnownFolderPath, KNOWN_FOLDER_FLAG},
     },
 };
 
-#[cfg(feature = "windows-coinitialize")]
-use windows::Win32::{
-    Foundation::CO_E_NOTINITIALIZED,
-    System::Com::{CoInitializeEx, COINIT_MULTITHREADED},
+use crate::wide::{from_wide, from_wide_into, from_wide_ptr, to_wide, wide_ptr_slice};
+use crate::{replace_path_opt, Backend};
+
+pub use account::{AccountName, LookupScope};
+pub use package::is_packaged;
+pub use path_form::PathForm;
+pub use shell_folders::KnownFolder;
+pub use token::{
+    desktop_user_home, home_for_token, known_folder_for_token, load_user_profile, LoadedProfile,
 };
+#[cfg(feature = "windows-wmi")]
+pub use wmi::{GetHomeInstance, ProfileInfo, Profiles, QueryOptions, QUERY_MANY_CHUNK};
+
+use netapi::{computer_name, NetUserInfo};
+use package::packaged_home;
+use registry::{RegKey, HKEY_LOCAL_MACHINE, PROFILE_LIST};
+
+mod account;
+mod netapi;
+mod package;
+mod path_form;
+mod registry;
+#[cfg(feature = "windows-wmi")]
+mod shared;
+mod shell_folders;
+mod token;
+#[cfg(feature = "windows-wmi")]
+mod wmi;
 
 /// An identifier for a user.
 ///
@@ -55,7 +80,7 @@ use windows::Win32::{
 ///
 /// See [`UserIdentifier::with_username`] for an example of the usage of this structure.
 #[repr(transparent)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct UserIdentifier(String);
 
 /// This enumeration is the error type returned by this crate's functions
@@ -64,20 +89,62 @@ pub struct UserIdentifier(String);
 pub enum GetHomeError {
     /// This represents an error as obtained from Windows' API.
     WindowsError(WinError),
-    /// This represents an error when parsing UTF-16 text.
-    Utf16Error(Utf16Error),
-    /// This represents an error when trying to represent a string that contains
-    /// a NUL byte `'\0'` as a C string.
-    ContainsNul(ContainsNul<u16>),
     /// This represents an error when a returned pointer was null when it was not expected to be
     /// so.
     NullPointerResult,
+    /// The operation is not supported in this context, such as in a packaged app running in an
+    /// app container. See [`is_packaged`].
+    Unsupported,
+    /// The relative path passed to [`home_join`](crate::home_join) was absolute or escaped the home directory.
+    InvalidPath,
 }
 
-/// This structure caches the results of the operations necessary to check the profile
-/// directory from an SID, see [`GetHomeInstance::query_home`]. This way, multiple
-/// queries can be performed at a smaller cost.
-pub struct GetHomeInstance(IWbemServices);
+/// The kind of directory to obtain for a user, see [`home_of_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HomeKind {
+    /// The profile directory of the user, for example `C:\Users\Administrator`. This is the
+    /// directory returned by [`home`].
+    Profile,
+    /// The home directory configured for the account, for example `\\server\homes\alice`.
+    /// For domain accounts, this is the `homeDirectory` attribute in Active Directory. Many
+    /// accounts do not have one.
+    Account,
+}
+
+/// The home directory configured for an account, as returned by [`account_home`].
+///
+/// This is obtained from the
+/// [`USER_INFO_4`](https://learn.microsoft.com/en-us/windows/win32/api/lmaccess/ns-lmaccess-user_info_4)
+/// structure. These are the values set in the "Profile" tab of the account's properties, or the
+/// corresponding Active Directory attributes.
+#[derive(Debug, Clone)]
+pub struct AccountHome {
+    home_dir: Option<PathBuf>,
+    home_dir_drive: Option<String>,
+    profile_path: Option<PathBuf>,
+}
+
+/// The primary group of a user, as returned by [`primary_group`].
+#[derive(Debug, Clone)]
+pub struct PrimaryGroup {
+    sid: String,
+    name: Option<AccountName>,
+}
+
+/// Information about a user, as returned by [`user_info`].
+#[derive(Debug, Clone)]
+pub struct UserInfo {
+    home: Option<PathBuf>,
+    username: Option<String>,
+    full_name: Option<String>,
+}
+
+/// The profile directory a user has or would have, as returned by [`projected_home`].
+#[derive(Debug, Clone)]
+pub struct ProjectedHome {
+    path: PathBuf,
+    exists: bool,
+}
 
 /// This function will get the home directory of a user given their username. Internally,
 /// it calls [`UserIdentifier::with_username`] followed by [`UserIdentifier::to_home`].
@@ -92,6 +159,253 @@ pub fn home<S: AsRef<str>>(username: S) -> Result<Option<PathBuf>, GetHomeError>
     id.to_home()
 }
 
+/// This function will get the home directory of a user given their username as an `OsStr`.
+/// Internally, it calls [`UserIdentifier::with_username_os`] followed by
+/// [`UserIdentifier::to_home`].
+pub fn home_os<S: AsRef<OsStr>>(username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    let Some(id) = UserIdentifier::with_username_os(username)? else {
+        return Ok(None);
+    };
+    id.to_home()
+}
+
+/// This function will get the home directory of a user given their username as a UTF-16 string.
+/// Internally, it calls [`UserIdentifier::with_username_wide`] followed by
+/// [`UserIdentifier::to_home`].
+pub fn home_wide(username: &[u16]) -> Result<Option<PathBuf>, GetHomeError> {
+    let Some(id) = UserIdentifier::with_username_wide(username)? else {
+        return Ok(None);
+    };
+    id.to_home()
+}
+
+/// This function will get the home directory of a user given their username, only looking for
+/// the account in the given scope. Internally, it calls [`UserIdentifier::with_username_in`]
+/// followed by [`UserIdentifier::to_home`].
+pub fn home_in<S: AsRef<str>>(
+    username: S,
+    scope: &LookupScope,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    let Some(id) = UserIdentifier::with_username_in(username, scope)? else {
+        return Ok(None);
+    };
+    id.to_home()
+}
+
+/// Get the profile directory of a user given their username, or, if they do not have a profile
+/// yet (because they have never logged on), the directory their profile would be created in.
+///
+/// The projected directory is the account name (without the domain) inside the directory named
+/// by the `ProfilesDirectory` registry value, which is usually `C:\Users`. Note that if this
+/// directory already exists when the profile is created, Windows picks a different name (for
+/// instance, by appending the domain or a number), so this is only a prediction.
+///
+/// If no user with the given username can be found, `Ok(None)` is returned.
+///
+/// # Example
+/// ```no_run
+/// use homedir::windows::projected_home;
+///
+/// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+/// if let Some(home) = projected_home("alice")? {
+///     if !home.exists() {
+///         println!("alice's profile will be created in {}", home.path().display());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn projected_home<S: AsRef<str>>(username: S) -> Result<Option<ProjectedHome>, GetHomeError> {
+    let Some(id) = UserIdentifier::with_username(username)? else {
+        return Ok(None);
+    };
+    if let Some(path) = id.to_home()? {
+        return Ok(Some(ProjectedHome { path, exists: true }));
+    }
+    let Some(account) = id.account_name()? else {
+        return Ok(None);
+    };
+    let profiles_dir = RegKey::open(HKEY_LOCAL_MACHINE, PROFILE_LIST)?
+        .map(|key| key.get_string("ProfilesDirectory"))
+        .transpose()?
+        .flatten()
+        .ok_or_else(|| WinError::from(E_UNEXPECTED))?;
+    Ok(Some(ProjectedHome {
+        path: PathBuf::from(profiles_dir).join(account.name()),
+        exists: false,
+    }))
+}
+
+/// Get the home directory configured for an account given its username, using
+/// [`NetUserGetInfo`](https://learn.microsoft.com/en-us/windows/win32/api/lmaccess/nf-lmaccess-netusergetinfo).
+/// For domain accounts, this queries a domain controller of the account's domain.
+///
+/// This is distinct from the profile directory returned by [`home`]: logon scripts map the
+/// configured home directory (often a file share) to the configured drive. If no account with
+/// the given username can be found, `Ok(None)` is returned.
+///
+/// # Example
+/// ```no_run
+/// use homedir::windows::account_home;
+///
+/// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+/// if let Some(account) = account_home("CONTOSO\\alice")? {
+///     if let (Some(dir), Some(drive)) = (account.home_dir(), account.home_dir_drive()) {
+///         println!("net use {drive} {}", dir.display());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn account_home<S: AsRef<str>>(username: S) -> Result<Option<AccountHome>, GetHomeError> {
+    Ok(
+        NetUserInfo::with_username(username.as_ref())?.map(|info| AccountHome {
+            home_dir: info.home_dir().map(PathBuf::from),
+            home_dir_drive: info
+                .home_dir_drive()
+                .map(|v| v.to_string_lossy().into_owned()),
+            profile_path: info.profile().map(PathBuf::from),
+        }),
+    )
+}
+
+/// Get information about a user given their username. If the user does not exist, `Ok(None)`
+/// is returned.
+pub fn user_info<S: AsRef<str>>(username: S) -> Result<Option<UserInfo>, GetHomeError> {
+    let Some(id) = UserIdentifier::with_username(username)? else {
+        return Ok(None);
+    };
+    id.to_user_info().map(Some)
+}
+
+/// The backends used by [`my_home`] and [`home`], in order, see
+/// [`HomeResolver`](crate::HomeResolver).
+pub(crate) fn default_backends() -> Vec<Backend> {
+    if cfg!(feature = "windows-wmi") {
+        vec![Backend::KnownFolder, Backend::Wmi]
+    } else {
+        vec![Backend::KnownFolder, Backend::Registry]
+    }
+}
+
+/// Look up a home directory through a single backend, see
+/// [`HomeResolver`](crate::HomeResolver). `None` stands for the current user.
+pub(crate) fn backend_home(
+    backend: Backend,
+    username: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    if cfg!(not(feature = "windows-wmi")) && backend == Backend::Wmi {
+        return Err(GetHomeError::Unsupported);
+    }
+    let id = match (backend, username) {
+        (Backend::KnownFolder, None) => return my_home(),
+        (Backend::KnownFolder, Some(_)) => return Ok(None),
+        (Backend::Wmi | Backend::Registry, Some(name)) => {
+            match UserIdentifier::with_username(name)? {
+                Some(id) => id,
+                None => return Ok(None),
+            }
+        }
+        (Backend::Wmi | Backend::Registry, None) => UserIdentifier::my_id()?,
+        (Backend::NetApi, Some(name)) => {
+            return Ok(account_home(name)?.and_then(|v| v.home_dir));
+        }
+        (Backend::NetApi, None) => match UserIdentifier::my_id()?.account_name()? {
+            Some(name) => return Ok(account_home(name.to_string())?.and_then(|v| v.home_dir)),
+            None => return Ok(None),
+        },
+        _ => return Err(GetHomeError::Unsupported),
+    };
+    match (backend, timeout) {
+        (Backend::Registry, _) => id.to_home_from_registry(),
+        #[cfg(feature = "windows-wmi")]
+        (_, Some(timeout)) if !is_packaged() => {
+            GetHomeInstance::with_options(QueryOptions::new().timeout(timeout))?.query_home(&id)
+        }
+        _ => id.to_home(),
+    }
+}
+
+/// Find the local accounts whose name matches a glob pattern, in which `*` matches any sequence
+/// of characters and `?` matches a single character, so that `adm*` finds all the accounts whose
+/// name starts with `adm`. Internally, this calls [`GetHomeInstance::find_users`] on the shared
+/// instance, see [`GetHomeInstance::reset_shared`].
+///
+/// Calling this function may present some issues if any other parts of the program use
+/// [`CoInitializeEx`](https://learn.microsoft.com/en-us/windows/win32/api/combaseapi/nf-combaseapi-coinitializeex).
+/// See [for Windows users](crate#for-windows-users) for more information.
+///
+/// Without the `windows-wmi` feature, this always fails with [`GetHomeError::Unsupported`].
+pub fn find_users(pattern: &str) -> Result<Vec<UserInfo>, GetHomeError> {
+    cfg_if! {
+        if #[cfg(feature = "windows-wmi")] {
+            shared::with_shared(|v| v.find_users(pattern))
+        } else {
+            let _ = pattern;
+            Err(GetHomeError::Unsupported)
+        }
+    }
+}
+
+/// Get the primary group of a user given their username, using
+/// [`NetUserGetInfo`](https://learn.microsoft.com/en-us/windows/win32/api/lmaccess/nf-lmaccess-netusergetinfo).
+/// For domain accounts, this queries a domain controller of the account's domain, and the
+/// primary group is generally `Domain Users`. If the user does not exist, `Ok(None)` is
+/// returned.
+///
+/// # Example
+/// ```no_run
+/// use homedir::windows::primary_group;
+///
+/// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+/// if let Some(group) = primary_group("alice")? {
+///     println!("{} {:?}", group.sid(), group.name());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn primary_group<S: AsRef<str>>(username: S) -> Result<Option<PrimaryGroup>, GetHomeError> {
+    let Some(info) = NetUserInfo::with_username(username.as_ref())? else {
+        return Ok(None);
+    };
+    let Some(sid) = info.primary_group_sid()? else {
+        return Ok(None);
+    };
+    let name = lookup_string_sid(&sid)?;
+    Ok(Some(PrimaryGroup { sid, name }))
+}
+
+/// This function will get either the profile directory or the configured home directory of a
+/// user given their username, see [`HomeKind`].
+///
+/// The configured home directory is obtained with
+/// [`NetUserGetInfo`](https://learn.microsoft.com/en-us/windows/win32/api/lmaccess/nf-lmaccess-netusergetinfo).
+/// For domain accounts, this queries a domain controller of the account's domain.
+///
+/// # Example
+/// ```no_run
+/// use homedir::windows::{home_of_kind, HomeKind};
+///
+/// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+/// // This assumes there is a domain account named `CONTOSO\alice` whose home directory is on a
+/// // file share.
+/// if let Some(home) = home_of_kind("CONTOSO\\alice", HomeKind::Account)? {
+///     println!("{}", home.display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn home_of_kind<S: AsRef<str>>(
+    username: S,
+    kind: HomeKind,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    match kind {
+        HomeKind::Profile => home(username),
+        HomeKind::Account => Ok(account_home(username)?.and_then(|v| v.home_dir)),
+    }
+}
+
 /// Get the home directory of the current process' user.
 pub fn my_home() -> Result<Option<PathBuf>, GetHomeError> {
     unsafe {
@@ -100,96 +414,533 @@ pub fn my_home() -> Result<Option<PathBuf>, GetHomeError> {
         if out.is_null() {
             return Ok(None);
         }
-        let s = U16CStr::from_ptr_str(out).to_os_string().into();
+        let s = from_wide_ptr(out).into();
         CoTaskMemFree(Some(out.cast()));
         Ok(Some(s))
     }
 }
 
+/// This function will get the current user's profile directory, writing it into `buf`. This is
+/// otherwise identical to [`my_home`], see [`my_home_into`](crate::my_home_into).
+pub fn my_home_into(buf: &mut PathBuf) -> Result<bool, GetHomeError> {
+    unsafe {
+        let out = SHGetKnownFolderPath(&FOLDERID_Profile, KNOWN_FOLDER_FLAG(0), None)?.0;
+        if out.is_null() {
+            buf.as_mut_os_string().clear();
+            return Ok(false);
+        }
+        from_wide_into(buf.as_mut_os_string(), wide_ptr_slice(out));
+        CoTaskMemFree(Some(out.cast()));
+        Ok(true)
+    }
+}
+
 unsafe fn sid_to_string(sid: PSID) -> Result<UserIdentifier, GetHomeError> {
     let mut str_pointer: PWSTR = PWSTR::null();
     // convert the SID to string.
     ConvertSidToStringSidW(sid, &mut str_pointer)?;
-    let ret = match U16CStr::from_ptr_str(str_pointer.0).to_string() {
-        Ok(v) => v,
-        Err(e) => {
-            // we already have an error. I won't check for this one.
-            LocalFree(HLOCAL(str_pointer.0.cast()));
+    let ret = from_wide_ptr(str_pointer.0);
+    if !LocalFree(HLOCAL(str_pointer.0.cast())).0.is_null() {
+        Err(WinError::from_win32())?;
+    }
+    // string SIDs only ever contain ASCII characters.
+    match ret.into_string() {
+        Ok(v) => Ok(UserIdentifier(v)),
+        Err(_) => Err(WinError::from(E_UNEXPECTED).into()),
+    }
+}
+
+/// Get the identifier of the owner of a file or directory, and the number of entries in its
+/// discretionary access control list, or `None` if it has none, with `GetNamedSecurityInfoW`.
+pub(crate) fn file_security(path: &Path) -> Result<(UserIdentifier, Option<u32>), GetHomeError> {
+    let Some(path) = to_wide(path.as_os_str()) else {
+        return Err(WinError::from(E_INVALIDARG).into());
+    };
+    let mut owner = PSID::default();
+    let mut dacl: *mut ACL = std::ptr::null_mut();
+    let mut descriptor = PSECURITY_DESCRIPTOR::default();
+    unsafe {
+        GetNamedSecurityInfoW(
+            PCWSTR(path.as_ptr()),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+            Some(&mut owner),
+            None,
+            Some(&mut dacl),
+            None,
+            &mut descriptor,
+        )
+        .ok()?;
+        // both point into the security descriptor, which must be freed after they are used.
+        let ret = sid_to_string(owner).map(|v| (v, dacl.as_ref().map(|v| u32::from(v.AceCount))));
+        let _ = LocalFree(HLOCAL(descriptor.0));
+        ret
+    }
+}
+
+/// Get the name of the filesystem of the volume which contains a path, such as `NTFS`, with
+/// `GetVolumeInformationW`.
+pub(crate) fn filesystem_type(path: &Path) -> Option<String> {
+    let path = to_wide(path.as_os_str())?;
+    let mut root = [0u16; 261];
+    let mut name = [0u16; 261];
+    unsafe {
+        GetVolumePathNameW(PCWSTR(path.as_ptr()), &mut root).ok()?;
+        GetVolumeInformationW(
+            PCWSTR(root.as_ptr()),
+            None,
+            None,
+            None,
+            None,
+            Some(&mut name),
+        )
+        .ok()?;
+    }
+    let len = name.iter().position(|&v| v == 0).unwrap_or(name.len());
+    Some(String::from_utf16_lossy(&name[..len]))
+}
+
+/// Look up the identifier of an account, along with the name of the domain it belongs to.
+/// `username` must be NUL-terminated.
+fn lookup_account_name(
+    username: &[u16],
+) -> Result<Option<(UserIdentifier, OsString)>, GetHomeError> {
+    unsafe {
+        let mut sid_size = 0;
+        let mut domain_size = 0;
+        let mut peuse = SID_NAME_USE(0);
+        // get buffer length necessary for SID.
+        if let Err(e) = LookupAccountNameW(
+            None,
+            PCWSTR(username.as_ptr()),
+            PSID(null_mut()),
+            &mut sid_size,
+            PWSTR::null(),
+            &mut domain_size,
+            &mut peuse,
+        ) {
+            if e == ERROR_NONE_MAPPED.into() {
+                return Ok(None);
+            } else if e != ERROR_INSUFFICIENT_BUFFER.into() {
+                return Err(e.into());
+            }
+        }
+        if sid_size == 0 {
+            return Err(WinError::from(E_UNEXPECTED).into());
+        }
+        let layout = Layout::from_size_align(sid_size as usize, align_of::<SID>()).unwrap();
+        let sid_buf = alloc_zeroed(layout);
+        if sid_buf.is_null() {
+            return Err(WinError::from(E_OUTOFMEMORY).into());
+        }
+        // the domain is unfortunately necessary, otherwise the function will not operate
+        // correctly.
+        let mut domain = vec![0; domain_size as usize];
+        let psid = PSID(sid_buf.cast());
+        let ret = if let Err(e) = LookupAccountNameW(
+            None,
+            PCWSTR(username.as_ptr()),
+            psid,
+            &mut sid_size,
+            PWSTR(domain.as_mut_ptr()),
+            &mut domain_size,
+            &mut peuse,
+        ) {
+            Err(e.into())
+        } else {
+            let domain = from_wide(&domain);
+            sid_to_string(psid).map(|id| Some((id, domain)))
+        };
+        dealloc(sid_buf, layout);
+        ret
+    }
+}
+
+/// Look up the name of the account a SID belongs to.
+unsafe fn lookup_account_sid(psid: PSID) -> Result<Option<AccountName>, GetHomeError> {
+    let mut name_size = 0;
+    let mut domain_size = 0;
+    let mut peuse = SID_NAME_USE(0);
+    // get the buffer lengths necessary for the name and domain.
+    if let Err(e) = LookupAccountSidW(
+        None,
+        psid,
+        PWSTR::null(),
+        &mut name_size,
+        PWSTR::null(),
+        &mut domain_size,
+        &mut peuse,
+    ) {
+        if e == ERROR_NONE_MAPPED.into() {
+            return Ok(None);
+        } else if e != ERROR_INSUFFICIENT_BUFFER.into() {
             return Err(e.into());
         }
     }
-    .to_owned();
-    if !LocalFree(HLOCAL(str_pointer.0.cast())).0.is_null() {
-        Err(WinError::from_win32())?;
+    let mut name = vec![0; name_size as usize];
+    let mut domain = vec![0; domain_size as usize];
+    LookupAccountSidW(
+        None,
+        psid,
+        PWSTR(name.as_mut_ptr()),
+        &mut name_size,
+        PWSTR(domain.as_mut_ptr()),
+        &mut domain_size,
+        &mut peuse,
+    )?;
+    let name = from_wide(&name).to_string_lossy().into_owned();
+    let domain = from_wide(&domain).to_string_lossy().into_owned();
+    Ok(Some(AccountName::new(
+        (!domain.is_empty()).then_some(domain),
+        name,
+    )))
+}
+
+/// Get the name of the account with the given string SID.
+fn lookup_string_sid(sid: &str) -> Result<Option<AccountName>, GetHomeError> {
+    // string SIDs never contain a NUL character.
+    let Some(sid_str) = to_wide(sid.as_ref()) else {
+        return Ok(None);
+    };
+    unsafe {
+        let mut psid = PSID::default();
+        ConvertStringSidToSidW(PCWSTR(sid_str.as_ptr()), &mut psid)?;
+        let ret = lookup_account_sid(psid);
+        let _ = LocalFree(HLOCAL(psid.0));
+        ret
     }
-    Ok(UserIdentifier(ret))
+}
+
+/// Translate a user principal name, such as `alice@contoso.com`, to the corresponding SAM
+/// compatible name, such as `CONTOSO\alice`, using
+/// [`TranslateNameW`](https://learn.microsoft.com/en-us/windows/win32/api/secext/nf-secext-translatenamew).
+/// Both strings are NUL-terminated. `None` is returned if `username` is not a user principal name,
+/// or if it could not be translated (for instance, because this machine is not part of a domain).
+fn upn_to_sam(username: &[u16]) -> Option<Vec<u16>> {
+    if !username.contains(&(b'@' as u16)) || username.contains(&(b'\\' as u16)) {
+        return None;
+    }
+    unsafe {
+        let mut size = 0;
+        // this fails, setting `size` to the required length.
+        let _ = TranslateNameW(
+            PCWSTR(username.as_ptr()),
+            NameUserPrincipal,
+            NameSamCompatible,
+            PWSTR::null(),
+            &mut size,
+        );
+        if size == 0 {
+            return None;
+        }
+        let mut buf = vec![0u16; size as usize];
+        TranslateNameW(
+            PCWSTR(username.as_ptr()),
+            NameUserPrincipal,
+            NameSamCompatible,
+            PWSTR(buf.as_mut_ptr()),
+            &mut size,
+        )
+        .as_bool()
+        .then_some(buf)
+    }
+}
+
+/// The prefix of the SIDs of Azure AD (Microsoft Entra ID) accounts.
+const AZURE_AD_SID_PREFIX: &str = "S-1-12-1-";
+
+/// The key under `HKEY_LOCAL_MACHINE` in which the identities of cloud accounts which have
+/// logged on to this machine are cached.
+const IDENTITY_CACHE: &str = "SOFTWARE\\Microsoft\\IdentityStore\\Cache";
+
+/// Look up an Azure AD account given its user principal name, optionally prefixed by the
+/// `AzureAD\` domain, for example `AzureAD\alice@contoso.com`.
+///
+/// `LookupAccountNameW` does not resolve these names on Azure AD joined machines, so instead
+/// the user principal names cached in the identity store are compared against `username`. This
+/// only finds accounts which have logged on to this machine, but these are the only ones which
+/// can have a profile anyway.
+fn azure_ad_lookup(username: &str) -> Result<Option<UserIdentifier>, GetHomeError> {
+    let name = match username.split_once('\\') {
+        Some((domain, name)) if domain.eq_ignore_ascii_case("AzureAD") => name,
+        Some(_) => return Ok(None),
+        None => username,
+    };
+    if !name.contains('@') {
+        return Ok(None);
+    }
+    let Some(cache) = RegKey::open(HKEY_LOCAL_MACHINE, IDENTITY_CACHE)? else {
+        return Ok(None);
+    };
+    for sid in cache.subkeys()? {
+        let Some(sid) = sid.to_str() else {
+            continue;
+        };
+        if !sid.starts_with(AZURE_AD_SID_PREFIX) {
+            continue;
+        }
+        let path = format!("{IDENTITY_CACHE}\\{sid}\\IdentityCache\\{sid}");
+        let Some(key) = RegKey::open(HKEY_LOCAL_MACHINE, &path)? else {
+            continue;
+        };
+        if key
+            .get_string("UserName")?
+            .is_some_and(|upn| upn.to_string_lossy().eq_ignore_ascii_case(name))
+        {
+            return Ok(Some(UserIdentifier(sid.to_owned())));
+        }
+    }
+    Ok(None)
 }
 
 impl UserIdentifier {
+    /// Construct a user identifier from the text representation of a SID, as received by the C
+    /// API. This only checks that it has the form `S-1-...`, not that the user exists.
+    #[cfg(feature = "ffi")]
+    pub(crate) fn from_sid_string(sid: &str) -> Option<Self> {
+        let valid = sid.strip_prefix("S-1-").is_some_and(|v| {
+            v.split('-')
+                .all(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()))
+        });
+        valid.then(|| Self(sid.to_owned()))
+    }
+
     /// Get the user identifier of a user given their username.
+    ///
+    /// Besides plain account names such as `Administrator` and qualified names such as
+    /// `CONTOSO\alice`, this accepts user principal names such as `alice@contoso.com`. If
+    /// `LookupAccountNameW` cannot resolve a user principal name directly, it is translated to the
+    /// corresponding `DOMAIN\user` name first.
     pub fn with_username<S: AsRef<str>>(
         username: S,
     ) -> Result<Option<UserIdentifier>, GetHomeError> {
-        unsafe {
-            let username = U16CString::from_str(username)?;
-            let mut sid_size = 0;
-            let mut domain_size = 0;
-            let mut peuse = SID_NAME_USE(0);
-            // get buffer length necessary for SID.
-            if let Err(e) = LookupAccountNameW(
-                None,
-                PCWSTR(username.as_ptr()),
-                PSID(null_mut()),
-                &mut sid_size,
-                PWSTR::null(),
-                &mut domain_size,
-                &mut peuse,
-            ) {
-                if e == ERROR_NONE_MAPPED.into() {
+        let username_str = username.as_ref();
+        // no account name can contain a NUL character.
+        let Some(username) = to_wide(username_str.as_ref()) else {
+            return Ok(None);
+        };
+        with_username_imp(&username, Some(username_str))
+    }
+
+    /// Get the user identifier of a user given their username as an `OsStr`. This is identical
+    /// to [`UserIdentifier::with_username_wide`].
+    pub fn with_username_os<S: AsRef<OsStr>>(
+        username: S,
+    ) -> Result<Option<UserIdentifier>, GetHomeError> {
+        let username: Vec<u16> = username.as_ref().encode_wide().collect();
+        Self::with_username_wide(&username)
+    }
+
+    /// Get the user identifier of a user given their username as a UTF-16 string, for instance
+    /// as returned by another function of the Windows API. The username may or may not be
+    /// NUL-terminated; everything from the first NUL onwards is ignored.
+    ///
+    /// This is otherwise identical to [`UserIdentifier::with_username`].
+    pub fn with_username_wide(username: &[u16]) -> Result<Option<UserIdentifier>, GetHomeError> {
+        let len = username
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(username.len());
+        let mut wide = Vec::with_capacity(len + 1);
+        wide.extend_from_slice(&username[..len]);
+        wide.push(0);
+        // Azure AD names are looked up by comparing strings, which requires valid UTF-16.
+        let username_str = String::from_utf16(&username[..len]).ok();
+        with_username_imp(&wide, username_str.as_deref())
+    }
+
+    /// Get the user identifier of a user given their username, only looking for the account in
+    /// the given scope. The username may be qualified with a domain, as in `DOMAIN\user`. If
+    /// this domain does not match the scope, `Ok(None)` is returned.
+    ///
+    /// This is useful when the same name exists both on the local machine and in a domain, as
+    /// [`UserIdentifier::with_username`] silently picks whichever one `LookupAccountNameW` finds
+    /// first.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::windows::{LookupScope, UserIdentifier};
+    ///
+    /// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+    /// // This only finds the local account, even if there is also a domain account named `alice`.
+    /// let id = UserIdentifier::with_username_in("alice", &LookupScope::LocalOnly)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_username_in<S: AsRef<str>>(
+        username: S,
+        scope: &LookupScope,
+    ) -> Result<Option<UserIdentifier>, GetHomeError> {
+        let account = AccountName::parse(username.as_ref());
+        let qualified = match scope {
+            LookupScope::Any => return Self::with_username(username),
+            LookupScope::LocalOnly => {
+                let computer_name = computer_name()?.to_string_lossy().into_owned();
+                if account.domain().is_some() && !account.is_local(&computer_name) {
                     return Ok(None);
-                } else if e != ERROR_INSUFFICIENT_BUFFER.into() {
-                    return Err(e.into());
                 }
+                AccountName::new(Some(computer_name.as_str()), account.name())
             }
-            if sid_size == 0 {
-                return Err(WinError::from(E_UNEXPECTED).into());
-            }
-            let layout = Layout::from_size_align(sid_size as usize, align_of::<SID>()).unwrap();
-            let sid_buf = alloc_zeroed(layout);
-            if sid_buf.is_null() {
-                return Err(WinError::from(E_OUTOFMEMORY).into());
+            LookupScope::Domain(domain) => {
+                if account
+                    .domain()
+                    .is_some_and(|d| !d.eq_ignore_ascii_case(domain))
+                {
+                    return Ok(None);
+                }
+                AccountName::new(Some(domain.as_str()), account.name())
             }
-            // the domain is unfortunately necessary, otherwise the function will not operate
-            // correctly.
-            let mut domain = vec![0; domain_size as usize];
-            let psid = PSID(sid_buf.cast());
-            let ret = if let Err(e) = LookupAccountNameW(
-                None,
-                PCWSTR(username.as_ptr()),
-                psid,
-                &mut sid_size,
-                PWSTR(domain.as_mut_ptr()),
-                &mut domain_size,
-                &mut peuse,
-            ) {
-                Err(e.into())
-            } else {
-                sid_to_string(psid).map(Some)
-            };
-            dealloc(sid_buf, layout);
-            ret
-        }
+        };
+        // no account name can contain a NUL character.
+        let Some(qualified) = to_wide(qualified.to_string().as_ref()) else {
+            return Ok(None);
+        };
+        Ok(lookup_account_name(&qualified)?.map(|(id, _)| id))
+    }
+
+    /// Get the user identifier of a user given their username, along with the canonical name of
+    /// the account, as returned by [`UserIdentifier::account_name`]. Account names are always
+    /// matched case-insensitively on Windows, so this finds the same user as
+    /// [`UserIdentifier::with_username`]. If the username is qualified with a domain, as in
+    /// `DOMAIN\user`, the name returned is qualified as well. If the name of the account cannot
+    /// be looked up, as for some Azure AD accounts, the username is returned as given.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::windows::UserIdentifier;
+    ///
+    /// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+    /// if let Some((_, name)) = UserIdentifier::with_username_ignore_case("ADMINISTRATOR")? {
+    ///     assert_eq!("Administrator", name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_username_ignore_case<S: AsRef<str>>(
+        username: S,
+    ) -> Result<Option<(UserIdentifier, String)>, GetHomeError> {
+        let username = username.as_ref();
+        let Some(id) = Self::with_username(username)? else {
+            return Ok(None);
+        };
+        let name = match id.account_name()? {
+            Some(name) if username.contains('\\') => name.to_string(),
+            Some(name) => name.name().to_owned(),
+            None => username.to_owned(),
+        };
+        Ok(Some((id, name)))
+    }
+
+    /// Get the name of the account this identifier belongs to, using
+    /// [`LookupAccountSidW`](https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-lookupaccountsidw).
+    /// If the identifier does not belong to any account (for instance, because the account was
+    /// deleted), `Ok(None)` is returned.
+    pub fn account_name(&self) -> Result<Option<AccountName>, GetHomeError> {
+        lookup_string_sid(&self.0)
+    }
+
+    /// Get information about the user with this identifier. See [`user_info`].
+    ///
+    /// The full name is obtained with `NetUserGetInfo`, which queries a domain controller for
+    /// domain accounts.
+    pub fn to_user_info(&self) -> Result<UserInfo, GetHomeError> {
+        let name = self.account_name()?;
+        let account = match &name {
+            Some(name) => NetUserInfo::with_username(&name.to_string())?,
+            None => None,
+        };
+        Ok(UserInfo {
+            home: self.to_home()?,
+            username: name.map(|v| v.name().to_owned()),
+            full_name: account
+                .as_ref()
+                .and_then(NetUserInfo::full_name)
+                .map(|v| v.to_string_lossy().into_owned()),
+        })
+    }
+
+    /// Get the location of a known folder, such as the documents folder, of the user with this
+    /// identifier. This takes into account folders which were redirected, for instance to a
+    /// file share. If the user does not have a profile, `Ok(None)` is returned.
+    ///
+    /// The location is read from the user's registry hive. If the user is not logged on, this
+    /// requires read access to the `NTUSER.DAT` file in their profile directory, which
+    /// generally means the calling process must be running as an administrator.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::windows::{KnownFolder, UserIdentifier};
+    ///
+    /// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+    /// if let Some(id) = UserIdentifier::with_username("alice")? {
+    ///     println!("{:?}", id.known_folder(KnownFolder::Documents)?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn known_folder(&self, folder: KnownFolder) -> Result<Option<PathBuf>, GetHomeError> {
+        let Some(profile) = self.to_home()? else {
+            return Ok(None);
+        };
+        shell_folders::user_shell_folder(self, &profile, folder).map(Some)
+    }
+
+    /// Check whether this identifier belongs to an Azure AD (Microsoft Entra ID) account.
+    pub fn is_azure_ad(&self) -> bool {
+        self.0.starts_with(AZURE_AD_SID_PREFIX)
     }
 
     /// This function will get the home directory of a user given their identifier.
-    /// Internally, this function calls [`GetHomeInstance::new`] followed by
-    /// [`GetHomeInstance::query_home`].
+    /// Internally, this function calls [`GetHomeInstance::query_home`] on an instance which is
+    /// connected on first use and shared by the whole process, see
+    /// [`GetHomeInstance::reset_shared`].
     ///
     /// Calling this function may present some issues if any other parts of the program use
     /// [`CoInitializeEx`](https://learn.microsoft.com/en-us/windows/win32/api/combaseapi/nf-combaseapi-coinitializeex).
     /// See [for Windows users](crate#for-windows-users) for more information.
+    ///
+    /// In packaged apps, the Windows Management Instrumentation is not used. See
+    /// [`is_packaged`]. Without the `windows-wmi` feature, it is not used either, and this
+    /// calls [`UserIdentifier::to_home_from_registry`] instead.
     pub fn to_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
-        GetHomeInstance::new()?.query_home(self)
+        if is_packaged() {
+            return packaged_home(self);
+        }
+        cfg_if! {
+            if #[cfg(feature = "windows-wmi")] {
+                shared::with_shared(|v| v.query_home(self))
+            } else {
+                self.to_home_from_registry()
+            }
+        }
+    }
+
+    /// Get the home directory of a user given their identifier, writing it into `buf`. With the
+    /// `windows-wmi` feature, the path is decoded directly into `buf`. This is otherwise
+    /// identical to [`UserIdentifier::to_home`], see [`my_home_into`](crate::my_home_into).
+    pub fn to_home_into(&self, buf: &mut PathBuf) -> Result<bool, GetHomeError> {
+        cfg_if! {
+            if #[cfg(feature = "windows-wmi")] {
+                if !is_packaged() {
+                    return shared::with_shared(|v| v.query_home_into(self, buf));
+                }
+            }
+        }
+        let home = self.to_home()?;
+        Ok(replace_path_opt(buf, home.as_deref()))
+    }
+
+    /// Get the profile directory of the user with this identifier from the `ProfileImagePath`
+    /// value of their key in the `ProfileList` key of the registry, which is where
+    /// `Win32_UserProfile` obtains it from. Unlike [`UserIdentifier::to_home`], this does not use
+    /// the Windows Management Instrumentation, but it fails with an access denied error in an app
+    /// container.
+    pub fn to_home_from_registry(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        let Some(key) = RegKey::open(HKEY_LOCAL_MACHINE, &format!("{PROFILE_LIST}\\{}", self.0))?
+        else {
+            return Ok(None);
+        };
+        Ok(key.get_string("ProfileImagePath")?.map(PathBuf::from))
     }
 
     /// Get the identifier of this process' user.
@@ -201,138 +952,151 @@ impl UserIdentifier {
             // get a token to query information about the current process. this handle must be dropped
             // manually with CloseHandle, as seen below.
             OpenProcessToken(handle, TOKEN_QUERY, &mut token_handle)?;
-            let mut buffer_size = 0;
-            // get the length of the buffer requried for this query.
-            if let Err(e) = GetTokenInformation(token_handle, TokenUser, None, 0, &mut buffer_size)
-            {
-                if e != ERROR_INSUFFICIENT_BUFFER.into() {
-                    let _ = CloseHandle(token_handle);
-                    return Err(e.into());
-                }
-            }
-            if buffer_size == 0 {
-                return Err(WinError::from(E_UNEXPECTED).into());
-            }
-            let layout =
-                Layout::from_size_align(buffer_size as usize, align_of::<TOKEN_USER>()).unwrap();
-            let buf_ptr = alloc_zeroed(layout);
-            if buf_ptr.is_null() {
-                CloseHandle(token_handle)?;
-                return Err(WinError::from(E_OUTOFMEMORY).into());
-            }
-            let ret = if let Err(e) = GetTokenInformation(
-                token_handle,
-                TokenUser,
-                Some(buf_ptr.cast()),
-                buffer_size,
-                &mut buffer_size,
-            ) {
-                Err(e.into())
-            } else {
-                sid_to_string((*buf_ptr.cast::<TOKEN_USER>()).User.Sid)
-            };
-            dealloc(buf_ptr, layout);
+            let ret = token_user(token_handle);
             CloseHandle(token_handle)?;
             ret
         }
     }
+
+    /// Get the identifier of the user an access token belongs to. The token must have been
+    /// opened with the `TOKEN_QUERY` access right.
+    pub fn from_token(token: BorrowedHandle<'_>) -> Result<UserIdentifier, GetHomeError> {
+        unsafe { token_user(HANDLE(token.as_raw_handle() as isize)) }
+    }
 }
 
-impl GetHomeInstance {
-    /// Construct this structure. This connects to the Windows Management Instrumentation.
-    pub fn new() -> Result<Self, GetHomeError> {
-        unsafe {
-            const NAMESPACE_PATH: &str = "ROOT\\CIMV2";
-            cfg_if!(
-                if #[cfg(feature = "windows-coinitialize")] {
-                    let instance_fn = || CoCreateInstance::<_, IWbemLocator>(&WbemLocator, None, CLSCTX_INPROC_SERVER);
-                    let instance = match instance_fn() {
-                        Ok(v) => v,
-                        Err(e) => {
-                            if e != CO_E_NOTINITIALIZED.into() {
-                                return Err(e.into());
-                            }
-                            CoInitializeEx(None, COINIT_MULTITHREADED).ok()?;
-                            instance_fn()?
-                        },
-                    };
-                } else {
-                    let instance = CoCreateInstance::<_, IWbemLocator>(&WbemLocator, None, CLSCTX_INPROC_SERVER)?;
-                }
-            );
-            let nms_path_bstr = BSTR::from(NAMESPACE_PATH);
-            let svc = instance.ConnectServer(
-                &nms_path_bstr,
-                &BSTR::new(),
-                &BSTR::new(),
-                &BSTR::new(),
-                WBEM_FLAG_CONNECT_USE_MAX_WAIT.0,
-                &BSTR::new(),
-                None,
-            )?;
-            CoSetProxyBlanket(
-                &svc,
-                RPC_C_AUTHN_WINNT,
-                RPC_C_AUTHZ_NONE,
-                None,
-                RPC_C_AUTHN_LEVEL_CALL,
-                RPC_C_IMP_LEVEL_IMPERSONATE,
-                None,
-                EOAC_NONE,
-            )?;
-            Ok(Self(svc))
+/// Look up a NUL-terminated username, trying it as an account name, then as a user principal
+/// name, and finally as an Azure AD name if `username_str` is available.
+fn with_username_imp(
+    username: &[u16],
+    username_str: Option<&str>,
+) -> Result<Option<UserIdentifier>, GetHomeError> {
+    if let Some((id, _)) = lookup_account_name(username)? {
+        return Ok(Some(id));
+    }
+    if let Some(sam_name) = upn_to_sam(username) {
+        if let Some((id, _)) = lookup_account_name(&sam_name)? {
+            return Ok(Some(id));
         }
     }
+    match username_str {
+        Some(v) => azure_ad_lookup(v),
+        None => Ok(None),
+    }
+}
 
-    /// Get the home directory of a user given their identifier.
-    pub fn query_home(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
-        unsafe {
-            let query_enum = self.0.ExecQuery(
-                &BSTR::from("WQL"),
-                &BSTR::from(format!(
-                    "SELECT LocalPath FROM Win32_UserProfile WHERE SID = '{}'",
-                    id.0
-                )),
-                WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY,
-                None,
-            )?;
-            let mut ret = [None; 1];
-            let mut ret_count = 0;
-            query_enum
-                .Next(WBEM_INFINITE, &mut ret, &mut ret_count)
-                .ok()?;
-            if ret_count == 0 {
-                return Ok(None);
-            }
-            let [ret] = ret;
-            let ret = ret.ok_or(GetHomeError::NullPointerResult)?;
-            let name = w!("LocalPath");
-            let mut variant = VARIANT::default();
-            let mut vt_type = 0;
-            ret.Get(name, 0, &mut variant, Some(&mut vt_type), None)?;
-            let bstr = BSTR::try_from(&variant)?;
-            Ok(Some(
-                U16Str::from_slice(bstr.as_wide()).to_os_string().into(),
-            ))
+/// Get the identifier of the user an access token belongs to.
+unsafe fn token_user(token_handle: HANDLE) -> Result<UserIdentifier, GetHomeError> {
+    let mut buffer_size = 0;
+    // get the length of the buffer requried for this query.
+    if let Err(e) = GetTokenInformation(token_handle, TokenUser, None, 0, &mut buffer_size) {
+        if e != ERROR_INSUFFICIENT_BUFFER.into() {
+            return Err(e.into());
         }
     }
+    if buffer_size == 0 {
+        return Err(WinError::from(E_UNEXPECTED).into());
+    }
+    let layout = Layout::from_size_align(buffer_size as usize, align_of::<TOKEN_USER>()).unwrap();
+    let buf_ptr = alloc_zeroed(layout);
+    if buf_ptr.is_null() {
+        return Err(WinError::from(E_OUTOFMEMORY).into());
+    }
+    let ret = if let Err(e) = GetTokenInformation(
+        token_handle,
+        TokenUser,
+        Some(buf_ptr.cast()),
+        buffer_size,
+        &mut buffer_size,
+    ) {
+        Err(e.into())
+    } else {
+        sid_to_string((*buf_ptr.cast::<TOKEN_USER>()).User.Sid)
+    };
+    dealloc(buf_ptr, layout);
+    ret
 }
 
-impl From<WinError> for GetHomeError {
-    fn from(value: WinError) -> Self {
-        Self::WindowsError(value)
+impl ProjectedHome {
+    /// The path of the profile directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the user already has a profile. If this is `false`, the path is only where the
+    /// profile is expected to be created.
+    pub fn exists(&self) -> bool {
+        self.exists
+    }
+
+    /// Convert this into the path of the profile directory.
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+}
+
+impl PrimaryGroup {
+    /// The string representation of the group's SID, such as `S-1-5-21-...-513`.
+    pub fn sid(&self) -> &str {
+        &self.sid
+    }
+
+    /// The name of the group, such as `Domain Users`, or `None` if the SID could not be
+    /// resolved.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(AccountName::name)
+    }
+
+    /// The name of the group, including its domain, or `None` if the SID could not be resolved.
+    pub fn account_name(&self) -> Option<&AccountName> {
+        self.name.as_ref()
     }
 }
 
-impl From<Utf16Error> for GetHomeError {
-    fn from(value: Utf16Error) -> Self {
-        Self::Utf16Error(value)
+impl UserInfo {
+    /// The profile directory of the user, or `None` if they do not have a profile.
+    pub fn home(&self) -> Option<&Path> {
+        self.home.as_deref()
+    }
+
+    /// The name of the account, without its domain, or `None` if the identifier does not belong
+    /// to any account.
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    /// The login shell of the user. Windows accounts have no login shell, so this is always
+    /// `None`. It exists for consistency with the other platforms.
+    pub fn shell(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The full name of the user, such as `Jane Doe`, or `None` if it is not set.
+    pub fn full_name(&self) -> Option<&str> {
+        self.full_name.as_deref()
+    }
+}
+
+impl AccountHome {
+    /// The home directory configured for the account, such as `\\server\homes\alice`.
+    pub fn home_dir(&self) -> Option<&Path> {
+        self.home_dir.as_deref()
+    }
+
+    /// The drive letter the home directory is mapped to, such as `H:`.
+    pub fn home_dir_drive(&self) -> Option<&str> {
+        self.home_dir_drive.as_deref()
+    }
+
+    /// The path of the roaming profile configured for the account.
+    pub fn profile_path(&self) -> Option<&Path> {
+        self.profile_path.as_deref()
     }
 }
 
-impl From<ContainsNul<u16>> for GetHomeError {
-    fn from(value: ContainsNul<u16>) -> Self {
-        Self::ContainsNul(value)
+impl From<WinError> for GetHomeError {
+    fn from(value: WinError) -> Self {
+        Self::WindowsError(value)
     }
 }
 
@@ -340,9 +1104,9 @@ impl fmt::Display for GetHomeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::WindowsError(e) => write!(f, "windows error: {e}"),
-            Self::Utf16Error(e) => write!(f, "utf-16 error: {e}"),
-            Self::ContainsNul(e) => write!(f, "str contains NUL: {e}"),
             Self::NullPointerResult => write!(f, "unexpected null pointer result"),
+            Self::Unsupported => write!(f, "the operation is not supported in this context"),
+            Self::InvalidPath => write!(f, "the path is absolute or escapes the home directory"),
         }
     }
 }
@@ -351,9 +1115,7 @@ impl std::error::Error for GetHomeError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::WindowsError(e) => Some(e),
-            Self::Utf16Error(e) => Some(e),
-            Self::ContainsNul(e) => Some(e),
-            Self::NullPointerResult => None,
+            Self::NullPointerResult | Self::Unsupported | Self::InvalidPath => None,
         }
     }
 }
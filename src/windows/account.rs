@@ -0,0 +1,101 @@
+// src/windows/account.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+use std::{convert::Infallible, fmt, str::FromStr};
+
+/// An account name, optionally qualified by the domain it belongs to, as in `DOMAIN\user`.
+///
+/// # Example
+/// ```
+/// use homedir::windows::AccountName;
+///
+/// let name = AccountName::parse("CONTOSO\\alice");
+/// assert_eq!(Some("CONTOSO"), name.domain());
+/// assert_eq!("alice", name.name());
+///
+/// let name = AccountName::parse("Administrator");
+/// assert_eq!(None, name.domain());
+/// assert_eq!("Administrator", name.name());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccountName {
+    domain: Option<String>,
+    name: String,
+}
+
+/// Where to look for an account, see
+/// [`UserIdentifier::with_username_in`](super::UserIdentifier::with_username_in).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum LookupScope {
+    /// Look for the account wherever `LookupAccountNameW` finds it first. This is the behaviour of
+    /// [`UserIdentifier::with_username`](super::UserIdentifier::with_username). For unqualified
+    /// names, this is the well-known accounts, then the local machine, then the primary domain,
+    /// then the trusted domains.
+    #[default]
+    Any,
+    /// Only look for accounts of the local machine.
+    LocalOnly,
+    /// Only look for accounts in the given domain.
+    Domain(String),
+}
+
+impl AccountName {
+    /// Parse an account name. If it contains a backslash, everything before the first backslash
+    /// is the domain. The domain `.` refers to the local machine.
+    pub fn parse(s: &str) -> Self {
+        match s.split_once('\\') {
+            Some((domain, name)) => Self {
+                domain: Some(domain.to_owned()),
+                name: name.to_owned(),
+            },
+            None => Self {
+                domain: None,
+                name: s.to_owned(),
+            },
+        }
+    }
+
+    /// Create an account name from its parts.
+    pub fn new<S: Into<String>>(domain: Option<S>, name: S) -> Self {
+        Self {
+            domain: domain.map(Into::into),
+            name: name.into(),
+        }
+    }
+
+    /// The domain of the account, if the name is qualified.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    /// The name of the account, without the domain.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check whether the domain of this name refers to the local machine, named `computer_name`.
+    pub(super) fn is_local(&self, computer_name: &str) -> bool {
+        self.domain
+            .as_deref()
+            .is_some_and(|d| d == "." || d.eq_ignore_ascii_case(computer_name))
+    }
+}
+
+impl FromStr for AccountName {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(s))
+    }
+}
+
+impl fmt::Display for AccountName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.domain {
+            Some(domain) => write!(f, "{domain}\\{}", self.name),
+            None => f.write_str(&self.name),
+        }
+    }
+}
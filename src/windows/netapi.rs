@@ -0,0 +1,169 @@
+// src/windows/netapi.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Account information obtained through the network management functions, such as
+//! [`NetUserGetInfo`](https://learn.microsoft.com/en-us/windows/win32/api/lmaccess/nf-lmaccess-netusergetinfo).
+//! These query the domain controller for domain accounts, so they see the attributes configured
+//! in Active Directory rather than the local profile.
+
+use std::{ffi::OsString, ptr::null_mut};
+
+use windows::{
+    core::{Error as WinError, PCWSTR, PWSTR},
+    Win32::{
+        Foundation::WIN32_ERROR,
+        NetworkManagement::NetManagement::{
+            NERR_Success, NERR_UserNotFound, NetApiBufferFree, NetGetAnyDCName, NetUserGetInfo,
+            USER_INFO_4,
+        },
+        System::SystemInformation::{ComputerNameNetBIOS, GetComputerNameExW},
+    },
+};
+
+use super::{lookup_account_name, sid_to_string, AccountName, GetHomeError};
+use crate::wide::{from_wide, from_wide_ptr, to_wide};
+
+/// A buffer allocated by the network management functions.
+struct NetBuffer<T>(*mut T);
+
+impl<T> Drop for NetBuffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            NetApiBufferFree(Some(self.0.cast()));
+        }
+    }
+}
+
+/// The information about an account, as returned by `NetUserGetInfo` at level 4.
+pub(super) struct NetUserInfo(NetBuffer<USER_INFO_4>);
+
+impl NetUserInfo {
+    /// Get the information about the account with the given name. If the name does not contain
+    /// a domain, it is resolved with `LookupAccountNameW`, and the domain controller of the
+    /// domain it belongs to is queried.
+    pub(super) fn with_username(username: &str) -> Result<Option<Self>, GetHomeError> {
+        // no account name can contain a NUL character.
+        let Some(wide) = to_wide(username.as_ref()) else {
+            return Ok(None);
+        };
+        let Some((_, domain)) = lookup_account_name(&wide)? else {
+            return Ok(None);
+        };
+        // NetUserGetInfo only accepts the account name, without the domain.
+        let name = to_wide(AccountName::parse(username).name().as_ref()).unwrap();
+        let server = account_server(&domain)?;
+        unsafe {
+            let mut buf = null_mut();
+            let status = NetUserGetInfo(
+                server
+                    .as_ref()
+                    .map_or(PCWSTR::null(), |v| PCWSTR(v.as_ptr())),
+                PCWSTR(name.as_ptr()),
+                4,
+                &mut buf,
+            );
+            if status == NERR_Success {
+                Ok(Some(Self(NetBuffer(buf.cast()))))
+            } else if status == NERR_UserNotFound {
+                Ok(None)
+            } else {
+                Err(WinError::from(WIN32_ERROR(status)).into())
+            }
+        }
+    }
+
+    fn get(&self) -> &USER_INFO_4 {
+        unsafe { &*self.0 .0 }
+    }
+
+    /// The home directory configured for the account. `None` is returned if it is empty.
+    pub(super) fn home_dir(&self) -> Option<OsString> {
+        non_empty(self.get().usri4_home_dir)
+    }
+
+    /// The drive letter assigned to the home directory, such as `H:`. `None` is returned if it
+    /// is empty.
+    pub(super) fn home_dir_drive(&self) -> Option<OsString> {
+        non_empty(self.get().usri4_home_dir_drive)
+    }
+
+    /// The full name of the user. `None` is returned if it is empty.
+    pub(super) fn full_name(&self) -> Option<OsString> {
+        non_empty(self.get().usri4_full_name)
+    }
+
+    /// The string SID of the account's primary group. This is formed from the domain part of
+    /// the account's SID and the relative identifier of the group. `None` is returned if the
+    /// account's SID is not available.
+    pub(super) fn primary_group_sid(&self) -> Result<Option<String>, GetHomeError> {
+        let info = self.get();
+        if info.usri4_user_sid.is_invalid() {
+            return Ok(None);
+        }
+        let user_sid = unsafe { sid_to_string(info.usri4_user_sid)? };
+        let Some((domain_sid, _)) = user_sid.as_ref().rsplit_once('-') else {
+            return Ok(None);
+        };
+        Ok(Some(format!(
+            "{domain_sid}-{}",
+            info.usri4_primary_group_id
+        )))
+    }
+
+    /// The path of the roaming profile configured for the account. `None` is returned if it is
+    /// empty.
+    pub(super) fn profile(&self) -> Option<OsString> {
+        non_empty(self.get().usri4_profile)
+    }
+}
+
+/// Read a string of a `USER_INFO_4` structure. `None` is returned if it is null or empty.
+fn non_empty(s: PWSTR) -> Option<OsString> {
+    if s.is_null() {
+        return None;
+    }
+    let ret = unsafe { from_wide_ptr(s.0) };
+    (!ret.is_empty()).then_some(ret)
+}
+
+/// Get the name of the server to query for an account in `domain`, as a NUL-terminated string.
+/// `None` means the local machine.
+fn account_server(domain: &OsString) -> Result<Option<Vec<u16>>, GetHomeError> {
+    let domain_str = domain.to_string_lossy();
+    // the well-known accounts are always local.
+    if domain.is_empty()
+        || domain_str.eq_ignore_ascii_case("BUILTIN")
+        || domain_str.eq_ignore_ascii_case("NT AUTHORITY")
+        || domain_str.eq_ignore_ascii_case(&computer_name()?.to_string_lossy())
+    {
+        return Ok(None);
+    }
+    let domain = to_wide(domain).unwrap();
+    unsafe {
+        let mut buf = null_mut();
+        let status = NetGetAnyDCName(PCWSTR::null(), PCWSTR(domain.as_ptr()), &mut buf);
+        if status != NERR_Success {
+            return Err(WinError::from(WIN32_ERROR(status)).into());
+        }
+        let buf = NetBuffer(buf.cast::<u16>());
+        let mut ret = to_wide(&from_wide_ptr(buf.0)).unwrap_or_default();
+        if ret.is_empty() {
+            ret.push(0);
+        }
+        Ok(Some(ret))
+    }
+}
+
+/// Get the NetBIOS name of the local machine.
+pub(super) fn computer_name() -> Result<OsString, GetHomeError> {
+    unsafe {
+        let mut size = 0;
+        // this fails with ERROR_MORE_DATA, setting `size` to the required length.
+        let _ = GetComputerNameExW(ComputerNameNetBIOS, PWSTR::null(), &mut size);
+        let mut buf = vec![0; size as usize];
+        GetComputerNameExW(ComputerNameNetBIOS, PWSTR(buf.as_mut_ptr()), &mut size)?;
+        Ok(from_wide(&buf))
+    }
+}
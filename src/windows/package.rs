@@ -0,0 +1,53 @@
+// src/windows/package.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Support for packaged (MSIX or UWP) apps, which may run in an app container that is denied
+//! access to the Windows Management Instrumentation.
+
+use std::{path::PathBuf, sync::OnceLock};
+
+use windows::{
+    core::PWSTR,
+    Win32::{
+        Foundation::{APPMODEL_ERROR_NO_PACKAGE, ERROR_ACCESS_DENIED},
+        Storage::Packaging::Appx::GetCurrentPackageFullName,
+    },
+};
+
+use super::{my_home, GetHomeError, UserIdentifier};
+
+/// Check whether this process is a packaged app, that is, whether it has a package identity,
+/// using
+/// [`GetCurrentPackageFullName`](https://learn.microsoft.com/en-us/windows/win32/api/appmodel/nf-appmodel-getcurrentpackagefullname).
+///
+/// In packaged apps, [`UserIdentifier::to_home`] does not use the Windows Management
+/// Instrumentation. The home directory of the current user is obtained through the package
+/// context, and that of other users from the registry, which fails with
+/// [`GetHomeError::Unsupported`] in an app container. Likewise, [`GetHomeInstance`] fails with
+/// [`GetHomeError::Unsupported`] if it cannot connect.
+///
+/// [`GetHomeInstance`]: super::GetHomeInstance
+pub fn is_packaged() -> bool {
+    static PACKAGED: OnceLock<bool> = OnceLock::new();
+    *PACKAGED.get_or_init(|| {
+        let mut len = 0;
+        // with an empty buffer, this fails with ERROR_INSUFFICIENT_BUFFER if there is a package.
+        unsafe { GetCurrentPackageFullName(&mut len, PWSTR::null()) != APPMODEL_ERROR_NO_PACKAGE }
+    })
+}
+
+/// Get the home directory of a user from a packaged app.
+pub(super) fn packaged_home(id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+    if *id == UserIdentifier::my_id()? {
+        return my_home();
+    }
+    log_debug!("running in a packaged app, reading the profile directory from the registry");
+    match id.to_home_from_registry() {
+        Err(GetHomeError::WindowsError(e)) if e.code() == ERROR_ACCESS_DENIED.to_hresult() => {
+            Err(GetHomeError::Unsupported)
+        }
+        ret => ret,
+    }
+}
@@ -0,0 +1,103 @@
+// src/windows/path_form.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Conversion of paths to and from the extended-length form, such as `\\?\C:\Users\alice`.
+
+use std::{
+    ffi::OsString,
+    os::windows::ffi::{OsStrExt, OsStringExt},
+    path::{Component, Path, PathBuf, Prefix},
+};
+
+/// The maximum length of a path which is not in the extended-length form, including the
+/// terminating NUL character.
+const MAX_PATH: usize = 260;
+
+/// The form in which paths are returned, see [`QueryOptions::path_form`].
+///
+/// Paths in the extended-length form start with `\\?\` (or `\\?\UNC\` for network shares) and
+/// may be longer than `MAX_PATH` (260) characters. The registry and the Windows Management
+/// Instrumentation normally contain paths in the standard form, but may contain either.
+///
+/// [`QueryOptions::path_form`]: super::QueryOptions::path_form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PathForm {
+    /// Return paths as they are stored. This is the default.
+    #[default]
+    Unchanged,
+    /// Remove the `\\?\` prefix from paths in the extended-length form, if the resulting path is
+    /// shorter than `MAX_PATH` characters. Longer paths are left in the extended-length form, as
+    /// most functions cannot open them otherwise.
+    Standard,
+    /// Convert absolute paths to the extended-length form. Forward slashes are replaced by
+    /// backslashes, as they are not separators in this form.
+    ExtendedLength,
+}
+
+impl PathForm {
+    /// Convert a path to this form. Relative paths and paths to devices, such as
+    /// `\\.\COM1`, are returned unchanged.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::windows::PathForm;
+    /// use std::path::PathBuf;
+    ///
+    /// assert_eq!(
+    ///     PathBuf::from(r"\\?\C:\Users\alice"),
+    ///     PathForm::ExtendedLength.apply(r"C:\Users\alice".into())
+    /// );
+    /// assert_eq!(
+    ///     PathBuf::from(r"\\server\share\alice"),
+    ///     PathForm::Standard.apply(r"\\?\UNC\server\share\alice".into())
+    /// );
+    /// ```
+    pub fn apply(self, path: PathBuf) -> PathBuf {
+        let Some(Component::Prefix(prefix)) = Path::new(&path).components().next() else {
+            return path;
+        };
+        match (self, prefix.kind()) {
+            (Self::Standard, Prefix::VerbatimDisk(_)) => strip(path, br"\\?\"),
+            (Self::Standard, Prefix::VerbatimUNC(..)) => strip(path, br"\\?\UNC"),
+            (Self::ExtendedLength, Prefix::Disk(_)) if path.has_root() => {
+                extend(&path, br"\\?\", 0)
+            }
+            (Self::ExtendedLength, Prefix::UNC(..)) => extend(&path, br"\\?\UNC", 1),
+            _ => path,
+        }
+    }
+}
+
+/// Remove a prefix from a path, keeping the rest of it, if the result is short enough to be used
+/// without the extended-length form. For UNC paths, the backslash following `UNC` is kept and
+/// the leading `\` is restored.
+fn strip(path: PathBuf, prefix: &[u8]) -> PathBuf {
+    let wide = path.as_os_str().encode_wide().collect::<Vec<_>>();
+    let mut ret = wide[prefix.len()..].to_vec();
+    if prefix.ends_with(b"UNC") {
+        ret.insert(0, u16::from(b'\\'));
+    }
+    if ret.len() >= MAX_PATH {
+        return path;
+    }
+    OsString::from_wide(&ret).into()
+}
+
+/// Add a prefix to a path, replacing forward slashes with backslashes. `skip` leading characters
+/// of the path are removed, such as the first `\` of a UNC path.
+fn extend(path: &Path, prefix: &[u8], skip: usize) -> PathBuf {
+    let ret = prefix
+        .iter()
+        .map(|&c| u16::from(c))
+        .chain(path.as_os_str().encode_wide().skip(skip).map(|c| {
+            if c == u16::from(b'/') {
+                u16::from(b'\\')
+            } else {
+                c
+            }
+        }))
+        .collect::<Vec<_>>();
+    OsString::from_wide(&ret).into()
+}
@@ -0,0 +1,190 @@
+// src/windows/registry.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! A minimal wrapper around the registry functions, for the values which are not exposed through
+//! the Windows Management Instrumentation.
+
+use std::{
+    ffi::{OsStr, OsString},
+    path::Path,
+};
+
+use windows::{
+    core::{Error as WinError, PCWSTR, PWSTR},
+    Win32::{
+        Foundation::{
+            ERROR_FILE_NOT_FOUND, ERROR_MORE_DATA, ERROR_NO_MORE_ITEMS, ERROR_PATH_NOT_FOUND,
+            ERROR_SHARING_VIOLATION, ERROR_SUCCESS,
+        },
+        System::{
+            Environment::ExpandEnvironmentStringsW,
+            Registry::{
+                RegCloseKey, RegEnumKeyExW, RegGetValueW, RegLoadAppKeyW, RegOpenKeyExW, HKEY,
+                KEY_READ, REG_ROUTINE_FLAGS, RRF_NOEXPAND, RRF_RT_REG_EXPAND_SZ, RRF_RT_REG_SZ,
+            },
+        },
+    },
+};
+
+use super::GetHomeError;
+use crate::wide::{from_wide, to_wide};
+
+pub(super) use windows::Win32::System::Registry::{HKEY_LOCAL_MACHINE, HKEY_USERS};
+
+/// The path of the key under `HKEY_LOCAL_MACHINE` which contains a subkey for each profile.
+pub(super) const PROFILE_LIST: &str =
+    "SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion\\ProfileList";
+
+/// An open registry key, closed when dropped.
+pub(super) struct RegKey(HKEY);
+
+impl RegKey {
+    /// Open a subkey of `parent` for reading. `None` is returned if it does not exist.
+    pub(super) fn open(parent: HKEY, path: &str) -> Result<Option<Self>, GetHomeError> {
+        let Some(path) = to_wide(path.as_ref()) else {
+            return Ok(None);
+        };
+        let mut key = HKEY::default();
+        let status = unsafe { RegOpenKeyExW(parent, PCWSTR(path.as_ptr()), 0, KEY_READ, &mut key) };
+        if status == ERROR_FILE_NOT_FOUND {
+            Ok(None)
+        } else {
+            status.ok()?;
+            Ok(Some(Self(key)))
+        }
+    }
+
+    /// Get the names of the subkeys of this key.
+    pub(super) fn subkeys(&self) -> Result<Vec<OsString>, GetHomeError> {
+        let mut ret = Vec::new();
+        // key names are limited to 255 characters.
+        let mut buf = [0u16; 256];
+        loop {
+            let mut len = buf.len() as u32;
+            let status = unsafe {
+                RegEnumKeyExW(
+                    self.0,
+                    ret.len() as u32,
+                    PWSTR(buf.as_mut_ptr()),
+                    &mut len,
+                    None,
+                    PWSTR::null(),
+                    None,
+                    None,
+                )
+            };
+            if status == ERROR_NO_MORE_ITEMS {
+                return Ok(ret);
+            }
+            status.ok()?;
+            ret.push(from_wide(&buf[..len as usize]));
+        }
+    }
+
+    /// Load a registry hive file, such as the `NTUSER.DAT` file of a profile, with
+    /// `RegLoadAppKeyW`. `None` is returned if the file does not exist or is already loaded by
+    /// another process (the hive of a user who is logged on is loaded under `HKEY_USERS`).
+    pub(super) fn load_app_key(file: &Path) -> Result<Option<Self>, GetHomeError> {
+        let Some(file) = to_wide(file.as_os_str()) else {
+            return Ok(None);
+        };
+        let mut key = HKEY::default();
+        let status = unsafe { RegLoadAppKeyW(PCWSTR(file.as_ptr()), &mut key, KEY_READ.0, 0, 0) };
+        if status == ERROR_FILE_NOT_FOUND
+            || status == ERROR_PATH_NOT_FOUND
+            || status == ERROR_SHARING_VIOLATION
+        {
+            Ok(None)
+        } else {
+            status.ok()?;
+            Ok(Some(Self(key)))
+        }
+    }
+
+    /// Open a subkey of this key for reading. `None` is returned if it does not exist.
+    pub(super) fn subkey(&self, path: &str) -> Result<Option<Self>, GetHomeError> {
+        Self::open(self.0, path)
+    }
+
+    /// Read a string value of this key. Values of type `REG_EXPAND_SZ` are expanded. `None` is
+    /// returned if the value does not exist.
+    pub(super) fn get_string(&self, value: &str) -> Result<Option<OsString>, GetHomeError> {
+        // RRF_RT_REG_SZ also accepts REG_EXPAND_SZ values, which are expanded.
+        self.get_string_with(value, RRF_RT_REG_SZ)
+    }
+
+    /// Read a string value of this key, without expanding the environment variables in values
+    /// of type `REG_EXPAND_SZ`. `None` is returned if the value does not exist.
+    pub(super) fn get_string_unexpanded(
+        &self,
+        value: &str,
+    ) -> Result<Option<OsString>, GetHomeError> {
+        self.get_string_with(value, RRF_RT_REG_SZ | RRF_RT_REG_EXPAND_SZ | RRF_NOEXPAND)
+    }
+
+    fn get_string_with(
+        &self,
+        value: &str,
+        flags: REG_ROUTINE_FLAGS,
+    ) -> Result<Option<OsString>, GetHomeError> {
+        let Some(value) = to_wide(value.as_ref()) else {
+            return Ok(None);
+        };
+        let mut size = 0;
+        loop {
+            let mut buf = vec![0u16; (size as usize).div_ceil(2)];
+            let ptr = if buf.is_empty() {
+                None
+            } else {
+                Some(buf.as_mut_ptr().cast())
+            };
+            let status = unsafe {
+                RegGetValueW(
+                    self.0,
+                    PCWSTR::null(),
+                    PCWSTR(value.as_ptr()),
+                    flags,
+                    None,
+                    ptr,
+                    Some(&mut size),
+                )
+            };
+            if status == ERROR_SUCCESS && !buf.is_empty() {
+                return Ok(Some(from_wide(&buf)));
+            } else if status == ERROR_FILE_NOT_FOUND {
+                return Ok(None);
+            } else if status != ERROR_SUCCESS && status != ERROR_MORE_DATA {
+                return Err(WinError::from(status).into());
+            }
+        }
+    }
+}
+
+/// Expand the environment variables in a string, such as `%SystemDrive%\Users`, using
+/// `ExpandEnvironmentStringsW`. Variables which are not set are left as they are.
+pub(super) fn expand_environment_strings(s: &OsStr) -> Result<OsString, GetHomeError> {
+    let Some(src) = to_wide(s) else {
+        return Ok(s.to_owned());
+    };
+    unsafe {
+        let size = ExpandEnvironmentStringsW(PCWSTR(src.as_ptr()), None);
+        if size == 0 {
+            return Err(WinError::from_win32().into());
+        }
+        let mut buf = vec![0; size as usize];
+        if ExpandEnvironmentStringsW(PCWSTR(src.as_ptr()), Some(&mut buf)) == 0 {
+            return Err(WinError::from_win32().into());
+        }
+        Ok(from_wide(&buf))
+    }
+}
+
+impl Drop for RegKey {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = RegCloseKey(self.0);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// src/windows/shared.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The process-wide [`GetHomeInstance`] used by [`UserIdentifier::to_home`] and [`find_users`],
+//! so that repeated lookups do not connect to the Windows Management Instrumentation every time.
+//!
+//! [`UserIdentifier::to_home`]: super::UserIdentifier::to_home
+//! [`find_users`]: super::find_users
+
+use std::{
+    mem,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+use windows::{
+    core::HRESULT,
+    Win32::{
+        Foundation::RPC_E_DISCONNECTED,
+        System::{
+            Com::{
+                CoGetApartmentType, CoInitializeEx, CoUninitialize, APTTYPE, APTTYPEQUALIFIER,
+                APTTYPE_MTA, COINIT_MULTITHREADED,
+            },
+            Rpc::RPC_S_SERVER_UNAVAILABLE,
+            Wmi::WBEM_E_TRANSPORT_FAILURE,
+        },
+    },
+};
+
+use super::{GetHomeError, GetHomeInstance};
+
+/// The shared instance, connected on first use.
+static SHARED: Mutex<Option<Arc<Shared>>> = Mutex::new(None);
+
+/// A [`GetHomeInstance`] which can be stored in a static.
+struct Shared(GetHomeInstance);
+
+// SAFETY: the instance is only used and released by threads in the multithreaded apartment, in
+// which the proxies of the Windows Management Instrumentation can be called from any thread. See
+// `with_shared` and `reset`.
+unsafe impl Send for Shared {}
+unsafe impl Sync for Shared {}
+
+/// Run a query with the shared instance, connecting it if necessary. Threads which are not in
+/// the multithreaded apartment, such as those which initialized the COM library for a user
+/// interface, use a new instance instead. If the query fails because the connection was lost,
+/// the shared instance is dropped, and the next query connects again.
+pub(super) fn with_shared<T>(
+    f: impl FnOnce(&GetHomeInstance) -> Result<T, GetHomeError>,
+) -> Result<T, GetHomeError> {
+    if !in_mta() {
+        log_debug!("not in the multithreaded apartment, connecting to WMI for this lookup");
+        return f(&GetHomeInstance::new()?);
+    }
+    let instance = {
+        // the lock is held while connecting, so that concurrent first lookups connect once.
+        let mut shared = SHARED.lock().unwrap_or_else(PoisonError::into_inner);
+        match &*shared {
+            Some(v) => Arc::clone(v),
+            None => {
+                let v = Arc::new(Shared(GetHomeInstance::new()?));
+                *shared = Some(Arc::clone(&v));
+                v
+            }
+        }
+    };
+    let ret = f(&instance.0);
+    match &ret {
+        Err(GetHomeError::WindowsError(e)) if is_disconnected(e.code()) => {
+            log_warn!("the connection to WMI was lost, reconnecting on the next lookup: {e}");
+            reset();
+        }
+        _ => {}
+    }
+    ret
+}
+
+/// Drop the shared instance, so that the next query connects again.
+///
+/// The proxies of the instance belong to the multithreaded apartment, so they must not be
+/// released from any other thread. If this thread is not in it, the instance is released by a
+/// new thread which joins it instead.
+pub(super) fn reset() {
+    let Some(instance) = SHARED.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+        return;
+    };
+    if in_mta() {
+        drop(instance);
+        return;
+    }
+    thread::spawn(move || unsafe {
+        if CoInitializeEx(None, COINIT_MULTITHREADED).is_ok() {
+            drop(instance);
+            CoUninitialize();
+        } else {
+            // releasing the proxies outside of their apartment is undefined, so leak them.
+            mem::forget(instance);
+        }
+    });
+}
+
+/// Check whether an error means that the connection to the Windows Management Instrumentation
+/// was lost, rather than that a single query failed, for instance because it timed out.
+fn is_disconnected(code: HRESULT) -> bool {
+    code == RPC_E_DISCONNECTED
+        || code == HRESULT(WBEM_E_TRANSPORT_FAILURE.0)
+        || code == HRESULT::from_win32(RPC_S_SERVER_UNAVAILABLE.0 as u32)
+}
+
+/// Check whether the current thread is in the multithreaded apartment, including implicitly.
+/// This is not the case before the COM library is first initialized by this crate.
+fn in_mta() -> bool {
+    let mut apt_type = APTTYPE::default();
+    let mut qualifier = APTTYPEQUALIFIER::default();
+    unsafe { CoGetApartmentType(&mut apt_type, &mut qualifier) }.is_ok() && apt_type == APTTYPE_MTA
+}
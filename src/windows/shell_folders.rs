@@ -0,0 +1,133 @@
+// src/windows/shell_folders.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The known folders of arbitrary users, read from the `User Shell Folders` key of their
+//! registry hive. `SHGetKnownFolderPath` can only do this given an access token for the user,
+//! which is generally not available.
+
+use std::path::{Path, PathBuf};
+
+use super::{
+    registry::{expand_environment_strings, RegKey, HKEY_USERS},
+    GetHomeError, UserIdentifier,
+};
+
+/// The path of the key, relative to the root of a user's hive, which contains the locations of
+/// their known folders.
+const USER_SHELL_FOLDERS: &str =
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\User Shell Folders";
+
+/// A folder inside a user's profile, whose location can be redirected by the user or by group
+/// policy. See [`UserIdentifier::known_folder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KnownFolder {
+    /// The user's desktop, `FOLDERID_Desktop`.
+    Desktop,
+    /// The user's documents, `FOLDERID_Documents`.
+    Documents,
+    /// The user's downloads, `FOLDERID_Downloads`.
+    Downloads,
+    /// The user's music, `FOLDERID_Music`.
+    Music,
+    /// The user's pictures, `FOLDERID_Pictures`.
+    Pictures,
+    /// The user's videos, `FOLDERID_Videos`.
+    Videos,
+    /// The user's favorites, `FOLDERID_Favorites`.
+    Favorites,
+    /// The user's roaming application data, `FOLDERID_RoamingAppData`.
+    RoamingAppData,
+    /// The user's local application data, `FOLDERID_LocalAppData`.
+    LocalAppData,
+    /// The user's start menu, `FOLDERID_StartMenu`.
+    StartMenu,
+    /// The user's templates, `FOLDERID_Templates`.
+    Templates,
+}
+
+impl KnownFolder {
+    /// The name of the value in the `User Shell Folders` key which contains the location of this
+    /// folder.
+    fn value_name(self) -> &'static str {
+        match self {
+            Self::Desktop => "Desktop",
+            Self::Documents => "Personal",
+            Self::Downloads => "{374DE290-123F-4565-9164-39C4925E467B}",
+            Self::Music => "My Music",
+            Self::Pictures => "My Pictures",
+            Self::Videos => "My Video",
+            Self::Favorites => "Favorites",
+            Self::RoamingAppData => "AppData",
+            Self::LocalAppData => "Local AppData",
+            Self::StartMenu => "Start Menu",
+            Self::Templates => "Templates",
+        }
+    }
+
+    /// The default location of this folder, relative to the profile directory.
+    fn default_path(self) -> &'static str {
+        match self {
+            Self::Desktop => "Desktop",
+            Self::Documents => "Documents",
+            Self::Downloads => "Downloads",
+            Self::Music => "Music",
+            Self::Pictures => "Pictures",
+            Self::Videos => "Videos",
+            Self::Favorites => "Favorites",
+            Self::RoamingAppData => "AppData\\Roaming",
+            Self::LocalAppData => "AppData\\Local",
+            Self::StartMenu => "AppData\\Roaming\\Microsoft\\Windows\\Start Menu",
+            Self::Templates => "AppData\\Roaming\\Microsoft\\Windows\\Templates",
+        }
+    }
+}
+
+/// Get the location of a known folder of the user with identifier `id`, whose profile
+/// directory is `profile`.
+///
+/// If the user is logged on, their hive is loaded under `HKEY_USERS`. Otherwise, the
+/// `NTUSER.DAT` file in their profile directory is loaded. If neither is possible, or if the
+/// folder was never redirected, its default location is returned.
+pub(super) fn user_shell_folder(
+    id: &UserIdentifier,
+    profile: &Path,
+    folder: KnownFolder,
+) -> Result<PathBuf, GetHomeError> {
+    let hive = match RegKey::open(HKEY_USERS, &id.0)? {
+        Some(v) => Some(v),
+        None => RegKey::load_app_key(&profile.join("NTUSER.DAT"))?,
+    };
+    let value = match hive
+        .map(|v| v.subkey(USER_SHELL_FOLDERS))
+        .transpose()?
+        .flatten()
+    {
+        Some(key) => key.get_string_unexpanded(folder.value_name())?,
+        None => None,
+    };
+    let Some(value) = value else {
+        return Ok(profile.join(folder.default_path()));
+    };
+    // the values refer to the profile directory with %USERPROFILE%, which must be replaced
+    // before expanding the other variables, as it would otherwise refer to this process' user.
+    let value = replace_user_profile(&value.to_string_lossy(), profile);
+    Ok(expand_environment_strings(value.as_ref())?.into())
+}
+
+/// Replace every occurrence of `%USERPROFILE%` (ignoring case) in `value` with `profile`.
+fn replace_user_profile(value: &str, profile: &Path) -> String {
+    const VAR: &str = "%userprofile%";
+    let lower = value.to_ascii_lowercase();
+    let mut ret = String::with_capacity(value.len());
+    let mut last = 0;
+    for (i, _) in lower.match_indices(VAR) {
+        ret.push_str(&value[last..i]);
+        ret.push_str(&profile.to_string_lossy());
+        last = i + VAR.len();
+    }
+    ret.push_str(&value[last..]);
+    ret
+}
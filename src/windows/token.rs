@@ -0,0 +1,193 @@
+// src/windows/token.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Functions which operate on the access token of a user, rather than on their username or
+//! identifier. These are mostly useful to services which impersonate users.
+
+use std::{
+    os::windows::io::{AsHandle, AsRawHandle, BorrowedHandle, FromRawHandle, OwnedHandle},
+    path::{Path, PathBuf},
+};
+
+use windows::{
+    core::{Error as WinError, GUID, PWSTR},
+    Win32::{
+        Foundation::{E_UNEXPECTED, HANDLE},
+        Security::{TOKEN_IMPERSONATE, TOKEN_QUERY},
+        System::{
+            Com::CoTaskMemFree,
+            Threading::{OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION},
+        },
+        UI::{
+            Shell::{
+                FOLDERID_Profile, GetUserProfileDirectoryW, LoadUserProfileW, SHGetKnownFolderPath,
+                UnloadUserProfile, KNOWN_FOLDER_FLAG, PROFILEINFOW,
+            },
+            WindowsAndMessaging::{GetShellWindow, GetWindowThreadProcessId},
+        },
+    },
+};
+
+use super::{GetHomeError, UserIdentifier};
+use crate::wide::{from_wide, from_wide_ptr, to_wide};
+
+/// Prevents `LoadUserProfileW` from displaying error messages.
+const PI_NOUI: u32 = 1;
+
+/// A user profile loaded with [`load_user_profile`]. The profile is unloaded when this is
+/// dropped.
+pub struct LoadedProfile<'a> {
+    token: BorrowedHandle<'a>,
+    profile: HANDLE,
+    path: PathBuf,
+}
+
+fn raw(token: BorrowedHandle<'_>) -> HANDLE {
+    HANDLE(token.as_raw_handle() as isize)
+}
+
+/// Get the location of a known folder of the user an access token belongs to, using
+/// [`SHGetKnownFolderPath`](https://learn.microsoft.com/en-us/windows/win32/api/shlobj_core/nf-shlobj_core-shgetknownfolderpath).
+/// `folder` is one of the `FOLDERID_*` constants from the
+/// [`windows`](https://crates.io/crates/windows) crate.
+///
+/// The token must have been opened with the `TOKEN_QUERY` and `TOKEN_IMPERSONATE` access
+/// rights, and the user's profile must be loaded, for instance with [`load_user_profile`].
+///
+/// # Example
+/// ```no_run
+/// use std::os::windows::io::BorrowedHandle;
+/// use homedir::windows::known_folder_for_token;
+/// use windows::Win32::UI::Shell::FOLDERID_Documents;
+///
+/// # fn example(token: BorrowedHandle<'_>) -> Result<(), homedir::windows::GetHomeError> {
+/// println!("{:?}", known_folder_for_token(&FOLDERID_Documents, token)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn known_folder_for_token(
+    folder: &GUID,
+    token: BorrowedHandle<'_>,
+) -> Result<PathBuf, GetHomeError> {
+    unsafe {
+        let out = SHGetKnownFolderPath(folder, KNOWN_FOLDER_FLAG(0), raw(token))?.0;
+        // there isn't any documented case where this will occur, but who knows.
+        if out.is_null() {
+            return Err(WinError::from(E_UNEXPECTED).into());
+        }
+        let ret = from_wide_ptr(out).into();
+        CoTaskMemFree(Some(out.cast()));
+        Ok(ret)
+    }
+}
+
+/// Get the profile directory of the user an access token belongs to. This is
+/// [`known_folder_for_token`] with `FOLDERID_Profile`, and it has the same requirements.
+pub fn home_for_token(token: BorrowedHandle<'_>) -> Result<PathBuf, GetHomeError> {
+    known_folder_for_token(&FOLDERID_Profile, token)
+}
+
+/// Get the home directory of the user of the interactive desktop session, that is, of the user
+/// who owns the shell (usually Explorer). If there is no shell, as in a service or in session 0,
+/// `Ok(None)` is returned.
+///
+/// Elevation by User Account Control does not change the profile of a process: when an
+/// administrator approves it, the elevated token belongs to the same user, so
+/// [`my_home`](super::my_home) already returns the desktop user's home directory. However, when
+/// a standard user elevates a process with the credentials of a different administrator
+/// account ("over-the-shoulder" elevation), [`my_home`](super::my_home) returns the profile of
+/// that administrator, whereas this function still returns the profile of the desktop user.
+///
+/// The shell's token is opened with the `TOKEN_QUERY` and `TOKEN_IMPERSONATE` access rights,
+/// which fails with `E_ACCESSDENIED` if this process is not allowed to do so.
+pub fn desktop_user_home() -> Result<Option<PathBuf>, GetHomeError> {
+    unsafe {
+        let window = GetShellWindow();
+        if window.0 == 0 {
+            return Ok(None);
+        }
+        let mut pid = 0;
+        if GetWindowThreadProcessId(window, Some(&mut pid)) == 0 {
+            return Err(WinError::from_win32().into());
+        }
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)?;
+        let process = OwnedHandle::from_raw_handle(process.0 as _);
+        let mut token = HANDLE(0);
+        OpenProcessToken(
+            raw(process.as_handle()),
+            TOKEN_QUERY | TOKEN_IMPERSONATE,
+            &mut token,
+        )?;
+        let token = OwnedHandle::from_raw_handle(token.0 as _);
+        // the desktop user is logged on, so their profile is loaded.
+        home_for_token(token.as_handle()).map(Some)
+    }
+}
+
+/// Load the profile of the user an access token belongs to, using
+/// [`LoadUserProfileW`](https://learn.microsoft.com/en-us/windows/win32/api/userenv/nf-userenv-loaduserprofilew).
+/// If the user has never logged on, this creates their profile, so the returned path is the
+/// user's actual profile directory rather than a prediction like
+/// [`projected_home`](super::projected_home).
+///
+/// The token must have been opened with the `TOKEN_QUERY`, `TOKEN_IMPERSONATE`, and
+/// `TOKEN_DUPLICATE` access rights, for instance as returned by `LogonUserW`. As well, the
+/// calling process must have the `SE_BACKUP_NAME` and `SE_RESTORE_NAME` privileges, which
+/// generally means it must be running as an administrator or as `LocalSystem`.
+///
+/// The profile remains loaded until the returned [`LoadedProfile`] is dropped.
+pub fn load_user_profile(token: BorrowedHandle<'_>) -> Result<LoadedProfile<'_>, GetHomeError> {
+    let id = UserIdentifier::from_token(token)?;
+    let name = id
+        .account_name()?
+        .ok_or_else(|| WinError::from(E_UNEXPECTED))?;
+    // no account name can contain a NUL character.
+    let mut name = to_wide(name.name().as_ref()).ok_or_else(|| WinError::from(E_UNEXPECTED))?;
+    unsafe {
+        let mut info = PROFILEINFOW {
+            dwSize: std::mem::size_of::<PROFILEINFOW>() as u32,
+            dwFlags: PI_NOUI,
+            lpUserName: PWSTR(name.as_mut_ptr()),
+            ..Default::default()
+        };
+        LoadUserProfileW(raw(token), &mut info)?;
+        // if getting the path fails, the profile is unloaded when this is dropped.
+        let mut ret = LoadedProfile {
+            token,
+            profile: info.hProfile,
+            path: PathBuf::new(),
+        };
+        ret.path = profile_directory(token)?;
+        Ok(ret)
+    }
+}
+
+/// Get the profile directory of the user an access token belongs to, using
+/// `GetUserProfileDirectoryW`.
+fn profile_directory(token: BorrowedHandle<'_>) -> Result<PathBuf, GetHomeError> {
+    unsafe {
+        let mut size = 0;
+        // this fails with ERROR_INSUFFICIENT_BUFFER, setting `size` to the required length.
+        let _ = GetUserProfileDirectoryW(raw(token), PWSTR::null(), &mut size);
+        let mut buf = vec![0; size as usize];
+        GetUserProfileDirectoryW(raw(token), PWSTR(buf.as_mut_ptr()), &mut size)?;
+        Ok(from_wide(&buf).into())
+    }
+}
+
+impl LoadedProfile<'_> {
+    /// The path of the loaded profile.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for LoadedProfile<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = UnloadUserProfile(raw(self.token), self.profile);
+        }
+    }
+}
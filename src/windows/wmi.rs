@@ -0,0 +1,677 @@
+// src/windows/wmi.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! The queries to the Windows Management Instrumentation, see [`GetHomeInstance`]. This module
+//! is only compiled with the `windows-wmi` feature.
+
+use std::{
+    collections::HashMap,
+    ffi::{OsStr, OsString},
+    mem,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use cfg_if::cfg_if;
+use windows::{
+    core::{w, Error as WinError, BSTR, HRESULT, PCWSTR, VARIANT},
+    Win32::System::{
+        Com::{
+            CoCreateInstance, CoSetProxyBlanket, CLSCTX_INPROC_SERVER, EOAC_NONE,
+            RPC_C_AUTHN_LEVEL_CALL, RPC_C_IMP_LEVEL_IMPERSONATE,
+        },
+        Rpc::{RPC_C_AUTHN_WINNT, RPC_C_AUTHZ_NONE},
+        Wmi::{
+            IEnumWbemClassObject, IWbemClassObject, IWbemLocator, IWbemServices, WbemLocator,
+            WBEM_E_TIMED_OUT, WBEM_FLAG_CONNECT_USE_MAX_WAIT, WBEM_FLAG_FORWARD_ONLY,
+            WBEM_FLAG_RETURN_IMMEDIATELY, WBEM_INFINITE, WBEM_S_TIMEDOUT,
+        },
+    },
+};
+
+#[cfg(feature = "windows-coinitialize")]
+use windows::Win32::{
+    Foundation::CO_E_NOTINITIALIZED,
+    System::Com::{CoInitializeEx, COINIT_MULTITHREADED},
+};
+
+use super::{
+    is_packaged, shared, shell_folders, GetHomeError, KnownFolder, PathForm, UserIdentifier,
+    UserInfo,
+};
+use crate::wide::from_wide_into;
+
+/// This structure caches the results of the operations necessary to check the profile
+/// directory from an SID, see [`GetHomeInstance::query_home`]. This way, multiple
+/// queries can be performed at a smaller cost.
+pub struct GetHomeInstance {
+    svc: IWbemServices,
+    options: QueryOptions,
+}
+
+/// The options used by a [`GetHomeInstance`] to query the Windows Management Instrumentation.
+///
+/// By default, queries are semisynchronous, never time out, use the default locale, and return
+/// paths as they are stored. If the WMI service may be unresponsive, a timeout should be set.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use homedir::windows::{GetHomeInstance, QueryOptions};
+///
+/// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+/// let options = QueryOptions::new().timeout(Duration::from_secs(5));
+/// let instance = GetHomeInstance::with_options(options)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryOptions {
+    timeout: Option<Duration>,
+    semisynchronous: bool,
+    locale: Option<String>,
+    path_form: PathForm,
+}
+
+/// The information about a user's profile, as returned by [`GetHomeInstance::query_profile`].
+///
+/// This is obtained from the
+/// [`Win32_UserProfile`](https://learn.microsoft.com/en-us/previous-versions/windows/desktop/legacy/ee886409(v=vs.85))
+/// class.
+#[derive(Debug, Clone)]
+pub struct ProfileInfo {
+    local_path: PathBuf,
+    roaming_path: Option<PathBuf>,
+    roaming_configured: bool,
+    loaded: bool,
+    last_use_time: Option<SystemTime>,
+    status: u32,
+    special: bool,
+}
+
+/// Connect to the `ROOT\\CIMV2` namespace of the Windows Management Instrumentation.
+unsafe fn connect_wmi(options: &QueryOptions) -> Result<IWbemServices, GetHomeError> {
+    const NAMESPACE_PATH: &str = "ROOT\\CIMV2";
+    cfg_if!(
+        if #[cfg(feature = "windows-coinitialize")] {
+            let instance_fn = || CoCreateInstance::<_, IWbemLocator>(&WbemLocator, None, CLSCTX_INPROC_SERVER);
+            let instance = match instance_fn() {
+                Ok(v) => v,
+                Err(e) => {
+                    if e != CO_E_NOTINITIALIZED.into() {
+                        return Err(e.into());
+                    }
+                    CoInitializeEx(None, COINIT_MULTITHREADED).ok()?;
+                    instance_fn()?
+                },
+            };
+        } else {
+            let instance = CoCreateInstance::<_, IWbemLocator>(&WbemLocator, None, CLSCTX_INPROC_SERVER)?;
+        }
+    );
+    let nms_path_bstr = BSTR::from(NAMESPACE_PATH);
+    let locale = options.locale.as_deref().map_or_else(BSTR::new, BSTR::from);
+    let svc = instance.ConnectServer(
+        &nms_path_bstr,
+        &BSTR::new(),
+        &BSTR::new(),
+        &locale,
+        WBEM_FLAG_CONNECT_USE_MAX_WAIT.0,
+        &BSTR::new(),
+        None,
+    )?;
+    CoSetProxyBlanket(
+        &svc,
+        RPC_C_AUTHN_WINNT,
+        RPC_C_AUTHZ_NONE,
+        None,
+        RPC_C_AUTHN_LEVEL_CALL,
+        RPC_C_IMP_LEVEL_IMPERSONATE,
+        None,
+        EOAC_NONE,
+    )?;
+    Ok(svc)
+}
+
+impl GetHomeInstance {
+    /// Construct this structure. This connects to the Windows Management Instrumentation.
+    pub fn new() -> Result<Self, GetHomeError> {
+        Self::with_options(QueryOptions::new())
+    }
+
+    /// Drop the instance shared by [`home`](super::home), [`UserIdentifier::to_home`], and
+    /// [`find_users`](super::find_users), so that the next lookup connects to the Windows
+    /// Management Instrumentation again.
+    ///
+    /// The shared instance is connected on first use, and reused by all the threads in the
+    /// multithreaded apartment. Threads in a single-threaded apartment connect for every lookup.
+    /// It is dropped automatically when the connection is lost, so this is only necessary to
+    /// release the connection, or before calling `CoUninitialize`. If the calling thread is not in
+    /// the multithreaded apartment, the connection is released by another thread which is.
+    pub fn reset_shared() {
+        shared::reset();
+    }
+
+    /// Construct this structure with the given options. This connects to the Windows Management
+    /// Instrumentation. If the connection fails in a packaged app, [`GetHomeError::Unsupported`]
+    /// is returned, as app containers are denied access to it.
+    pub fn with_options(options: QueryOptions) -> Result<Self, GetHomeError> {
+        match unsafe { connect_wmi(&options) } {
+            Ok(svc) => Ok(Self { svc, options }),
+            Err(_) if is_packaged() => Err(GetHomeError::Unsupported),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Construct this structure from an existing connection to the `ROOT\\CIMV2` namespace of
+    /// the Windows Management Instrumentation, for instance one opened with the
+    /// [`wmi`](https://crates.io/crates/wmi) crate. This neither initializes the COM library nor
+    /// opens a second connection. The default [`QueryOptions`] are used, see
+    /// [`from_services_with_options`](GetHomeInstance::from_services_with_options).
+    pub fn from_services(svc: IWbemServices) -> Self {
+        Self::from_services_with_options(svc, QueryOptions::new())
+    }
+
+    /// Construct this structure from an existing connection, as with
+    /// [`from_services`](GetHomeInstance::from_services), with the given options. As the locale
+    /// is a property of the connection, it is ignored.
+    pub fn from_services_with_options(svc: IWbemServices, options: QueryOptions) -> Self {
+        Self { svc, options }
+    }
+
+    /// The options this instance was constructed with.
+    pub fn options(&self) -> &QueryOptions {
+        &self.options
+    }
+
+    /// Get the home directory of a user given their identifier.
+    pub fn query_home(&self, id: &UserIdentifier) -> Result<Option<PathBuf>, GetHomeError> {
+        let query = format!(
+            "SELECT LocalPath FROM Win32_UserProfile WHERE SID = '{}'",
+            id.0
+        );
+        let Some(obj) = self.exec_query(&query)?.next().transpose()? else {
+            return Ok(None);
+        };
+        Ok(get_string(&obj, w!("LocalPath"))?.map(|v| self.options.path_form.apply(v.into())))
+    }
+
+    /// Get the home directory of a user given their identifier, writing it into `buf`. If the user
+    /// has a profile, `Ok(true)` is returned. Otherwise, `buf` is cleared and `Ok(false)` is
+    /// returned. With the default [`PathForm`], the path is decoded directly into `buf`, reusing
+    /// its allocation.
+    pub fn query_home_into(
+        &self,
+        id: &UserIdentifier,
+        buf: &mut PathBuf,
+    ) -> Result<bool, GetHomeError> {
+        let query = format!(
+            "SELECT LocalPath FROM Win32_UserProfile WHERE SID = '{}'",
+            id.0
+        );
+        let found = match self.exec_query(&query)?.next().transpose()? {
+            Some(obj) => get_string_into(&obj, w!("LocalPath"), buf.as_mut_os_string())?,
+            None => false,
+        };
+        if !found {
+            buf.as_mut_os_string().clear();
+        } else if self.options.path_form != PathForm::Unchanged {
+            *buf = self.options.path_form.apply(mem::take(buf));
+        }
+        Ok(found)
+    }
+
+    /// Get the information about the profile of a user given their identifier. If the user does
+    /// not have a profile, `Ok(None)` is returned.
+    pub fn query_profile(&self, id: &UserIdentifier) -> Result<Option<ProfileInfo>, GetHomeError> {
+        let query = format!(
+            "SELECT LocalPath, RoamingPath, RoamingConfigured, Loaded, LastUseTime, Status, \
+            Special FROM Win32_UserProfile WHERE SID = '{}'",
+            id.0
+        );
+        let Some(obj) = self.exec_query(&query)?.next().transpose()? else {
+            return Ok(None);
+        };
+        let Some(local_path) = get_string(&obj, w!("LocalPath"))? else {
+            return Ok(None);
+        };
+        Ok(Some(ProfileInfo {
+            local_path: self.options.path_form.apply(local_path.into()),
+            roaming_path: get_string(&obj, w!("RoamingPath"))?
+                .map(|v| self.options.path_form.apply(v.into())),
+            roaming_configured: get_bool(&obj, w!("RoamingConfigured"))?.unwrap_or(false),
+            loaded: get_bool(&obj, w!("Loaded"))?.unwrap_or(false),
+            last_use_time: get_string(&obj, w!("LastUseTime"))?
+                .as_deref()
+                .and_then(OsStr::to_str)
+                .and_then(parse_cim_datetime),
+            status: get_u32(&obj, w!("Status"))?.unwrap_or(0),
+            special: get_bool(&obj, w!("Special"))?.unwrap_or(false),
+        }))
+    }
+
+    /// Get the location of a known folder of a user given their identifier. This is identical
+    /// to [`UserIdentifier::known_folder`], except it uses this instance to query the profile
+    /// directory.
+    pub fn query_known_folder(
+        &self,
+        id: &UserIdentifier,
+        folder: KnownFolder,
+    ) -> Result<Option<PathBuf>, GetHomeError> {
+        let Some(profile) = self.query_home(id)? else {
+            return Ok(None);
+        };
+        shell_folders::user_shell_folder(id, &profile, folder).map(Some)
+    }
+
+    /// Get the home directories of multiple users given their identifiers.
+    ///
+    /// Rather than performing one query per user as [`GetHomeInstance::query_home`] would, this
+    /// function performs a single query for every [`QUERY_MANY_CHUNK`] identifiers. Users which
+    /// do not have a profile are absent from the returned map.
+    pub fn query_many(
+        &self,
+        ids: &[UserIdentifier],
+    ) -> Result<HashMap<UserIdentifier, PathBuf>, GetHomeError> {
+        let mut ret = HashMap::with_capacity(ids.len());
+        for chunk in ids.chunks(QUERY_MANY_CHUNK) {
+            let mut query = String::from("SELECT SID, LocalPath FROM Win32_UserProfile WHERE ");
+            for (i, id) in chunk.iter().enumerate() {
+                if i != 0 {
+                    query.push_str(" OR ");
+                }
+                query.push_str("SID = '");
+                query.push_str(&id.0);
+                query.push('\'');
+            }
+            for obj in self.exec_query(&query)? {
+                let obj = obj?;
+                let (Some(sid), Some(path)) = (
+                    get_string(&obj, w!("SID"))?,
+                    get_string(&obj, w!("LocalPath"))?,
+                ) else {
+                    continue;
+                };
+                if let Ok(sid) = sid.into_string() {
+                    ret.insert(
+                        UserIdentifier(sid),
+                        self.options.path_form.apply(path.into()),
+                    );
+                }
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Enumerate every profile on this system, along with the identifier of the user it
+    /// belongs to.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::windows::GetHomeInstance;
+    ///
+    /// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+    /// for profile in GetHomeInstance::new()?.enumerate_profiles()? {
+    ///     let (id, path) = profile?;
+    ///     println!("{}: {}", id.as_ref() as &str, path.display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enumerate_profiles(&self) -> Result<Profiles, GetHomeError> {
+        Ok(Profiles(
+            self.exec_query("SELECT SID, LocalPath FROM Win32_UserProfile")?,
+            self.options.path_form,
+        ))
+    }
+
+    /// List the profiles on this system whose identifier no longer belongs to any account,
+    /// typically because the account was deleted without its profile. These are the profiles
+    /// for which [`UserIdentifier::account_name`] returns `Ok(None)`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::windows::GetHomeInstance;
+    ///
+    /// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+    /// for (id, path) in GetHomeInstance::new()?.orphaned_profiles()? {
+    ///     println!("{} is orphaned ({})", path.display(), id.as_ref() as &str);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn orphaned_profiles(&self) -> Result<Vec<(UserIdentifier, PathBuf)>, GetHomeError> {
+        let mut ret = Vec::new();
+        for profile in self.enumerate_profiles()? {
+            let (id, path) = profile?;
+            if id.account_name()?.is_none() {
+                ret.push((id, path));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Find the local accounts whose name matches a glob pattern, see
+    /// [`find_users`](super::find_users). The pattern is translated to a `LIKE` clause of a query
+    /// on `Win32_UserAccount`, so, as account names on Windows, it is matched case-insensitively.
+    /// Domain accounts are not included, as enumerating them queries a domain controller.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use homedir::windows::GetHomeInstance;
+    ///
+    /// # fn main() -> Result<(), homedir::windows::GetHomeError> {
+    /// for info in GetHomeInstance::new()?.find_users("adm*")? {
+    ///     println!("{:?}: {:?}", info.username(), info.home());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn find_users(&self, pattern: &str) -> Result<Vec<UserInfo>, GetHomeError> {
+        let query = format!(
+            "SELECT SID FROM Win32_UserAccount WHERE LocalAccount = TRUE AND Name LIKE '{}'",
+            like_pattern(pattern)
+        );
+        let mut ret = Vec::new();
+        for obj in self.exec_query(&query)? {
+            if let Some(Ok(sid)) = get_string(&obj?, w!("SID"))?.map(OsString::into_string) {
+                let mut info = UserIdentifier(sid).to_user_info()?;
+                info.home = info.home.map(|v| self.options.path_form.apply(v));
+                ret.push(info);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Execute a WQL query, returning an iterator over the resulting objects.
+    fn exec_query(&self, query: &str) -> Result<QueryObjects, GetHomeError> {
+        let flags = if self.options.semisynchronous {
+            WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY
+        } else {
+            WBEM_FLAG_FORWARD_ONLY
+        };
+        unsafe {
+            let query_enum =
+                self.svc
+                    .ExecQuery(&BSTR::from("WQL"), &BSTR::from(query), flags, None)?;
+            Ok(QueryObjects {
+                objects: query_enum,
+                timeout: self.options.timeout_ms(),
+            })
+        }
+    }
+}
+
+impl QueryOptions {
+    /// Construct the default options.
+    pub fn new() -> Self {
+        Self {
+            timeout: None,
+            semisynchronous: true,
+            locale: None,
+            path_form: PathForm::Unchanged,
+        }
+    }
+
+    /// Set the maximum amount of time to wait for each object returned by a query. If it
+    /// elapses, the query fails with `WBEM_E_TIMED_OUT`. Durations longer than about 24 days are
+    /// treated as infinite.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set whether queries are semisynchronous (the default) or synchronous. Synchronous queries
+    /// block until all the results are available, so the timeout does not apply to them.
+    pub fn semisynchronous(mut self, semisynchronous: bool) -> Self {
+        self.semisynchronous = semisynchronous;
+        self
+    }
+
+    /// Set the locale used to connect to the Windows Management Instrumentation, for instance
+    /// `MS_409`.
+    pub fn locale<S: Into<String>>(mut self, locale: S) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Set the form of the paths returned by queries. By default, they are returned as they are
+    /// stored.
+    pub fn path_form(mut self, path_form: PathForm) -> Self {
+        self.path_form = path_form;
+        self
+    }
+
+    /// The timeout in milliseconds, as passed to `IEnumWbemClassObject::Next`.
+    fn timeout_ms(&self) -> i32 {
+        match self.timeout {
+            Some(v) => i32::try_from(v.as_millis()).unwrap_or(WBEM_INFINITE),
+            None => WBEM_INFINITE,
+        }
+    }
+}
+
+impl Default for QueryOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The maximum number of identifiers included in a single query by
+/// [`GetHomeInstance::query_many`].
+pub const QUERY_MANY_CHUNK: usize = 64;
+
+/// An iterator over the profiles on this system, returned by
+/// [`GetHomeInstance::enumerate_profiles`].
+pub struct Profiles(QueryObjects, PathForm);
+
+impl Iterator for Profiles {
+    type Item = Result<(UserIdentifier, PathBuf), GetHomeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let obj = match self.0.next()? {
+                Ok(v) => v,
+                Err(e) => return Some(Err(e)),
+            };
+            let (sid, path) = match (
+                get_string(&obj, w!("SID")),
+                get_string(&obj, w!("LocalPath")),
+            ) {
+                (Ok(Some(sid)), Ok(Some(path))) => (sid, path),
+                (Err(e), _) | (_, Err(e)) => return Some(Err(e)),
+                // profiles without a path are skipped.
+                _ => continue,
+            };
+            if let Ok(sid) = sid.into_string() {
+                return Some(Ok((UserIdentifier(sid), self.1.apply(path.into()))));
+            }
+        }
+    }
+}
+
+/// An iterator over the objects returned by a WQL query.
+struct QueryObjects {
+    objects: IEnumWbemClassObject,
+    timeout: i32,
+}
+
+impl Iterator for QueryObjects {
+    type Item = Result<IWbemClassObject, GetHomeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut ret = [None; 1];
+        let mut ret_count = 0;
+        unsafe {
+            let hr = self.objects.Next(self.timeout, &mut ret, &mut ret_count);
+            // a timeout is reported with a success code.
+            if hr == HRESULT(WBEM_S_TIMEDOUT.0) {
+                return Some(Err(WinError::from(HRESULT(WBEM_E_TIMED_OUT.0)).into()));
+            }
+            if let Err(e) = hr.ok() {
+                return Some(Err(e.into()));
+            }
+        }
+        if ret_count == 0 {
+            return None;
+        }
+        let [ret] = ret;
+        Some(ret.ok_or(GetHomeError::NullPointerResult))
+    }
+}
+
+/// Translate a glob pattern to the pattern of a WQL `LIKE` clause, escaped to be placed within
+/// single quotes. The characters which `LIKE` treats specially are enclosed in brackets.
+fn like_pattern(pattern: &str) -> String {
+    let mut ret = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        match c {
+            '*' => ret.push('%'),
+            '?' => ret.push('_'),
+            '%' | '_' | '[' | ']' | '^' => {
+                ret.push('[');
+                ret.push(c);
+                ret.push(']');
+            }
+            '\\' | '\'' => {
+                ret.push('\\');
+                ret.push(c);
+            }
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
+/// Read a string property of a WMI object. `None` is returned if the property is null.
+fn get_string(obj: &IWbemClassObject, name: PCWSTR) -> Result<Option<OsString>, GetHomeError> {
+    let mut ret = OsString::new();
+    Ok(get_string_into(obj, name, &mut ret)?.then_some(ret))
+}
+
+/// Read a string property of a WMI object into `buf`, returning whether it is set. `buf` is
+/// left as it is if the property is null.
+fn get_string_into(
+    obj: &IWbemClassObject,
+    name: PCWSTR,
+    buf: &mut OsString,
+) -> Result<bool, GetHomeError> {
+    unsafe {
+        let mut variant = VARIANT::default();
+        obj.Get(name, 0, &mut variant, None, None)?;
+        if variant_is_null(&variant) {
+            return Ok(false);
+        }
+        let bstr = BSTR::try_from(&variant)?;
+        from_wide_into(buf, bstr.as_wide());
+        Ok(true)
+    }
+}
+
+/// Read a boolean property of a WMI object. `None` is returned if the property is null.
+fn get_bool(obj: &IWbemClassObject, name: PCWSTR) -> Result<Option<bool>, GetHomeError> {
+    unsafe {
+        let mut variant = VARIANT::default();
+        obj.Get(name, 0, &mut variant, None, None)?;
+        if variant_is_null(&variant) {
+            return Ok(None);
+        }
+        Ok(Some(bool::try_from(&variant)?))
+    }
+}
+
+/// Read an unsigned integer property of a WMI object. `None` is returned if the property is
+/// null.
+fn get_u32(obj: &IWbemClassObject, name: PCWSTR) -> Result<Option<u32>, GetHomeError> {
+    unsafe {
+        let mut variant = VARIANT::default();
+        obj.Get(name, 0, &mut variant, None, None)?;
+        if variant_is_null(&variant) {
+            return Ok(None);
+        }
+        Ok(Some(u32::try_from(&variant)?))
+    }
+}
+
+/// Parse a [CIM datetime](https://learn.microsoft.com/en-us/windows/win32/wmisdk/cim-datetime),
+/// which has the format `yyyymmddHHMMSS.mmmmmmsUUU`, where `sUUU` is the offset from UTC in
+/// minutes.
+fn parse_cim_datetime(s: &str) -> Option<SystemTime> {
+    let field = |range: std::ops::Range<usize>| s.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+    let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+    let micros = field(15..21)?;
+    let offset = match s.get(21..22)? {
+        "+" => field(22..25)?,
+        "-" => -field(22..25)?,
+        _ => return None,
+    };
+    // the number of days since the unix epoch of the given date, see
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second - offset * 60;
+    let secs = u64::try_from(secs).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs) + Duration::from_micros(micros as u64))
+}
+
+/// Check whether a variant is empty or null.
+fn variant_is_null(variant: &VARIANT) -> bool {
+    const VT_EMPTY: u16 = 0;
+    const VT_NULL: u16 = 1;
+    let vt = unsafe { variant.as_raw().Anonymous.Anonymous.vt };
+    vt == VT_EMPTY || vt == VT_NULL
+}
+
+impl ProfileInfo {
+    /// The path of the local copy of the profile. This is the same path as returned by
+    /// [`GetHomeInstance::query_home`].
+    pub fn local_path(&self) -> &Path {
+        &self.local_path
+    }
+
+    /// The path of the roaming copy of the profile, if one is set.
+    pub fn roaming_path(&self) -> Option<&Path> {
+        self.roaming_path.as_deref()
+    }
+
+    /// Whether the profile is configured to be roaming.
+    pub fn roaming_configured(&self) -> bool {
+        self.roaming_configured
+    }
+
+    /// Whether the profile is currently loaded, for instance because the user is logged on.
+    pub fn loaded(&self) -> bool {
+        self.loaded
+    }
+
+    /// The last time the profile was used, if known.
+    pub fn last_use_time(&self) -> Option<SystemTime> {
+        self.last_use_time
+    }
+
+    /// The status of the profile. This is a combination of the following flags.
+    ///
+    /// | Value | Meaning |
+    /// |-------|---------|
+    /// | 1     | The profile is a temporary profile. |
+    /// | 2     | The profile is a roaming profile. |
+    /// | 4     | The profile is a mandatory profile. |
+    /// | 8     | The profile is corrupted. |
+    ///
+    /// A status of zero means that the status of the profile is unknown.
+    pub fn status(&self) -> u32 {
+        self.status
+    }
+
+    /// Whether the profile belongs to a special system account, such as `LocalSystem`.
+    pub fn special(&self) -> bool {
+        self.special
+    }
+}
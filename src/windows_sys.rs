@@ -0,0 +1,616 @@
+// src/windows_sys.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! This is the lightweight implementation of the crate for Windows systems, enabled by the
+//! `windows-sys` feature, and used whenever the `windows-rs` feature is not enabled. It uses the
+//! [`windows-sys`](https://crates.io/crates/windows-sys) bindings instead of the
+//! [`windows`](https://crates.io/crates/windows) crate, and it does not use the COM library or
+//! the Windows Management Instrumentation. Instead, the profile directory of a user is read from
+//! the `ProfileList` key of the registry, which is where `Win32_UserProfile` obtains it from.
+//! Since the `windows` crate is not compiled, errors from the Windows API are reported as an
+//! `io::Error` or an HRESULT, rather than as a `windows::core::Error`.
+//!
+//! Only the platform-agnostic API is available with this implementation. The
+//! `GetHomeInstance` type, as well as the other WMI-backed functionality, require the
+//! `windows-rs` and `windows-wmi` features instead.
+
+use std::{
+    ffi::OsStr,
+    fmt, io,
+    path::{Path, PathBuf},
+    ptr::null_mut,
+    time::Duration,
+};
+
+use windows_sys::{
+    core::PWSTR,
+    Win32::{
+        Foundation::{
+            CloseHandle, GetLastError, LocalFree, ERROR_FILE_NOT_FOUND, ERROR_INSUFFICIENT_BUFFER,
+            ERROR_MORE_DATA, ERROR_NONE_MAPPED, ERROR_SUCCESS, HANDLE,
+        },
+        Security::{
+            Authorization::{ConvertSidToStringSidW, GetNamedSecurityInfoW, SE_FILE_OBJECT},
+            GetTokenInformation, LookupAccountNameW, LookupAccountSidW, TokenUser, ACL,
+            DACL_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID,
+            SID_NAME_USE, TOKEN_QUERY, TOKEN_USER,
+        },
+        Storage::FileSystem::{GetVolumeInformationW, GetVolumePathNameW},
+        System::{
+            Com::CoTaskMemFree,
+            Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ},
+            Threading::{GetCurrentProcess, OpenProcessToken},
+        },
+        UI::Shell::{FOLDERID_Profile, SHGetKnownFolderPath},
+    },
+};
+
+use crate::wide::{from_wide, from_wide_into, from_wide_ptr, to_wide, wide_ptr_slice};
+use crate::{replace_path_opt, Backend};
+
+/// The facility of the HRESULTs which wrap a Win32 error code.
+const FACILITY_WIN32: u32 = 7;
+
+/// The registry key under `HKEY_LOCAL_MACHINE` which contains a subkey for each profile.
+const PROFILE_LIST_KEY: &str = "SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion\\ProfileList";
+
+/// An identifier for a user.
+///
+/// This contains the text representation of the user's
+/// [SID](https://learn.microsoft.com/en-us/windows-server/identity/ad-ds/manage/understand-security-identifiers).
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UserIdentifier(String);
+
+/// Information about a user, as returned by [`user_info`].
+#[derive(Debug, Clone)]
+pub struct UserInfo {
+    home: Option<PathBuf>,
+}
+
+/// The primary group of a user, as returned by [`primary_group`]. This requires the
+/// `windows-rs` feature, so it is never constructed with this implementation.
+#[derive(Debug, Clone)]
+pub struct PrimaryGroup {
+    _priv: (),
+}
+
+/// This enumeration is the error type returned by this crate's functions
+/// on Windows.
+#[derive(Debug)]
+pub enum GetHomeError {
+    /// This represents an error as obtained from Windows' API.
+    WindowsError(io::Error),
+    /// This represents an HRESULT returned by Windows' API which does not wrap a Win32 error
+    /// code, and so cannot be represented as an `io::Error`.
+    HResult(i32),
+    /// This represents an error when a returned pointer was null when it was not expected to be
+    /// so.
+    NullPointerResult,
+    /// This represents an operation which requires the `windows-rs` feature.
+    Unsupported,
+    /// The relative path passed to [`home_join`](crate::home_join) was absolute or escaped the home directory.
+    InvalidPath,
+}
+
+/// This function will get the home directory of a user given their username. Internally,
+/// it calls [`UserIdentifier::with_username`] followed by [`UserIdentifier::to_home`].
+pub fn home<S: AsRef<str>>(username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    let Some(id) = UserIdentifier::with_username(username)? else {
+        return Ok(None);
+    };
+    id.to_home()
+}
+
+/// This function will get the home directory of a user given their username as an `OsStr`.
+/// Internally, it calls [`UserIdentifier::with_username_os`] followed by
+/// [`UserIdentifier::to_home`].
+pub fn home_os<S: AsRef<OsStr>>(username: S) -> Result<Option<PathBuf>, GetHomeError> {
+    let Some(id) = UserIdentifier::with_username_os(username)? else {
+        return Ok(None);
+    };
+    id.to_home()
+}
+
+/// Get information about a user given their username. If the user does not exist, `Ok(None)`
+/// is returned.
+pub fn user_info<S: AsRef<str>>(username: S) -> Result<Option<UserInfo>, GetHomeError> {
+    let Some(id) = UserIdentifier::with_username(username)? else {
+        return Ok(None);
+    };
+    id.to_user_info().map(Some)
+}
+
+/// Get the primary group of a user given their username. This requires the `windows-rs`
+/// feature, so it always fails with [`GetHomeError::Unsupported`] with this implementation.
+pub fn primary_group<S: AsRef<str>>(_username: S) -> Result<Option<PrimaryGroup>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// The backends used by [`my_home`] and [`home`], in order, see
+/// [`HomeResolver`](crate::HomeResolver).
+pub(crate) fn default_backends() -> Vec<Backend> {
+    vec![Backend::KnownFolder, Backend::Registry]
+}
+
+/// Look up a home directory through a single backend, see
+/// [`HomeResolver`](crate::HomeResolver). `None` stands for the current user.
+pub(crate) fn backend_home(
+    backend: Backend,
+    username: Option<&str>,
+    _timeout: Option<Duration>,
+) -> Result<Option<PathBuf>, GetHomeError> {
+    match (backend, username) {
+        (Backend::KnownFolder, None) => my_home(),
+        (Backend::KnownFolder, Some(_)) => Ok(None),
+        (Backend::Registry, Some(name)) => home(name),
+        (Backend::Registry, None) => UserIdentifier::my_id()?.to_home(),
+        _ => Err(GetHomeError::Unsupported),
+    }
+}
+
+/// Find the accounts whose name matches a glob pattern. This requires the `windows-rs` feature,
+/// so it always fails with [`GetHomeError::Unsupported`] with this implementation.
+pub fn find_users(_pattern: &str) -> Result<Vec<UserInfo>, GetHomeError> {
+    Err(GetHomeError::Unsupported)
+}
+
+/// Get the home directory of the current process' user.
+pub fn my_home() -> Result<Option<PathBuf>, GetHomeError> {
+    unsafe {
+        let mut out: PWSTR = null_mut();
+        let hr = SHGetKnownFolderPath(&FOLDERID_Profile, 0, null_mut(), &mut out);
+        if hr < 0 {
+            CoTaskMemFree(out.cast());
+            return Err(hresult_error(hr));
+        }
+        // there isn't any documented case where this will occur, but who knows.
+        if out.is_null() {
+            return Ok(None);
+        }
+        let s = from_wide_ptr(out).into();
+        CoTaskMemFree(out.cast());
+        Ok(Some(s))
+    }
+}
+
+/// This function will get the current user's profile directory, writing it into `buf`. This is
+/// otherwise identical to [`my_home`], see [`my_home_into`](crate::my_home_into).
+pub fn my_home_into(buf: &mut PathBuf) -> Result<bool, GetHomeError> {
+    unsafe {
+        let mut out: PWSTR = null_mut();
+        let hr = SHGetKnownFolderPath(&FOLDERID_Profile, 0, null_mut(), &mut out);
+        if hr < 0 {
+            CoTaskMemFree(out.cast());
+            return Err(hresult_error(hr));
+        }
+        if out.is_null() {
+            buf.as_mut_os_string().clear();
+            return Ok(false);
+        }
+        from_wide_into(buf.as_mut_os_string(), wide_ptr_slice(out));
+        CoTaskMemFree(out.cast());
+        Ok(true)
+    }
+}
+
+fn last_error() -> GetHomeError {
+    GetHomeError::WindowsError(io::Error::last_os_error())
+}
+
+/// Convert a Win32 error code, as returned by the registry and security functions, into an error.
+fn win32_error(code: u32) -> GetHomeError {
+    GetHomeError::WindowsError(io::Error::from_raw_os_error(code as i32))
+}
+
+/// Convert an HRESULT, as returned by `SHGetKnownFolderPath`, into an error. An HRESULT is not a
+/// Win32 error code, so only those with `FACILITY_WIN32` are unwrapped into one; the others are
+/// kept in [`GetHomeError::HResult`].
+fn hresult_error(hr: i32) -> GetHomeError {
+    if (hr as u32 >> 16) & 0x1FFF == FACILITY_WIN32 {
+        win32_error(hr as u32 & 0xFFFF)
+    } else {
+        GetHomeError::HResult(hr)
+    }
+}
+
+/// Get the error code of the last Win32 function which failed.
+fn last_error_code() -> u32 {
+    unsafe { GetLastError() }
+}
+
+unsafe fn sid_to_string(sid: PSID) -> Result<UserIdentifier, GetHomeError> {
+    let mut str_pointer: PWSTR = null_mut();
+    // convert the SID to string.
+    if ConvertSidToStringSidW(sid, &mut str_pointer) == 0 {
+        return Err(last_error());
+    }
+    let ret = from_wide_ptr(str_pointer);
+    if !LocalFree(str_pointer.cast()).is_null() {
+        return Err(last_error());
+    }
+    // string SIDs only ever contain ASCII characters.
+    ret.into_string()
+        .map(UserIdentifier)
+        .map_err(|_| io::Error::from(io::ErrorKind::InvalidData).into())
+}
+
+/// Get the identifier of the owner of a file or directory, and the number of entries in its
+/// discretionary access control list, or `None` if it has none, with `GetNamedSecurityInfoW`.
+pub(crate) fn file_security(path: &Path) -> Result<(UserIdentifier, Option<u32>), GetHomeError> {
+    let Some(path) = to_wide(path.as_os_str()) else {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput).into());
+    };
+    let mut owner: PSID = null_mut();
+    let mut dacl: *mut ACL = null_mut();
+    let mut descriptor: PSECURITY_DESCRIPTOR = null_mut();
+    unsafe {
+        let status = GetNamedSecurityInfoW(
+            path.as_ptr(),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+            &mut owner,
+            null_mut(),
+            &mut dacl,
+            null_mut(),
+            &mut descriptor,
+        );
+        if status != ERROR_SUCCESS {
+            return Err(win32_error(status));
+        }
+        // both point into the security descriptor, which must be freed after they are used.
+        let ret = sid_to_string(owner).map(|v| (v, dacl.as_ref().map(|v| u32::from(v.AceCount))));
+        LocalFree(descriptor);
+        ret
+    }
+}
+
+/// Get the name of the filesystem of the volume which contains a path, such as `NTFS`, with
+/// `GetVolumeInformationW`.
+pub(crate) fn filesystem_type(path: &Path) -> Option<String> {
+    let path = to_wide(path.as_os_str())?;
+    let mut root = [0u16; 261];
+    let mut name = [0u16; 261];
+    unsafe {
+        if GetVolumePathNameW(path.as_ptr(), root.as_mut_ptr(), root.len() as u32) == 0 {
+            return None;
+        }
+        let ok = GetVolumeInformationW(
+            root.as_ptr(),
+            null_mut(),
+            0,
+            null_mut(),
+            null_mut(),
+            null_mut(),
+            name.as_mut_ptr(),
+            name.len() as u32,
+        );
+        if ok == 0 {
+            return None;
+        }
+    }
+    let len = name.iter().position(|&v| v == 0).unwrap_or(name.len());
+    Some(String::from_utf16_lossy(&name[..len]))
+}
+
+/// Look up the SID of an account with `LookupAccountNameW` and pass it to `f`. If no account
+/// has the given name, `Ok(None)` is returned.
+unsafe fn lookup_account<R, F>(username: &OsStr, f: F) -> Result<Option<R>, GetHomeError>
+where
+    F: FnOnce(PSID) -> Result<R, GetHomeError>,
+{
+    // no account name can contain a NUL character.
+    let Some(username) = to_wide(username) else {
+        return Ok(None);
+    };
+    let mut sid_size = 0;
+    let mut domain_size = 0;
+    let mut peuse: SID_NAME_USE = 0;
+    // get buffer length necessary for SID.
+    if LookupAccountNameW(
+        null_mut(),
+        username.as_ptr(),
+        null_mut(),
+        &mut sid_size,
+        null_mut(),
+        &mut domain_size,
+        &mut peuse,
+    ) == 0
+    {
+        let e = last_error_code();
+        if e == ERROR_NONE_MAPPED {
+            return Ok(None);
+        } else if e != ERROR_INSUFFICIENT_BUFFER {
+            return Err(win32_error(e));
+        }
+    }
+    if sid_size == 0 {
+        return Err(GetHomeError::NullPointerResult);
+    }
+    // a u64 buffer keeps the SID suitably aligned.
+    let mut sid_buf = vec![0u64; (sid_size as usize).div_ceil(8)];
+    // the domain is unfortunately necessary, otherwise the function will not operate
+    // correctly.
+    let mut domain = vec![0u16; domain_size as usize];
+    let psid: PSID = sid_buf.as_mut_ptr().cast();
+    if LookupAccountNameW(
+        null_mut(),
+        username.as_ptr(),
+        psid,
+        &mut sid_size,
+        domain.as_mut_ptr(),
+        &mut domain_size,
+        &mut peuse,
+    ) == 0
+    {
+        return Err(last_error());
+    }
+    f(psid).map(Some)
+}
+
+/// Get the domain and the name of the account with a SID, using `LookupAccountSidW`.
+unsafe fn lookup_account_sid(psid: PSID) -> Result<(String, String), GetHomeError> {
+    let mut name_size = 0;
+    let mut domain_size = 0;
+    let mut peuse: SID_NAME_USE = 0;
+    // get the buffer lengths necessary for the name and the domain.
+    if LookupAccountSidW(
+        null_mut(),
+        psid,
+        null_mut(),
+        &mut name_size,
+        null_mut(),
+        &mut domain_size,
+        &mut peuse,
+    ) == 0
+    {
+        let e = last_error_code();
+        if e != ERROR_INSUFFICIENT_BUFFER {
+            return Err(win32_error(e));
+        }
+    }
+    let mut name = vec![0u16; name_size as usize];
+    let mut domain = vec![0u16; domain_size as usize];
+    if LookupAccountSidW(
+        null_mut(),
+        psid,
+        name.as_mut_ptr(),
+        &mut name_size,
+        domain.as_mut_ptr(),
+        &mut domain_size,
+        &mut peuse,
+    ) == 0
+    {
+        return Err(last_error());
+    }
+    Ok((
+        from_wide(&domain).to_string_lossy().into_owned(),
+        from_wide(&name).to_string_lossy().into_owned(),
+    ))
+}
+
+impl UserIdentifier {
+    /// Construct a user identifier from the text representation of a SID, as received by the C
+    /// API. This only checks that it has the form `S-1-...`, not that the user exists.
+    #[cfg(feature = "ffi")]
+    pub(crate) fn from_sid_string(sid: &str) -> Option<Self> {
+        let valid = sid.strip_prefix("S-1-").is_some_and(|v| {
+            v.split('-')
+                .all(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()))
+        });
+        valid.then(|| Self(sid.to_owned()))
+    }
+
+    /// Get the user identifier of a user given their username.
+    pub fn with_username<S: AsRef<str>>(
+        username: S,
+    ) -> Result<Option<UserIdentifier>, GetHomeError> {
+        Self::with_username_os(username.as_ref())
+    }
+
+    /// Get the user identifier of a user given their username as an `OsStr`.
+    pub fn with_username_os<S: AsRef<OsStr>>(
+        username: S,
+    ) -> Result<Option<UserIdentifier>, GetHomeError> {
+        unsafe { lookup_account(username.as_ref(), |psid| sid_to_string(psid)) }
+    }
+
+    /// Get the user identifier of a user given their username, along with the canonical name of
+    /// the account, as returned by `LookupAccountSidW`. Account names are always matched
+    /// case-insensitively on Windows. If the username is qualified with a domain, as in
+    /// `DOMAIN\user`, the name returned is qualified as well.
+    pub fn with_username_ignore_case<S: AsRef<str>>(
+        username: S,
+    ) -> Result<Option<(UserIdentifier, String)>, GetHomeError> {
+        let username = username.as_ref();
+        unsafe {
+            lookup_account(username.as_ref(), |psid| {
+                let (domain, name) = lookup_account_sid(psid)?;
+                let name = if username.contains('\\') {
+                    format!("{domain}\\{name}")
+                } else {
+                    name
+                };
+                Ok((sid_to_string(psid)?, name))
+            })
+        }
+    }
+
+    /// This function will get the home directory of a user given their identifier.
+    /// The path is read from the `ProfileImagePath` value of the user's key in the
+    /// `ProfileList` registry key.
+    pub fn to_home(&self) -> Result<Option<PathBuf>, GetHomeError> {
+        let Some(subkey) = to_wide(format!("{PROFILE_LIST_KEY}\\{}", self.0).as_ref()) else {
+            return Ok(None);
+        };
+        let value = to_wide("ProfileImagePath".as_ref()).unwrap();
+        unsafe {
+            let mut size = 0;
+            loop {
+                let mut buf = vec![0u16; (size as usize).div_ceil(2)];
+                let ptr = if buf.is_empty() {
+                    null_mut()
+                } else {
+                    buf.as_mut_ptr().cast()
+                };
+                // RRF_RT_REG_SZ also accepts REG_EXPAND_SZ values, which are expanded.
+                let status = RegGetValueW(
+                    HKEY_LOCAL_MACHINE,
+                    subkey.as_ptr(),
+                    value.as_ptr(),
+                    RRF_RT_REG_SZ,
+                    null_mut(),
+                    ptr,
+                    &mut size,
+                );
+                match status {
+                    ERROR_SUCCESS if !buf.is_empty() => {
+                        return Ok(Some(from_wide(&buf).into()));
+                    }
+                    ERROR_SUCCESS | ERROR_MORE_DATA => continue,
+                    ERROR_FILE_NOT_FOUND => return Ok(None),
+                    e => return Err(win32_error(e)),
+                }
+            }
+        }
+    }
+
+    /// Get the home directory of a user given their identifier, writing it into `buf`. This is
+    /// otherwise identical to [`UserIdentifier::to_home`], see
+    /// [`my_home_into`](crate::my_home_into).
+    pub fn to_home_into(&self, buf: &mut PathBuf) -> Result<bool, GetHomeError> {
+        let home = self.to_home()?;
+        Ok(replace_path_opt(buf, home.as_deref()))
+    }
+
+    /// Get information about the user with this identifier. See [`user_info`].
+    pub fn to_user_info(&self) -> Result<UserInfo, GetHomeError> {
+        Ok(UserInfo {
+            home: self.to_home()?,
+        })
+    }
+
+    /// Get the identifier of this process' user.
+    pub fn my_id() -> Result<UserIdentifier, GetHomeError> {
+        unsafe {
+            let mut token_handle: HANDLE = null_mut();
+            // get a token to query information about the current process. this handle must be
+            // closed manually with CloseHandle, as seen below.
+            if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token_handle) == 0 {
+                return Err(last_error());
+            }
+            let ret = token_user(token_handle);
+            if CloseHandle(token_handle) == 0 && ret.is_ok() {
+                return Err(last_error());
+            }
+            ret
+        }
+    }
+}
+
+unsafe fn token_user(token_handle: HANDLE) -> Result<UserIdentifier, GetHomeError> {
+    let mut buffer_size = 0;
+    // get the length of the buffer requried for this query.
+    if GetTokenInformation(token_handle, TokenUser, null_mut(), 0, &mut buffer_size) == 0 {
+        let e = last_error_code();
+        if e != ERROR_INSUFFICIENT_BUFFER {
+            return Err(win32_error(e));
+        }
+    }
+    if buffer_size == 0 {
+        return Err(GetHomeError::NullPointerResult);
+    }
+    // a u64 buffer keeps the TOKEN_USER structure suitably aligned.
+    let mut buf = vec![0u64; (buffer_size as usize).div_ceil(8)];
+    if GetTokenInformation(
+        token_handle,
+        TokenUser,
+        buf.as_mut_ptr().cast(),
+        buffer_size,
+        &mut buffer_size,
+    ) == 0
+    {
+        return Err(last_error());
+    }
+    sid_to_string((*buf.as_ptr().cast::<TOKEN_USER>()).User.Sid)
+}
+
+impl PrimaryGroup {
+    /// The string representation of the group's SID.
+    pub fn sid(&self) -> &str {
+        ""
+    }
+
+    /// The name of the group.
+    pub fn name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl UserInfo {
+    /// The profile directory of the user, or `None` if they do not have a profile.
+    pub fn home(&self) -> Option<&Path> {
+        self.home.as_deref()
+    }
+
+    /// The login shell of the user. Windows accounts have no login shell, so this is always
+    /// `None`. It exists for consistency with the other platforms.
+    pub fn shell(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The name of the account. This requires the `windows-rs` feature, so it is always `None`
+    /// with this implementation.
+    pub fn username(&self) -> Option<&str> {
+        None
+    }
+
+    /// The full name of the user. This requires the `windows-rs` feature, so it is always
+    /// `None` with this implementation.
+    pub fn full_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl From<io::Error> for GetHomeError {
+    fn from(value: io::Error) -> Self {
+        Self::WindowsError(value)
+    }
+}
+
+impl fmt::Display for GetHomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WindowsError(e) => write!(f, "windows error: {e}"),
+            Self::HResult(hr) => write!(f, "windows error: HRESULT {:#010x}", *hr as u32),
+            Self::NullPointerResult => write!(f, "unexpected null pointer result"),
+            Self::Unsupported => write!(f, "operation requires the windows-rs feature"),
+            Self::InvalidPath => write!(f, "the path is absolute or escapes the home directory"),
+        }
+    }
+}
+
+impl std::error::Error for GetHomeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::WindowsError(e) => Some(e),
+            Self::HResult(_) | Self::NullPointerResult | Self::Unsupported | Self::InvalidPath => {
+                None
+            }
+        }
+    }
+}
+
+impl AsRef<str> for UserIdentifier {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<UserIdentifier> for String {
+    fn from(value: UserIdentifier) -> Self {
+        value.0
+    }
+}
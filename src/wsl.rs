@@ -0,0 +1,217 @@
+// src/wsl.rs
+//
+// Copyright (C) 2023-2024 James Petersen <m@jamespetersen.ca>
+// Licensed under Apache 2.0 OR MIT. See LICENSE-APACHE or LICENSE-MIT
+
+//! Helpers to translate paths between Windows and the Windows Subsystem for Linux (WSL).
+//!
+//! Inside WSL, the Windows drives are mounted under `/mnt/` by default, so that
+//! `C:\Users\alice` is `/mnt/c/Users/alice`. From Windows, the filesystem of a distribution is
+//! shared as `\\wsl$\<distribution>`, so that `/home/alice` in the `Ubuntu` distribution is
+//! `\\wsl$\Ubuntu\home\alice`.
+//!
+//! The paths of the other system are handled as strings, as a [`Path`](std::path::Path) would
+//! interpret them with the syntax of the system this program runs on. These functions do not
+//! access the filesystem, except for `distro_home` on Windows.
+//!
+//! # Example
+//! ```no_run
+//! use homedir::wsl::{windows_to_wsl, wsl_to_windows, DEFAULT_AUTOMOUNT_ROOT};
+//!
+//! assert_eq!(
+//!     Some("/mnt/c/Users/alice".to_owned()),
+//!     windows_to_wsl(r"C:\Users\alice", DEFAULT_AUTOMOUNT_ROOT)
+//! );
+//! assert_eq!(
+//!     Some(r"C:\Users\alice".to_owned()),
+//!     wsl_to_windows("/mnt/c/Users/alice", DEFAULT_AUTOMOUNT_ROOT)
+//! );
+//! ```
+
+/// The directory under which WSL mounts the Windows drives, unless the `root` setting of the
+/// `[automount]` section of `/etc/wsl.conf` is set.
+pub const DEFAULT_AUTOMOUNT_ROOT: &str = "/mnt/";
+
+/// Translate an absolute Windows path on a drive, such as `C:\Users\alice`, to the path of the
+/// same file inside WSL, such as `/mnt/c/Users/alice`, given the directory under which the
+/// drives are mounted. `None` is returned if the path is not an absolute path on a drive, for
+/// instance if it is a network path.
+pub fn windows_to_wsl(path: &str, automount_root: &str) -> Option<String> {
+    let path = path.strip_prefix(r"\\?\").unwrap_or(path);
+    let mut chars = path.chars();
+    let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+    if chars.next() != Some(':') {
+        return None;
+    }
+    let rest = chars.as_str();
+    if !rest.starts_with(['\\', '/']) {
+        return None;
+    }
+    let mut ret = automount_root.trim_end_matches('/').to_owned();
+    ret.push('/');
+    ret.push(drive.to_ascii_lowercase());
+    for component in rest.split(['\\', '/']).filter(|v| !v.is_empty()) {
+        ret.push('/');
+        ret.push_str(component);
+    }
+    Some(ret)
+}
+
+/// Translate a path inside WSL which is on a mounted Windows drive, such as
+/// `/mnt/c/Users/alice`, to the Windows path of the same file, such as `C:\Users\alice`, given
+/// the directory under which the drives are mounted. `None` is returned if the path is not on a
+/// mounted drive; see [`wsl_to_unc`] for the other paths.
+pub fn wsl_to_windows(path: &str, automount_root: &str) -> Option<String> {
+    let root = automount_root.trim_end_matches('/');
+    let rest = path.strip_prefix(root)?.strip_prefix('/')?;
+    let (drive, rest) = rest.split_once('/').unwrap_or((rest, ""));
+    let mut chars = drive.chars();
+    let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+    if chars.next().is_some() {
+        return None;
+    }
+    let mut ret = format!("{}:", drive.to_ascii_uppercase());
+    let mut components = rest.split('/').filter(|v| !v.is_empty()).peekable();
+    if components.peek().is_none() {
+        ret.push('\\');
+    }
+    for component in components {
+        ret.push('\\');
+        ret.push_str(component);
+    }
+    Some(ret)
+}
+
+/// Translate an absolute path inside a WSL distribution, such as `/home/alice`, to the network
+/// path through which Windows accesses it, such as `\\wsl$\Ubuntu\home\alice`. `None` is
+/// returned if the path is not absolute.
+pub fn wsl_to_unc(path: &str, distro: &str) -> Option<String> {
+    let rest = path.strip_prefix('/')?;
+    let mut ret = format!(r"\\wsl$\{distro}");
+    for component in rest.split('/').filter(|v| !v.is_empty()) {
+        ret.push('\\');
+        ret.push_str(component);
+    }
+    Some(ret)
+}
+
+/// Locate the home directory of a user of a WSL distribution from Windows, such as
+/// `\\wsl$\Ubuntu\home\alice`, by reading the `/etc/passwd` file of the distribution. The
+/// distribution is started if it is not running. If the user cannot be found, `Ok(None)` is
+/// returned.
+///
+/// Users provided by NSS modules other than `files` cannot be found this way.
+///
+/// # Example
+/// ```no_run
+/// use homedir::wsl::distro_home;
+///
+/// # fn main() -> std::io::Result<()> {
+/// if let Some(home) = distro_home("Ubuntu", "alice")? {
+///     println!("{}", home.display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(windows)]
+pub fn distro_home(distro: &str, username: &str) -> std::io::Result<Option<std::path::PathBuf>> {
+    let passwd = std::fs::read_to_string(format!(r"\\wsl$\{distro}\etc\passwd"))?;
+    Ok(passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.len() >= 7 && fields[0] == username)
+        .and_then(|fields| wsl_to_unc(fields[5], distro))
+        .map(Into::into))
+}
+
+/// Check whether this process is running inside WSL, from the kernel release in
+/// `/proc/sys/kernel/osrelease`, which contains `microsoft` (or `Microsoft` with WSL 1).
+///
+/// # Example
+/// ```no_run
+/// use homedir::wsl::{is_wsl, my_windows_home};
+///
+/// # fn main() -> std::io::Result<()> {
+/// if is_wsl() {
+///     println!("{:?}", my_windows_home()?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(target_os = "linux")]
+pub fn is_wsl() -> bool {
+    std::fs::read_to_string("/proc/sys/kernel/osrelease")
+        .is_ok_and(|v| v.to_ascii_lowercase().contains("microsoft"))
+}
+
+/// Get the directory under which WSL mounts the Windows drives, from the `root` setting of the
+/// `[automount]` section of `/etc/wsl.conf`. If it is not set, or the file cannot be read,
+/// [`DEFAULT_AUTOMOUNT_ROOT`] is returned.
+#[cfg(target_os = "linux")]
+pub fn automount_root() -> String {
+    let Ok(conf) = std::fs::read_to_string("/etc/wsl.conf") else {
+        return DEFAULT_AUTOMOUNT_ROOT.to_owned();
+    };
+    let mut in_automount = false;
+    for line in conf.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            in_automount = section.trim() == "automount";
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| in_automount) {
+            if key.trim() == "root" {
+                let value = value.trim().trim_matches('"');
+                if value.starts_with('/') {
+                    return value.to_owned();
+                }
+            }
+        }
+    }
+    DEFAULT_AUTOMOUNT_ROOT.to_owned()
+}
+
+/// Get the Windows profile directory of the user running WSL, as a path inside WSL such as
+/// `/mnt/c/Users/alice`. `Ok(None)` is returned outside of WSL, or if the profile directory is
+/// not on a mounted drive.
+///
+/// If `USERPROFILE` is shared with WSL through `WSLENV` with the `/p` flag, it is used.
+/// Otherwise, `%USERPROFILE%` is read by running `cmd.exe` through the Windows interoperability,
+/// which fails if it is disabled.
+#[cfg(target_os = "linux")]
+pub fn my_windows_home() -> std::io::Result<Option<std::path::PathBuf>> {
+    use std::{io, path::Path, process::Command};
+
+    if !is_wsl() {
+        return Ok(None);
+    }
+    // with the `/p` flag of WSLENV, the variable is already translated to a WSL path.
+    if let Some(v) = crate::env_var("USERPROFILE").filter(|v| Path::new(v).is_absolute()) {
+        return Ok(Some(v.into()));
+    }
+    let root = automount_root();
+    let fallback = format!("{}/c/Windows/System32/cmd.exe", root.trim_end_matches('/'));
+    let mut output = None;
+    for cmd in ["cmd.exe", fallback.as_str()] {
+        // cmd.exe warns on stderr if its working directory is in the WSL filesystem, which
+        // is ignored.
+        match Command::new(cmd)
+            .args(["/d", "/c", "echo %USERPROFILE%"])
+            .output()
+        {
+            Ok(v) => {
+                output = Some(v);
+                break;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    let Some(output) = output.filter(|v| v.status.success()) else {
+        return Ok(None);
+    };
+    let profile = String::from_utf8_lossy(&output.stdout);
+    let profile = profile.trim();
+    // cmd.exe leaves variables which are not set as they are.
+    if profile.is_empty() || profile == "%USERPROFILE%" {
+        return Ok(None);
+    }
+    Ok(windows_to_wsl(profile, &root).map(Into::into))
+}